@@ -0,0 +1,145 @@
+//! Tools for understanding and polishing puzzles.
+
+use crate::board::{Board, Entry};
+use crate::logic::Grid;
+use crate::solver;
+
+/// Find the givens which could be removed without making the puzzle ambiguous.
+///
+/// Each of the returned indices can be cleared on its own and the puzzle will still have exactly
+/// one solution. Clearing several of them at once might not work, though, since one clue can be
+/// redundant only because of another. If the puzzle doesn't have exactly one solution to begin
+/// with, nothing is reported.
+pub fn redundant_givens(board: &Board) -> Vec<usize> {
+    if solver::count_solutions(board, 2) != 1 {
+        return Vec::new();
+    }
+
+    let mut board = board.clone();
+    (0..81)
+        .filter(|&index| {
+            let Some(given) = board.get_cell_index(index) else {
+                return false;
+            };
+            board.set_cell_index(index, None);
+            let redundant = solver::count_solutions(&board, 2) == 1;
+            board.set_cell_index(index, Some(given));
+            redundant
+        })
+        .collect()
+}
+
+/// Work out how deep the reasoning behind each cell goes.
+///
+/// The board is solved by logic in rounds. In each round, every single which is available at the
+/// start of the round is placed at once. If there are none, one round is spent on a technique that
+/// eliminates candidates instead. The depth of a cell is the round that it was placed in, so givens
+/// have a depth of 0. Cells which logic can't resolve at all have a depth of [`None`].
+pub fn resolution_depths(board: &Board) -> [Option<usize>; 81] {
+    let mut depths: [Option<usize>; 81] =
+        std::array::from_fn(|index| board.get_cell_index(index).map(|_| 0));
+    if !board.is_valid() {
+        return depths;
+    }
+
+    let mut grid = Grid::new(board);
+    let mut round = 0;
+    while !grid.is_solved() && !grid.is_stuck() {
+        round += 1;
+        let singles = available_singles(&grid);
+        if singles.is_empty() {
+            let Some(deduction) = grid.find_deduction() else {
+                break;
+            };
+            grid.apply(&deduction);
+            continue;
+        }
+
+        for (index, entry) in singles {
+            grid.place(index, entry);
+            depths[index] = Some(round);
+        }
+    }
+    depths
+}
+
+/// Find every naked or hidden single on the grid, without placing any of them.
+fn available_singles(grid: &Grid) -> Vec<(usize, Entry)> {
+    (0..81)
+        .filter(|&index| grid.entry(index).is_none())
+        .filter_map(|index| {
+            let candidates = grid.candidates(index);
+            let hidden = || {
+                candidates.iter().find(|&entry| {
+                    grid.variant()
+                        .units_containing(index)
+                        .into_iter()
+                        .any(|unit| {
+                            grid.variant()
+                                .cells(unit)
+                                .into_iter()
+                                .filter(|&other| grid.candidates(other).contains(entry))
+                                .eq([index])
+                        })
+                })
+            };
+            candidates
+                .single()
+                .or_else(hidden)
+                .map(|entry| (index, entry))
+        })
+        .collect()
+}
+
+/// Find the cells which need the deepest reasoning.
+///
+/// These are the cells which logic can't resolve at all, if there are any, or otherwise the cells
+/// which are placed in the last round of [`resolution_depths`]. A full board has no such cells.
+pub fn hardest_cells(board: &Board) -> Vec<usize> {
+    let depths = resolution_depths(board);
+    let unresolved: Vec<usize> = (0..81).filter(|&index| depths[index].is_none()).collect();
+    if !unresolved.is_empty() {
+        return unresolved;
+    }
+
+    let deepest = depths.iter().flatten().copied().max().unwrap_or(0);
+    (0..81)
+        .filter(|&index| deepest > 0 && depths[index] == Some(deepest))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redundant_givens() {
+        let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+
+        // A full grid stays unique no matter which single cell is cleared.
+        let solution = solver::solve_copy(&puzzle).unwrap();
+        assert_eq!(redundant_givens(&solution).len(), 81);
+
+        for index in redundant_givens(&puzzle) {
+            let mut fewer = puzzle.clone();
+            fewer.set_cell_index(index, None);
+            assert_eq!(solver::count_solutions(&fewer, 2), 1);
+        }
+        assert!(redundant_givens(&Board::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_hardest_cells() {
+        let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let depths = resolution_depths(&puzzle);
+        assert!(depths.iter().all(Option::is_some));
+
+        let hardest = hardest_cells(&puzzle);
+        assert!(!hardest.is_empty());
+        let deepest = depths[hardest[0]];
+        assert!(depths.iter().all(|&depth| depth <= deepest));
+
+        let solution = solver::solve_copy(&puzzle).unwrap();
+        assert!(hardest_cells(&solution).is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+//! A stochastic solver based on simulated annealing.
+//!
+//! Unlike the backtracking search, this solver never leaves a cell empty. Every big cell is filled
+//! with the digits it is missing, so the big cells are always correct, and the solver then swaps
+//! pairs of cells within a big cell to reduce the number of clashes in the rows and columns.
+//! Swaps which make things worse are sometimes accepted too, with a probability that shrinks as
+//! the "temperature" cools, so that the solver can climb out of local minima.
+//!
+//! There is no guarantee that this finds a solution, and it has no way of telling that a board is
+//! unsolvable, so it is more of a curiosity than a practical solver. It is fun to watch, though,
+//! which is what [`Annealing`] is for.
+
+use raylib::prelude::*;
+
+use crate::board::{Board, BoardView, Entry, Unit, Variant};
+use crate::locale::Message;
+use crate::random::{Rng, Seed};
+use crate::ui::{self, Widget};
+
+/// The temperature that the solver starts at, and goes back to whenever it gets stuck.
+const INITIAL_TEMPERATURE: f64 = 0.5;
+
+/// How much the temperature is multiplied by after every step.
+const COOLING_RATE: f64 = 0.9999;
+
+/// How many steps can go by without a new best cost before the solver reheats.
+const REHEAT_AFTER: u64 = 20_000;
+
+/// The state of a simulated annealing solve.
+#[derive(Debug, Clone)]
+pub struct Annealer {
+    cells: [Entry; 81],
+
+    /// The cells which can be swapped around in each big cell. Givens are left out.
+    free: [Vec<usize>; 9],
+
+    /// How many times each digit appears in each row and each column.
+    row_counts: [[u8; 9]; 9],
+    column_counts: [[u8; 9]; 9],
+
+    cost: u32,
+    best_cost: u32,
+    temperature: f64,
+    steps: u64,
+    steps_since_best: u64,
+    rng: Rng,
+}
+
+impl Annealer {
+    /// Fill in a board to start annealing from.
+    ///
+    /// The empty cells of each big cell are filled with that big cell's missing digits in a random
+    /// order. If the givens already clash with each other, or the board has extra rules which the
+    /// annealer doesn't know how to score, [`None`] is returned.
+    pub fn new(board: &Board, seed: Seed) -> Option<Annealer> {
+        let standard = board.variant() == Variant::STANDARD && board.constraints().is_empty();
+        if !board.is_valid() || !standard {
+            return None;
+        }
+
+        let mut rng = Rng::new(seed);
+        let mut cells = [Entry::One; 81];
+        let mut free: [Vec<usize>; 9] = Default::default();
+        for (big_cell, free) in free.iter_mut().enumerate() {
+            let unit_cells = Unit::Box(big_cell).cells();
+            let mut missing: Vec<Entry> = Entry::ALL
+                .into_iter()
+                .filter(|&entry| {
+                    unit_cells
+                        .iter()
+                        .all(|&index| board.get_cell_index(index) != Some(entry))
+                })
+                .collect();
+            rng.shuffle(&mut missing);
+
+            for index in unit_cells {
+                cells[index] = match board.get_cell_index(index) {
+                    Some(entry) => entry,
+                    None => {
+                        free.push(index);
+                        missing.pop()?
+                    }
+                };
+            }
+        }
+
+        let mut annealer = Annealer {
+            cells,
+            free,
+            row_counts: [[0; 9]; 9],
+            column_counts: [[0; 9]; 9],
+            cost: 0,
+            best_cost: 0,
+            temperature: INITIAL_TEMPERATURE,
+            steps: 0,
+            steps_since_best: 0,
+            rng,
+        };
+        for index in 0..81 {
+            annealer.count(index, 1);
+        }
+        annealer.cost = (0..9)
+            .map(|line| annealer.row_cost(line) + annealer.column_cost(line))
+            .sum();
+        annealer.best_cost = annealer.cost;
+        Some(annealer)
+    }
+
+    /// Get the number of clashes left. Each missing digit in a row or column counts as one. A
+    /// cost of zero means that the board is solved.
+    pub const fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// Check whether the board has been solved.
+    pub const fn is_solved(&self) -> bool {
+        self.cost == 0
+    }
+
+    /// Get the number of steps taken so far.
+    pub const fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Write the current state of every cell into a board. Cells which already hold the right
+    /// digit are left alone, so the givens stay givens.
+    pub fn write_to(&self, board: &mut Board) {
+        for (index, &entry) in self.cells.iter().enumerate() {
+            if board.get_cell_index(index) != Some(entry) {
+                board.set_cell_index(index, Some(entry));
+            }
+        }
+    }
+
+    /// Try swapping a random pair of cells in a random big cell.
+    ///
+    /// The swap is kept if it doesn't make the board worse, or by chance depending on the
+    /// temperature if it does. Once the board is solved, this does nothing. Returns whether the
+    /// board is solved.
+    pub fn step(&mut self) -> bool {
+        if self.is_solved() {
+            return true;
+        }
+        self.steps += 1;
+
+        let big_cell = self.rng.below(9);
+        let free = &self.free[big_cell];
+        if free.len() >= 2 {
+            let first = free[self.rng.below(free.len())];
+            let second = free[self.rng.below(free.len())];
+            if first != second {
+                self.try_swap(first, second);
+            }
+        }
+
+        self.temperature *= COOLING_RATE;
+        if self.cost < self.best_cost {
+            self.best_cost = self.cost;
+            self.steps_since_best = 0;
+        } else {
+            self.steps_since_best += 1;
+            if self.steps_since_best > REHEAT_AFTER {
+                self.temperature = INITIAL_TEMPERATURE;
+                self.steps_since_best = 0;
+            }
+        }
+        self.is_solved()
+    }
+
+    /// Swap two cells, then swap them back unless the change is accepted.
+    fn try_swap(&mut self, first: usize, second: usize) {
+        let before = self.lines_cost(first, second);
+        self.swap(first, second);
+        let after = self.lines_cost(first, second);
+
+        let accepted = after <= before || {
+            let worse = f64::from(after - before);
+            self.rng.next_f64() < (-worse / self.temperature).exp()
+        };
+        if accepted {
+            self.cost = self.cost + after - before;
+        } else {
+            self.swap(first, second);
+        }
+    }
+
+    fn swap(&mut self, first: usize, second: usize) {
+        self.count(first, -1);
+        self.count(second, -1);
+        self.cells.swap(first, second);
+        self.count(first, 1);
+        self.count(second, 1);
+    }
+
+    /// Add or remove the entry at the supplied index from the counts of its row and column.
+    fn count(&mut self, index: usize, delta: i8) {
+        let digit = self.cells[index] as usize;
+        let row = &mut self.row_counts[index / 9][digit];
+        *row = row.wrapping_add_signed(delta);
+        let column = &mut self.column_counts[index % 9][digit];
+        *column = column.wrapping_add_signed(delta);
+    }
+
+    /// Add up the costs of the rows and columns containing two cells, counting each line once.
+    fn lines_cost(&self, first: usize, second: usize) -> u32 {
+        let mut cost = self.row_cost(first / 9) + self.column_cost(first % 9);
+        if second / 9 != first / 9 {
+            cost += self.row_cost(second / 9);
+        }
+        if second % 9 != first % 9 {
+            cost += self.column_cost(second % 9);
+        }
+        cost
+    }
+
+    fn row_cost(&self, row: usize) -> u32 {
+        self.row_counts[row]
+            .iter()
+            .filter(|&&count| count == 0)
+            .count() as u32
+    }
+
+    fn column_cost(&self, column: usize) -> u32 {
+        self.column_counts[column]
+            .iter()
+            .filter(|&&count| count == 0)
+            .count() as u32
+    }
+}
+
+/// Solve a Sudoku board by simulated annealing.
+///
+/// The solver gives up after `max_steps` steps. If it finds a solution before then, the board is
+/// mutated into the solved state and `true` is returned. Otherwise the board is left unchanged and
+/// `false` is returned. The same board and seed always take the same path.
+pub fn solve_annealing(board: &mut Board, seed: Seed, max_steps: u64) -> bool {
+    let Some(mut annealer) = Annealer::new(board, seed) else {
+        return false;
+    };
+    while !annealer.step() {
+        if annealer.steps() >= max_steps {
+            return false;
+        }
+    }
+    annealer.write_to(board);
+    true
+}
+
+/// An annealing solve of a copy of a puzzle, for watching in the window.
+///
+/// Every empty cell of the copy is filled in from the start, so the clashes can be seen clearing
+/// up as the temperature cools. Like a [`Race`](crate::race::Race), it leaves the board in the tab
+/// alone.
+#[derive(Debug)]
+pub struct Annealing {
+    annealer: Annealer,
+    board: Board,
+    view: BoardView,
+}
+
+impl Annealing {
+    /// Start annealing a copy of the supplied puzzle. Anything filled in after the givens is left
+    /// out. Boards that [`Annealer::new`] can't start from give [`None`].
+    pub fn new(board: &Board, seed: Seed) -> Option<Annealing> {
+        let mut board = board.original();
+        let annealer = Annealer::new(&board, seed)?;
+        annealer.write_to(&mut board);
+        Some(Annealing {
+            annealer,
+            board,
+            view: BoardView::new(),
+        })
+    }
+
+    /// Take up to `n` steps, stopping early once the board is solved. Returns whether it is.
+    pub fn step(&mut self, n: usize) -> bool {
+        let solved = (0..n).any(|_| self.annealer.step()) || self.annealer.is_solved();
+        self.annealer.write_to(&mut self.board);
+        solved
+    }
+
+    /// Get the copy of the puzzle, as it stands after the last step.
+    pub const fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Draw the copy of the puzzle into `rect`.
+    pub fn draw_board(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        self.view.draw(d, &self.board, rect);
+    }
+
+    /// Set the color that the digits filled in by the annealer are drawn in.
+    pub fn set_filled_color(&mut self, color: Color) {
+        self.view.set_filled_color(color);
+    }
+}
+
+impl Widget for Annealing {
+    /// Draw how many clashes are left and how many steps have been taken.
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        d.draw_rectangle_rec(rect, Color::LIGHTGRAY);
+        let text = Message::AnnealingProgress {
+            clashes: self.annealer.cost(),
+            steps: self.annealer.steps(),
+        }
+        .to_string();
+        ui::draw_text_centered(d, &text, rect, ui::font_size() * 0.6, Color::BLACK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_annealing() {
+        let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let mut board = puzzle.clone();
+        assert!(solve_annealing(&mut board, Seed(1), 2_000_000));
+        assert!(board.is_valid());
+        assert!(board.first_unfilled_index().is_none());
+        for index in 0..81 {
+            if let Some(given) = puzzle.get_cell_index(index) {
+                assert_eq!(board.get_cell_index(index), Some(given));
+            }
+        }
+    }
+
+    #[test]
+    fn test_annealing() {
+        let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let mut annealing = Annealing::new(&puzzle, Seed(1)).unwrap();
+        assert!(annealing.board().first_unfilled_index().is_none());
+        assert!(!annealing.step(0));
+        while !annealing.step(10_000) {}
+        assert!(annealing.board().is_valid());
+        assert!((0..81).all(|index| annealing.board().is_given(index) == puzzle.is_given(index)));
+    }
+}
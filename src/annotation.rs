@@ -0,0 +1,220 @@
+//! Drawing the reasoning behind logical steps on the board.
+//!
+//! When the logical solver takes a step, or a hint is shown, the candidates that the technique
+//! relied on are circled and the candidates it rules out are crossed out. The corners of an X-wing
+//! are joined up too. Seeing the pattern on the board makes the techniques much easier to learn
+//! than their names alone. Annotations fade away after a moment, so they don't pile up while the
+//! solver runs.
+
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+
+use crate::board::{Board, Candidates, Entry};
+use crate::logic::{Action, Deduction, Technique};
+use crate::ui;
+
+/// How long an annotation stays on the board.
+const ANNOTATION_LENGTH: Duration = Duration::from_millis(1500);
+
+/// The color of the circles around candidates that a technique relied on.
+const CIRCLE_COLOR: Color = Color::new(0, 150, 80, 255);
+
+/// The color of the crosses over candidates that a technique rules out.
+const CROSS_COLOR: Color = Color::new(220, 40, 40, 255);
+
+/// The reasoning behind some logical steps, drawn over the board for a moment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Annotation {
+    deductions: Vec<Deduction>,
+    started: Instant,
+}
+
+impl Annotation {
+    /// Start showing the supplied steps, in the order they were made.
+    pub fn new(deductions: Vec<Deduction>) -> Annotation {
+        Annotation {
+            deductions,
+            started: Instant::now(),
+        }
+    }
+
+    /// Get how visible the annotation is, from 1 when it first appears down to 0, or [`None`]
+    /// once it has faded away.
+    ///
+    /// The annotation stays solid for most of its time, and only fades at the end.
+    pub fn visibility(&self) -> Option<f32> {
+        let progress = self.started.elapsed().as_secs_f32() / ANNOTATION_LENGTH.as_secs_f32();
+        if progress >= 1.0 {
+            return None;
+        }
+        Some(f32::min(1.0, (1.0 - progress) * 3.0))
+    }
+
+    /// Draw the annotation over a board, where `cell_rects` are the rectangles of its cells.
+    pub fn draw(&self, d: &mut RaylibDrawHandle, board: &Board, cell_rects: &[Rectangle; 81]) {
+        let Some(visible) = self.visibility() else {
+            return;
+        };
+        for deduction in &self.deductions {
+            draw_deduction(d, board, cell_rects, deduction, visible);
+        }
+    }
+}
+
+/// Get the candidates that a step relied on in each of its supporting cells.
+///
+/// Which candidates those are depends on the technique. Subsets lean on the digits of the subset,
+/// and the other eliminating techniques on the digit they rule out. Singles are supported by
+/// filled cells, which have no candidates to circle.
+fn relied_on(board: &Board, deduction: &Deduction) -> Vec<(usize, Candidates)> {
+    let Action::Eliminate(eliminated) = &deduction.action else {
+        return Vec::new();
+    };
+    let ruled_out = |index: usize| -> Candidates {
+        eliminated
+            .iter()
+            .filter(|&&(cell, _)| cell == index)
+            .map(|&(_, entry)| entry)
+            .collect()
+    };
+    let digits: Candidates = eliminated.iter().map(|&(_, entry)| entry).collect();
+    deduction
+        .support
+        .iter()
+        .map(|&index| {
+            let candidates = board.candidates(index);
+            let circled = match deduction.technique {
+                // The cells of a hidden subset lose every candidate except the subset's digits.
+                Technique::HiddenSubset { .. } => candidates.difference(ruled_out(index)),
+                _ => candidates.intersection(digits),
+            };
+            (index, circled)
+        })
+        .collect()
+}
+
+/// Get the rectangle that a candidate is written in within a cell, the same as pencil marks.
+fn candidate_rect(cell: Rectangle, entry: Entry) -> Rectangle {
+    let digit = entry as usize;
+    Rectangle {
+        x: cell.x + (digit % 3) as f32 * cell.width / 3.0,
+        y: cell.y + (digit / 3) as f32 * cell.height / 3.0,
+        width: cell.width / 3.0,
+        height: cell.height / 3.0,
+    }
+}
+
+/// Get the middle of a rectangle.
+fn center(rect: Rectangle) -> Vector2 {
+    Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0)
+}
+
+/// Draw a circle just inside a rectangle.
+fn draw_circle_around(d: &mut RaylibDrawHandle, rect: Rectangle, thickness: f32, color: Color) {
+    let radius = rect.width.min(rect.height) / 2.0;
+    d.draw_ring(
+        center(rect),
+        radius - thickness,
+        radius,
+        0.0,
+        360.0,
+        24,
+        color,
+    );
+}
+
+/// Draw the reasoning behind a single step.
+fn draw_deduction(
+    d: &mut RaylibDrawHandle,
+    board: &Board,
+    cell_rects: &[Rectangle; 81],
+    deduction: &Deduction,
+    visible: f32,
+) {
+    let circle = CIRCLE_COLOR.alpha(visible);
+    let cross = CROSS_COLOR.alpha(visible);
+    let thickness = 2.0 * ui::scale();
+    let font_size = ui::font_size() * 0.4;
+
+    let relied_on = relied_on(board, deduction);
+    for &(index, candidates) in &relied_on {
+        for entry in candidates.iter() {
+            let slot = candidate_rect(cell_rects[index], entry);
+            ui::draw_text_centered(d, &entry.to_string(), slot, font_size, circle);
+            draw_circle_around(d, slot, thickness, circle);
+        }
+    }
+
+    match &deduction.action {
+        Action::Place { index, entry } => {
+            let slot = candidate_rect(cell_rects[*index], *entry);
+            draw_circle_around(d, slot, thickness, circle);
+            // Singles rely on whole cells, like the filled cells which rule out every other digit.
+            for &support in &deduction.support {
+                d.draw_rectangle_lines_ex(cell_rects[support], thickness, circle);
+            }
+        }
+        Action::Eliminate(eliminated) => {
+            for &(index, entry) in eliminated {
+                let slot = candidate_rect(cell_rects[index], entry);
+                ui::draw_text_centered(d, &entry.to_string(), slot, font_size, cross);
+                let (x, y, w, h) = (slot.x, slot.y, slot.width, slot.height);
+                d.draw_line_ex(
+                    Vector2::new(x, y),
+                    Vector2::new(x + w, y + h),
+                    thickness,
+                    cross,
+                );
+                d.draw_line_ex(
+                    Vector2::new(x + w, y),
+                    Vector2::new(x, y + h),
+                    thickness,
+                    cross,
+                );
+            }
+        }
+    }
+
+    // The corners of an X-wing are joined up into a rectangle, which is the shape that gives the
+    // technique away.
+    if let Technique::XWing { entry, .. } = deduction.technique {
+        let corners = &deduction.support;
+        for (i, &first) in corners.iter().enumerate() {
+            for &second in &corners[i + 1..] {
+                if first / 9 == second / 9 || first % 9 == second % 9 {
+                    let from = center(candidate_rect(cell_rects[first], entry));
+                    let to = center(candidate_rect(cell_rects[second], entry));
+                    d.draw_line_ex(from, to, thickness, circle);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Unit;
+
+    #[test]
+    fn test_relied_on() {
+        let board = Board::empty();
+        let deduction = Deduction {
+            technique: Technique::Pointing {
+                from: Unit::Box(0),
+                into: Unit::Row(0),
+            },
+            action: Action::Eliminate(vec![(5, Entry::Three)]),
+            support: vec![0, 1],
+        };
+        let circled = relied_on(&board, &deduction);
+        assert_eq!(
+            circled,
+            vec![
+                (0, Candidates::only(Entry::Three)),
+                (1, Candidates::only(Entry::Three))
+            ]
+        );
+    }
+}
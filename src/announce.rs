@@ -0,0 +1,132 @@
+//! Describing the solve in words.
+//!
+//! With announcements turned on, every change the solver makes to the board and every change in
+//! the status strip is written to standard output as a sentence, like "Placed 5 at row 3, column
+//! 7." Screen readers can read these out, and the output can be piped into a log file to follow a
+//! solve without watching the window.
+
+use crate::board::Board;
+use crate::graphics::SolvingStatus;
+use crate::solver::StepOutcome;
+
+/// Describe where a cell is, like "row 3, column 7".
+pub fn describe_cell(index: usize) -> String {
+    format!("row {}, column {}", index / 9 + 1, index % 9 + 1)
+}
+
+/// Describe a step that the solver just took on the board.
+///
+/// The board should be the one the step was taken on, as it is straight after the step, so that
+/// the digit which replaced a rejected one can be read from it. Steps which only report that the
+/// solver is finished give [`None`], since the status says that instead.
+pub fn describe_step(outcome: &StepOutcome, board: &Board) -> Option<String> {
+    let replaced = |index: usize, entry| match board.get_cell_index(index) {
+        Some(next) => format!("replaced {entry} with {next} at {}", describe_cell(index)),
+        None => format!("took {entry} out of {}", describe_cell(index)),
+    };
+    let sentence = match *outcome {
+        StepOutcome::Placed { index, entry } => {
+            format!("Placed {entry} at {}.", describe_cell(index))
+        }
+        StepOutcome::Rejected { index, entry } => {
+            format!("{entry} breaks the rules, so {}.", replaced(index, entry))
+        }
+        StepOutcome::Backtracked { index, entry } => {
+            format!("Backtracked and {}.", replaced(index, entry))
+        }
+        StepOutcome::Deduced {
+            technique,
+            placed: Some((index, entry)),
+        } => format!("Placed {entry} at {} by {technique}.", describe_cell(index)),
+        StepOutcome::Deduced {
+            technique,
+            placed: None,
+        } => format!("Ruled out candidates by {technique}."),
+        StepOutcome::Solved | StepOutcome::Unsolvable => return None,
+    };
+    Some(sentence)
+}
+
+/// Describe the state of the solve, as shown in the status strip.
+pub fn describe_status(status: &SolvingStatus) -> String {
+    match status {
+        SolvingStatus::Going => "Solving.".to_string(),
+        SolvingStatus::Stopped => "Stopped.".to_string(),
+        SolvingStatus::Paused => "Paused.".to_string(),
+        SolvingStatus::Solved => "Solved!".to_string(),
+        SolvingStatus::Hint(technique) => format!("Hint: look for a {technique}."),
+        SolvingStatus::Unsolvable => "The puzzle has no solution.".to_string(),
+        SolvingStatus::Invalid => "The puzzle breaks the rules.".to_string(),
+    }
+}
+
+/// Writes announcements to standard output, saying when the status changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Announcer {
+    /// The last status that was announced.
+    status: Option<String>,
+}
+
+impl Announcer {
+    /// Create an announcer which hasn't said anything yet.
+    pub const fn new() -> Announcer {
+        Announcer { status: None }
+    }
+
+    /// Announce a step that the solver just took on the board.
+    pub fn step(&self, outcome: &StepOutcome, board: &Board) {
+        if let Some(sentence) = describe_step(outcome, board) {
+            println!("{sentence}");
+        }
+    }
+
+    /// Announce the status, if it isn't the same as the last time.
+    ///
+    /// This is cheap enough to call every frame.
+    pub fn status(&mut self, status: &SolvingStatus) {
+        let sentence = describe_status(status);
+        if self.status.as_ref() != Some(&sentence) {
+            println!("{sentence}");
+            self.status = Some(sentence);
+        }
+    }
+
+    /// Announce something else, like an undone step.
+    pub fn say(&self, sentence: &str) {
+        println!("{sentence}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Entry;
+
+    #[test]
+    fn test_describe_step() {
+        let mut board = Board::empty();
+        let placed = StepOutcome::Placed {
+            index: 24,
+            entry: Entry::Five,
+        };
+        assert_eq!(
+            describe_step(&placed, &board).unwrap(),
+            "Placed 5 at row 3, column 7."
+        );
+
+        let rejected = StepOutcome::Rejected {
+            index: 24,
+            entry: Entry::Five,
+        };
+        assert_eq!(
+            describe_step(&rejected, &board).unwrap(),
+            "5 breaks the rules, so took 5 out of row 3, column 7."
+        );
+        board.set_cell_index(24, Some(Entry::Six));
+        assert_eq!(
+            describe_step(&rejected, &board).unwrap(),
+            "5 breaks the rules, so replaced 5 with 6 at row 3, column 7."
+        );
+        assert_eq!(describe_step(&StepOutcome::Solved, &board), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Benchmarks for the solvers.
+//!
+//! A suite is just a list of puzzles. Running it with a [`Strategy`] solves every puzzle in turn
+//! and records how long each one took, so that the effect of a change to the solver can be measured
+//! on the same puzzles every time.
+
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::logic::Grid;
+use crate::random::Seed;
+use crate::solver::{self, Limits, SolveOutcome};
+
+/// The way that each puzzle in a suite is solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// The plain backtracking search from [`solver::solve`].
+    Backtracking,
+
+    /// The backtracking search with a shuffled digit order, from [`solver::solve_seeded`].
+    Seeded(Seed),
+
+    /// The multi-threaded search from [`solver::solve_parallel`]. Node counts are not available
+    /// for this strategy.
+    Parallel,
+
+    /// The deduction engine from [`crate::logic`], with backtracking to finish off anything that
+    /// logic can't.
+    Logic,
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strategy::Backtracking => write!(f, "backtracking"),
+            Strategy::Seeded(seed) => write!(f, "backtracking with seed {seed}"),
+            Strategy::Parallel => write!(f, "parallel"),
+            Strategy::Logic => write!(f, "logic"),
+        }
+    }
+}
+
+/// The result of solving a single puzzle in a suite.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PuzzleResult {
+    /// Whether the puzzle was solved.
+    pub solved: bool,
+
+    /// How long the solve took.
+    pub time: Duration,
+
+    /// The number of search nodes visited, if the strategy keeps count.
+    pub nodes: Option<u64>,
+}
+
+/// The results of running a whole suite.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SuiteReport {
+    /// The strategy which the suite was run with.
+    pub strategy: Strategy,
+
+    /// The result for each puzzle, in the same order as the puzzles were given.
+    pub results: Vec<PuzzleResult>,
+}
+
+impl SuiteReport {
+    /// Get the number of puzzles which were solved.
+    pub fn solved(&self) -> usize {
+        self.results.iter().filter(|result| result.solved).count()
+    }
+
+    /// Get the positions of the puzzles which could not be solved.
+    pub fn failures(&self) -> Vec<usize> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| !result.solved)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Get the time taken to solve the whole suite.
+    pub fn total_time(&self) -> Duration {
+        self.results.iter().map(|result| result.time).sum()
+    }
+
+    /// Get the average time taken per puzzle, or zero if the suite is empty.
+    pub fn mean_time(&self) -> Duration {
+        match u32::try_from(self.results.len()) {
+            Ok(0) | Err(_) => Duration::ZERO,
+            Ok(count) => self.total_time() / count,
+        }
+    }
+
+    /// Get the time which the supplied percentage of puzzles were solved within, by the
+    /// nearest-rank method, or zero if the suite is empty. The 50th percentile is the median.
+    pub fn percentile_time(&self, percent: usize) -> Duration {
+        let mut times: Vec<Duration> = self.results.iter().map(|result| result.time).collect();
+        times.sort_unstable();
+        let rank = (percent.min(100) * times.len()).div_ceil(100);
+        times
+            .get(rank.saturating_sub(1))
+            .copied()
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Get the number of search nodes visited over the whole suite, if the strategy keeps count.
+    pub fn total_nodes(&self) -> Option<u64> {
+        self.results.iter().map(|result| result.nodes).sum()
+    }
+
+    /// Write the report as a JSON object, so that results can be kept and compared over time.
+    ///
+    /// Times are in seconds. The object holds the strategy, the totals, and the mean, median, and
+    /// 95th percentile times, followed by the result for each puzzle under `results`. Node counts
+    /// are `null` if the strategy doesn't keep count.
+    pub fn to_json(&self) -> String {
+        let nodes =
+            |nodes: Option<u64>| nodes.map_or("null".to_string(), |nodes| nodes.to_string());
+        let results: Vec<String> = self
+            .results
+            .iter()
+            .map(|result| {
+                format!(
+                    "{{\"solved\":{},\"time\":{},\"nodes\":{}}}",
+                    result.solved,
+                    result.time.as_secs_f64(),
+                    nodes(result.nodes)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"strategy\":\"{}\",\"solved\":{},\"total\":{},\"time\":{},\"mean\":{},\
+             \"median\":{},\"p95\":{},\"nodes\":{},\"results\":[{}]}}",
+            self.strategy,
+            self.solved(),
+            self.results.len(),
+            self.total_time().as_secs_f64(),
+            self.mean_time().as_secs_f64(),
+            self.percentile_time(50).as_secs_f64(),
+            self.percentile_time(95).as_secs_f64(),
+            nodes(self.total_nodes()),
+            results.join(",")
+        )
+    }
+}
+
+impl std::fmt::Display for SuiteReport {
+    /// Write the report as a table with one row per puzzle, followed by the totals.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "strategy: {}", self.strategy)?;
+        writeln!(
+            f,
+            "{:>6}  {:>12}  {:>12}  result",
+            "puzzle", "time", "nodes"
+        )?;
+        for (i, result) in self.results.iter().enumerate() {
+            let nodes = result
+                .nodes
+                .map_or("-".to_string(), |nodes| nodes.to_string());
+            let outcome = if result.solved { "solved" } else { "FAILED" };
+            writeln!(
+                f,
+                "{:>6}  {:>12}  {nodes:>12}  {outcome}",
+                i + 1,
+                format!("{:.3?}", result.time),
+            )?;
+        }
+
+        write!(
+            f,
+            "solved {} of {} in {:.3?} (mean {:.3?}, median {:.3?}, 95th percentile {:.3?})",
+            self.solved(),
+            self.results.len(),
+            self.total_time(),
+            self.mean_time(),
+            self.percentile_time(50),
+            self.percentile_time(95),
+        )?;
+        if let Some(nodes) = self.total_nodes() {
+            write!(f, ", {nodes} nodes")?;
+        }
+        Ok(())
+    }
+}
+
+/// Solve every puzzle in a suite with the supplied strategy.
+///
+/// The puzzles themselves are left untouched, since each one is solved on a copy.
+pub fn run_suite(puzzles: &[Board], strategy: Strategy) -> SuiteReport {
+    let results = puzzles
+        .iter()
+        .map(|puzzle| {
+            let mut board = puzzle.clone();
+            let start = Instant::now();
+            let (solved, nodes) = solve(&mut board, strategy);
+            PuzzleResult {
+                solved,
+                time: start.elapsed(),
+                nodes,
+            }
+        })
+        .collect();
+    SuiteReport { strategy, results }
+}
+
+/// Solve a single board, returning whether it worked and how many nodes were visited.
+fn solve(board: &mut Board, strategy: Strategy) -> (bool, Option<u64>) {
+    let (outcome, stats) = match strategy {
+        Strategy::Backtracking => solver::search(board, Limits::NONE, None),
+        Strategy::Seeded(seed) => solver::search(board, Limits::NONE, Some(seed)),
+        Strategy::Parallel => return (solver::solve_parallel(board), None),
+        Strategy::Logic => {
+            let mut grid = Grid::new(board);
+            while let Some(deduction) = grid.find_deduction() {
+                grid.apply(&deduction);
+            }
+            grid.write_to(board);
+            solver::search(board, Limits::NONE, None)
+        }
+    };
+    (outcome == SolveOutcome::Solved, Some(stats.nodes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Entry;
+
+    #[test]
+    fn test_run_suite() {
+        let medium: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let mut broken = Board::empty();
+        broken.set_cell_index(0, Some(Entry::One));
+        broken.set_cell_index(1, Some(Entry::One));
+
+        let report = run_suite(&[medium.clone(), broken], Strategy::Logic);
+        assert_eq!(report.solved(), 1);
+        assert_eq!(report.failures(), vec![1]);
+        assert!(report.total_nodes().is_some());
+
+        let report = run_suite(&[medium], Strategy::Parallel);
+        assert_eq!(report.failures(), Vec::<usize>::new());
+        assert_eq!(report.total_nodes(), None);
+        assert!(
+            report
+                .to_json()
+                .contains(r#""nodes":null,"results":[{"solved":true,"#)
+        );
+    }
+
+    #[test]
+    fn test_percentile_time() {
+        let results = (1..=20)
+            .map(|millis| PuzzleResult {
+                solved: true,
+                time: Duration::from_millis(millis),
+                nodes: None,
+            })
+            .rev()
+            .collect();
+        let report = SuiteReport {
+            strategy: Strategy::Backtracking,
+            results,
+        };
+        assert_eq!(report.percentile_time(50), Duration::from_millis(10));
+        assert_eq!(report.percentile_time(95), Duration::from_millis(19));
+        assert_eq!(report.percentile_time(100), Duration::from_millis(20));
+        assert_eq!(report.percentile_time(0), Duration::from_millis(1));
+
+        let empty = SuiteReport {
+            strategy: Strategy::Logic,
+            results: Vec::new(),
+        };
+        assert_eq!(empty.percentile_time(50), Duration::ZERO);
+    }
+}
@@ -4,18 +4,27 @@
 
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use itertools::Itertools;
 
 use raylib::prelude::*;
 
-use crate::ui::{self, Widget};
+use crate::annotation::Annotation;
+use crate::constraint::{self, Constraint};
+use crate::hints::Hint;
+use crate::history::{Change, Move, MoveHistory};
+use crate::keys::Action;
+use crate::logic::Deduction;
+use crate::ui::{self, InputState};
 
 /// An entry for a cell of the Sudoku board.
 ///
-/// Each square of the board can contain a digit from 1 to 9. This enum ensures that no invalid
-/// digit can be represented inside of the board. I would hope that the individual members do not
-/// need their own documentation.
+/// Each square of the board can contain a digit from 1 to 9. Boards of [other sizes](crate::sized)
+/// go up to 16, so there are entries for the digits past 9 as well, but a 9x9 [`Board`] never
+/// holds one of those. I would hope that the individual members do not need their own
+/// documentation.
 #[allow(missing_docs)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
@@ -29,14 +38,103 @@ pub enum Entry {
     Seven,
     Eight,
     Nine,
+    Ten,
+    Eleven,
+    Twelve,
+    Thirteen,
+    Fourteen,
+    Fifteen,
+    Sixteen,
 }
 
 impl Entry {
+    /// Every entry that fits on a 9x9 board, in increasing order.
+    pub const ALL: [Entry; 9] = [
+        Entry::One,
+        Entry::Two,
+        Entry::Three,
+        Entry::Four,
+        Entry::Five,
+        Entry::Six,
+        Entry::Seven,
+        Entry::Eight,
+        Entry::Nine,
+    ];
+
+    /// Every entry that fits on a 16x16 board, which is every entry there is, in increasing order.
+    const WIDE: [Entry; 16] = [
+        Entry::One,
+        Entry::Two,
+        Entry::Three,
+        Entry::Four,
+        Entry::Five,
+        Entry::Six,
+        Entry::Seven,
+        Entry::Eight,
+        Entry::Nine,
+        Entry::Ten,
+        Entry::Eleven,
+        Entry::Twelve,
+        Entry::Thirteen,
+        Entry::Fourteen,
+        Entry::Fifteen,
+        Entry::Sixteen,
+    ];
+
+    /// Get the entries that fit on a board `size` cells wide, in increasing order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is bigger than 16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sudoku_solver::board::Entry;
+    ///
+    /// assert_eq!(Entry::up_to(9), Entry::ALL);
+    /// assert_eq!(Entry::up_to(12).last(), Some(&Entry::Twelve));
+    /// ```
+    pub fn up_to(size: usize) -> &'static [Entry] {
+        &Entry::WIDE[..size]
+    }
+
+    /// Check whether the entry fits on a board `size` cells wide.
+    pub const fn fits(self, size: usize) -> bool {
+        (self as usize) < size
+    }
+
+    /// Get the character that the entry is written as. Digits past 9 are written as letters, so 10
+    /// is `A`, 11 is `B`, and so on up to `G` for 16.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sudoku_solver::board::Entry;
+    ///
+    /// assert_eq!(Entry::Seven.to_char(), '7');
+    /// assert_eq!(Entry::Sixteen.to_char(), 'G');
+    /// assert_eq!(Entry::from_char('g'), Some(Entry::Sixteen));
+    /// assert_eq!(Entry::from_char('0'), None);
+    /// ```
+    pub fn to_char(self) -> char {
+        char::from_digit(self as u32 + 1, 36)
+            .unwrap_or('?')
+            .to_ascii_uppercase()
+    }
+
+    /// Read an entry from the character it is written as, which is the opposite of
+    /// [`Entry::to_char`]. Letters can be either case.
+    pub fn from_char(c: char) -> Option<Entry> {
+        let digit = c.to_digit(36)?;
+        Entry::try_from(digit as i32).ok()
+    }
+
     /// Get the successor of an entry.
     ///
     /// An entry is just a number, so this function retrieves the Peano-style successor. Naturally,
-    /// there is no valid entry larger than 9, so attempting to get the successor of 9 will return
-    /// [`None`].
+    /// there is no valid entry on a 9x9 board larger than 9, so attempting to get the successor of
+    /// 9 will return [`None`].
     ///
     /// # Examples
     ///
@@ -49,7 +147,9 @@ impl Entry {
     /// ```
     pub fn successor(&self) -> Option<Self> {
         let number: i32 = self.clone().into();
-        Self::try_from(number + 1).ok()
+        Self::try_from(number + 1)
+            .ok()
+            .filter(|entry| entry.fits(9))
     }
 }
 
@@ -60,7 +160,7 @@ impl TryFrom<i32> for Entry {
     ///
     /// Since the board entries represent numbers, it is natural to want to convert to an entry
     /// from a number. However, not all integers represent valid entries (in particular, only the
-    /// digits 1-9 represent valid entries). If the integer passed is in that range, then the
+    /// numbers 1-16 represent valid entries). If the integer passed is in that range, then the
     /// corresponding entry is returned. Otherwise, `Err(())` is returned.
     ///
     /// # Examples
@@ -71,19 +171,12 @@ impl TryFrom<i32> for Entry {
     /// assert_eq!(Entry::try_from(1), Ok(Entry::One));
     /// assert_eq!(Entry::try_from(7), Ok(Entry::Seven));
     /// assert_eq!(Entry::try_from(0), Err(()));
-    /// assert_eq!(Entry::try_from(10), Err(()));
+    /// assert_eq!(Entry::try_from(10), Ok(Entry::Ten));
+    /// assert_eq!(Entry::try_from(17), Err(()));
     /// ```
     fn try_from(value: i32) -> Result<Entry, Self::Error> {
         match value {
-            1 => Ok(Entry::One),
-            2 => Ok(Entry::Two),
-            3 => Ok(Entry::Three),
-            4 => Ok(Entry::Four),
-            5 => Ok(Entry::Five),
-            6 => Ok(Entry::Six),
-            7 => Ok(Entry::Seven),
-            8 => Ok(Entry::Eight),
-            9 => Ok(Entry::Nine),
+            1..=16 => Ok(Entry::WIDE[value as usize - 1]),
             _ => Err(()),
         }
     }
@@ -101,6 +194,13 @@ impl Into<i32> for Entry {
             Self::Seven => 7,
             Self::Eight => 8,
             Self::Nine => 9,
+            Self::Ten => 10,
+            Self::Eleven => 11,
+            Self::Twelve => 12,
+            Self::Thirteen => 13,
+            Self::Fourteen => 14,
+            Self::Fifteen => 15,
+            Self::Sixteen => 16,
         }
     }
 }
@@ -111,6 +211,380 @@ impl std::fmt::Display for Entry {
     }
 }
 
+/// A set of entries.
+///
+/// This is mostly used to keep track of which digits could still go into a cell, hence the name.
+/// The set is stored as a bitmask, so it is cheap to copy around and to combine with other sets.
+///
+/// # Examples
+///
+/// ```
+/// use sudoku_solver::board::{Candidates, Entry};
+///
+/// let mut candidates = Candidates::ALL;
+/// candidates.remove(Entry::Three);
+/// assert_eq!(candidates.len(), 8);
+/// assert!(!candidates.contains(Entry::Three));
+/// assert!(candidates.contains(Entry::Four));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Candidates(u16);
+
+impl Candidates {
+    /// The empty set.
+    pub const NONE: Candidates = Candidates(0);
+
+    /// The set containing every entry.
+    pub const ALL: Candidates = Candidates(0b1_1111_1111);
+
+    /// Create a set containing just one entry.
+    pub const fn only(entry: Entry) -> Candidates {
+        Candidates(1 << entry as u8)
+    }
+
+    /// Check whether the set contains an entry.
+    pub const fn contains(&self, entry: Entry) -> bool {
+        self.0 & (1 << entry as u8) != 0
+    }
+
+    /// Add an entry to the set.
+    pub fn insert(&mut self, entry: Entry) {
+        self.0 |= 1 << entry as u8;
+    }
+
+    /// Remove an entry from the set.
+    pub fn remove(&mut self, entry: Entry) {
+        self.0 &= !(1 << entry as u8);
+    }
+
+    /// Add an entry to the set if it is missing, or remove it if it is there.
+    pub fn toggle(&mut self, entry: Entry) {
+        self.0 ^= 1 << entry as u8;
+    }
+
+    /// Get the number of entries in the set.
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Check whether the set is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Get the only entry in the set.
+    ///
+    /// If the set contains anything other than exactly one entry, [`None`] is returned.
+    pub fn single(&self) -> Option<Entry> {
+        if self.len() == 1 {
+            self.iter().next()
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the entries in the set in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Entry> + use<> {
+        let set = *self;
+        Entry::ALL
+            .into_iter()
+            .filter(move |&entry| set.contains(entry))
+    }
+
+    /// Get the entries that are in both sets.
+    pub const fn intersection(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 & other.0)
+    }
+
+    /// Get the entries that are in either set.
+    pub const fn union(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 | other.0)
+    }
+
+    /// Get the entries that are in this set but not the other one.
+    pub const fn difference(&self, other: Candidates) -> Candidates {
+        Candidates(self.0 & !other.0)
+    }
+}
+
+impl FromIterator<Entry> for Candidates {
+    fn from_iter<I: IntoIterator<Item = Entry>>(iter: I) -> Self {
+        let mut candidates = Candidates::NONE;
+        for entry in iter {
+            candidates.insert(entry);
+        }
+        candidates
+    }
+}
+
+/// A group of nine cells which must contain every digit exactly once.
+///
+/// The number held by each variant is the index of the row, column, or box, from 0 to 8. Boxes
+/// are numbered the same way as in [`Board::get_big_cell`]. Some units only exist under certain
+/// [`Variant`] rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// A row, numbered from the top.
+    Row(usize),
+
+    /// A column, numbered from the left.
+    Column(usize),
+
+    /// A 3x3 box, numbered along the width of the board first, then down the height. On a jigsaw
+    /// board, this is one of the irregular [`Regions`] instead.
+    Box(usize),
+
+    /// One of the two long diagonals, which are only units in Sudoku X. Diagonal 0 runs from the
+    /// top left to the bottom right, and diagonal 1 runs from the top right to the bottom left.
+    Diagonal(usize),
+
+    /// One of the four extra 3x3 windows of hyper Sudoku, which are only units in that variant.
+    /// The windows sit one cell in from the edges of the board, and are numbered the same way as
+    /// boxes.
+    Window(usize),
+}
+
+impl Unit {
+    /// Iterate over the 27 units of an ordinary board.
+    pub fn all() -> impl Iterator<Item = Unit> {
+        (0..9)
+            .map(Unit::Row)
+            .chain((0..9).map(Unit::Column))
+            .chain((0..9).map(Unit::Box))
+    }
+
+    /// Get the three units of an ordinary board that the cell at the supplied index belongs to.
+    pub const fn containing(index: usize) -> [Unit; 3] {
+        let row = index / 9;
+        let column = index % 9;
+        [
+            Unit::Row(row),
+            Unit::Column(column),
+            Unit::Box((row / 3) * 3 + column / 3),
+        ]
+    }
+
+    /// Get the indices of the cells in the unit on an ordinary board. Use [`Variant::cells`] for
+    /// boards which might have irregular regions.
+    pub fn cells(&self) -> [usize; 9] {
+        match *self {
+            Unit::Row(row) => std::array::from_fn(|x| row * 9 + x),
+            Unit::Column(column) => std::array::from_fn(|x| x * 9 + column),
+            Unit::Box(index) => {
+                let small_index = as_small_index(index);
+                std::array::from_fn(|x| small_index + (x / 3) * 9 + x % 3)
+            }
+            Unit::Diagonal(0) => std::array::from_fn(|x| x * 10),
+            Unit::Diagonal(_) => std::array::from_fn(|x| (x + 1) * 8),
+            Unit::Window(index) => {
+                let top_left = (1 + (index / 2) * 4) * 9 + 1 + (index % 2) * 4;
+                std::array::from_fn(|x| top_left + (x / 3) * 9 + x % 3)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Unit {
+    /// Write a human readable name for the unit.
+    ///
+    /// Units are numbered from 1 rather than 0 here, since this is what players expect to read.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unit::Row(row) => write!(f, "row {}", row + 1),
+            Unit::Column(column) => write!(f, "column {}", column + 1),
+            Unit::Box(index) => write!(f, "box {}", index + 1),
+            Unit::Diagonal(0) => write!(f, "the main diagonal"),
+            Unit::Diagonal(_) => write!(f, "the anti-diagonal"),
+            Unit::Window(index) => write!(f, "window {}", index + 1),
+        }
+    }
+}
+
+/// The irregular regions which replace the boxes in jigsaw Sudoku.
+///
+/// Each cell is assigned to one of nine regions, numbered from 0 to 8. A valid map has exactly
+/// nine cells in every region, and the cells of each region are connected to each other through
+/// their edges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Regions([u8; 81]);
+
+impl Regions {
+    /// Create a region map from the region number of every cell.
+    ///
+    /// If the map is not valid, [`None`] is returned.
+    pub fn new(map: [u8; 81]) -> Option<Regions> {
+        let regions = Regions(map);
+        (0..9)
+            .all(|region| regions.is_connected_region(region))
+            .then_some(regions)
+    }
+
+    /// Get the region that the cell at the supplied index belongs to.
+    pub const fn region_of(&self, index: usize) -> usize {
+        self.0[index] as usize
+    }
+
+    /// Get the indices of the cells in a region, in reading order.
+    pub fn cells(&self, region: usize) -> [usize; 9] {
+        let mut cells = [0; 9];
+        let indices = (0..81).filter(|&index| self.region_of(index) == region);
+        for (cell, index) in cells.iter_mut().zip(indices) {
+            *cell = index;
+        }
+        cells
+    }
+
+    /// Check that a region has nine cells, all connected to each other.
+    fn is_connected_region(&self, region: usize) -> bool {
+        let members: Vec<usize> = (0..81)
+            .filter(|&index| self.region_of(index) == region)
+            .collect();
+        if members.len() != 9 {
+            return false;
+        }
+
+        // Flood fill from the first cell and check that every cell was reached.
+        let mut reached = vec![members[0]];
+        let mut frontier = vec![members[0]];
+        while let Some(index) = frontier.pop() {
+            let (row, column) = (index / 9, index % 9);
+            let neighbours = [
+                (row > 0).then(|| index - 9),
+                (row < 8).then(|| index + 9),
+                (column > 0).then(|| index - 1),
+                (column < 8).then(|| index + 1),
+            ];
+            for neighbour in neighbours.into_iter().flatten() {
+                if self.region_of(neighbour) == region && !reached.contains(&neighbour) {
+                    reached.push(neighbour);
+                    frontier.push(neighbour);
+                }
+            }
+        }
+        reached.len() == 9
+    }
+}
+
+impl std::str::FromStr for Regions {
+    type Err = ();
+
+    /// Convert a string into a region map.
+    ///
+    /// Each cell is written as a letter from `a` to `i` naming its region, in reading order. Any
+    /// other characters are skipped, so the map can be laid out as a grid. If there aren't exactly
+    /// 81 letters, or the map is not valid, `Err(())` is returned.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let letters: Vec<u8> = s
+            .bytes()
+            .map(|c| c.to_ascii_lowercase())
+            .filter(|c| (b'a'..=b'i').contains(c))
+            .map(|c| c - b'a')
+            .collect();
+        let map = letters.try_into().map_err(|_| ())?;
+        Regions::new(map).ok_or(())
+    }
+}
+
+impl std::fmt::Display for Regions {
+    /// Write the region map as nine lines of nine letters, which [`str::parse`] reads back.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.0.chunks(9) {
+            let line: String = row
+                .iter()
+                .map(|&region| char::from(b'a' + region))
+                .collect();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Extra rules that a board can be played with, on top of the usual rows, columns, and boxes.
+///
+/// Everything that checks or solves a board goes through the board's variant, so the solvers
+/// respect the extra rules without having to know about them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Variant {
+    /// Whether the two long diagonals must also contain every digit exactly once, as in Sudoku X.
+    pub diagonals: bool,
+
+    /// Whether the four windows of hyper Sudoku (also called windoku) must also contain every digit
+    /// exactly once.
+    pub windows: bool,
+
+    /// The irregular regions which replace the boxes in jigsaw Sudoku, if there are any.
+    pub regions: Option<Regions>,
+}
+
+impl Variant {
+    /// The rules of ordinary Sudoku, with nothing extra.
+    pub const STANDARD: Variant = Variant {
+        diagonals: false,
+        windows: false,
+        regions: None,
+    };
+
+    /// Get every unit under these rules.
+    pub fn units(&self) -> Vec<Unit> {
+        Unit::all().chain(self.extra_units()).collect()
+    }
+
+    /// Get the units which don't exist on an ordinary board.
+    pub fn extra_units(&self) -> Vec<Unit> {
+        let mut units = Vec::new();
+        if self.diagonals {
+            units.extend([Unit::Diagonal(0), Unit::Diagonal(1)]);
+        }
+        if self.windows {
+            units.extend((0..4).map(Unit::Window));
+        }
+        units
+    }
+
+    /// Get the indices of the cells in a unit, taking the irregular regions into account.
+    pub fn cells(&self, unit: Unit) -> [usize; 9] {
+        match (unit, &self.regions) {
+            (Unit::Box(region), Some(regions)) => regions.cells(region),
+            _ => unit.cells(),
+        }
+    }
+
+    /// Get the number of the box (or region) that the cell at the supplied index belongs to.
+    pub const fn box_of(&self, index: usize) -> usize {
+        match &self.regions {
+            Some(regions) => regions.region_of(index),
+            None => (index / 27) * 3 + (index % 9) / 3,
+        }
+    }
+
+    /// Get the units that the cell at the supplied index belongs to. The first three are always
+    /// the cell's row, column, and box, in that order.
+    pub fn units_containing(&self, index: usize) -> Vec<Unit> {
+        let mut units = vec![
+            Unit::Row(index / 9),
+            Unit::Column(index % 9),
+            Unit::Box(self.box_of(index)),
+        ];
+        units.extend(
+            self.extra_units()
+                .into_iter()
+                .filter(|&unit| self.cells(unit).contains(&index)),
+        );
+        units
+    }
+
+    /// Get every cell which shares a unit with the cell at the supplied index, not including the
+    /// cell itself.
+    pub fn peers(&self, index: usize) -> Vec<usize> {
+        self.units_containing(index)
+            .into_iter()
+            .flat_map(|unit| self.cells(unit))
+            .filter(|&peer| peer != index)
+            .unique()
+            .collect()
+    }
+}
+
 /// Convert a big index into a small index.
 ///
 /// This function converts the index of a big cell into the index of a small cell by taking the
@@ -125,7 +599,7 @@ fn as_small_index(big_index: usize) -> usize {
     }
 }
 
-fn has_duplicates<I>(iterator: I) -> bool
+pub(crate) fn has_duplicates<I>(iterator: I) -> bool
 where
     I: Iterator<Item: Eq + Hash>,
 {
@@ -143,26 +617,119 @@ where
 /// The board contains 9 rows and 9 columns, grouped into a 3x3 grid. Each cell contains a digit
 /// from 1 to 9. Boards have the important invariant that no digit can appear twice within the same
 /// row, column, or 3x3 subgrid.
-#[derive(Debug)]
+///
+/// Only the digits and the rules are kept here. How the board is shown, from the selection to the
+/// pencil marks, is kept in a [`BoardView`].
+#[derive(Debug, Clone)]
 pub struct Board {
     /// The cells of the board. Each square of a Sudoku board is either empty, or occupied by a
     /// digit in the range 1-9.
     cells: [Option<Entry>; 81],
 
-    /// This variable is for the UI, it controls which square is currently selected. The selected
-    /// square is highlighted with a red border, and the user can edit the number in that square.
-    selected_square: Option<usize>,
+    /// Which cells hold the clues that the puzzle started with. Givens are drawn differently from
+    /// digits which were filled in later, and are what is left when the board is reset.
+    givens: [bool; 81],
+
+    /// The extra rules that the board is played with.
+    variant: Variant,
+
+    /// Rules added on top of the variant, like killer cages. These are shared between copies of
+    /// the board, since the solvers copy boards all the time and the rules never change mid-solve.
+    constraints: Vec<Arc<dyn Constraint>>,
 }
 
 impl Board {
     /// Creates a new empty board.
     ///
-    /// All of the squares in the board start out unfilled, and no square is selected initially.
+    /// All of the squares in the board start out unfilled.
     pub const fn empty() -> Board {
         Board {
             cells: [None; 81],
-            selected_square: None,
+            givens: [false; 81],
+            variant: Variant::STANDARD,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Get the extra rules that the board is played with.
+    pub const fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Change the extra rules that the board is played with.
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    /// Check whether the cell at the supplied index holds one of the puzzle's original clues.
+    pub const fn is_given(&self, index: usize) -> bool {
+        self.givens[index]
+    }
+
+    /// Treat every filled cell as one of the puzzle's original clues, and every empty cell as one
+    /// to be filled in.
+    ///
+    /// Boards parsed from strings already have their givens marked. Boards which are built up one
+    /// cell at a time need this once the clues are in place.
+    pub fn mark_givens(&mut self) {
+        self.givens = self.cells.map(|cell| cell.is_some());
+    }
+
+    /// Put the board back the way the puzzle started, keeping only the givens.
+    ///
+    /// Every other cell is emptied, and the variant and the rules are left alone. Any
+    /// [`MoveHistory`] for the board no longer matches it afterwards, so it should be cleared as
+    /// well, along with the pencil marks in its [`BoardView`].
+    pub fn reset(&mut self) {
+        for index in 0..81 {
+            if !self.givens[index] {
+                self.cells[index] = None;
+            }
+        }
+    }
+
+    /// Get a copy of the puzzle as it started, holding only the givens.
+    ///
+    /// Nothing but the givens, the variant, and the rules is carried over.
+    pub fn original(&self) -> Board {
+        let mut original = Board::empty();
+        for index in (0..81).filter(|&index| self.givens[index]) {
+            original.cells[index] = self.cells[index];
         }
+        original.givens = self.givens;
+        original.variant = self.variant;
+        original.constraints = self.constraints.clone();
+        original
+    }
+
+    /// Add a rule on top of the board's variant.
+    pub fn add_constraint(&mut self, constraint: impl Constraint + 'static) {
+        self.constraints.push(Arc::new(constraint));
+    }
+
+    /// Get the rules which have been added on top of the board's variant.
+    pub fn constraints(&self) -> &[Arc<dyn Constraint>] {
+        &self.constraints
+    }
+
+    /// Iterate over every rule of the board, starting with its variant.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Constraint> {
+        std::iter::once(&self.variant as &dyn Constraint).chain(
+            self.constraints
+                .iter()
+                .map(|constraint| constraint.as_ref()),
+        )
+    }
+
+    /// Get every unit of the board, including any extra units from its variant.
+    pub fn units(&self) -> Vec<Unit> {
+        self.variant.units()
+    }
+
+    /// Get the units that the cell at the supplied index belongs to, including any extra units
+    /// from the board's variant.
+    pub fn units_containing(&self, index: usize) -> Vec<Unit> {
+        self.variant.units_containing(index)
     }
 
     /// Retrieve the entry in a particular cell.
@@ -209,163 +776,918 @@ impl Board {
         (0..9).map(|x| self.cells[x * 9 + column]).collect()
     }
 
-    /// Retrieve a big cell.
-    ///
-    /// In Sudoku, the board can be divided into 9 big cells, each 3x3 in size. This function will
-    /// treat the board as if it is made up of big cells, and return the cell at the supplied
-    /// index. Indices run along the width of the board first, then down the height.
+    /// Retrieve a big cell.
+    ///
+    /// In Sudoku, the board can be divided into 9 big cells, each 3x3 in size. This function will
+    /// treat the board as if it is made up of big cells, and return the cell at the supplied
+    /// index. Indices run along the width of the board first, then down the height. On a jigsaw
+    /// board, the big cells are the irregular regions, and their cells are in reading order.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the index is at least 9.
+    pub fn get_big_cell(&self, index: usize) -> Vec<Option<Entry>> {
+        assert!(index < 9, "there are only 9 big cells");
+        self.variant
+            .cells(Unit::Box(index))
+            .into_iter()
+            .map(|index| self.cells[index])
+            .collect()
+    }
+
+    /// Set the cell at the target index to the specified value.
+    ///
+    /// The board has exactly 81 cells, so this function will do nothing if the index is greater
+    /// than 80. Additionally, all cells must be in the range [1, 9], so if the supplied entry is
+    /// not in that range, the function will do nothing. To clear the entry at the target index,
+    /// you can pass [`None`]. Either way, the cell stops being a given.
+    pub fn set_cell_index(&mut self, index: usize, entry: Option<Entry>) {
+        if index < self.cells.len() && entry.is_none_or(|entry| entry.fits(9)) {
+            self.cells[index] = entry;
+            self.givens[index] = false;
+        }
+    }
+
+    /// Retrieve the index of the first unfilled cell.
+    ///
+    /// Imagine that the rows of the board are positioned one after another. The first unfilled
+    /// cell is the first cell from the left which contains no entry. If there is no such cell,
+    /// e.g. all cells have been filled, then [`None`] is returned.
+    pub fn first_unfilled_index(&self) -> Option<usize> {
+        self.cells
+            .into_iter()
+            .enumerate()
+            .find(|(_, x)| x.is_none())
+            .map(|(index, _)| index)
+    }
+
+    /// Check whether or not a board is valid.
+    ///
+    /// A board is valid if every row, column, and big cell contains every digit at most once. For
+    /// instance, a board is not valid if a row contains two 2's. The same goes for any extra units
+    /// from the board's variant, and the board also has to pass the check of every other rule.
+    pub fn is_valid(&self) -> bool {
+        self.rules().all(|rule| rule.check(self))
+    }
+
+    /// Find every pair of cells which share a unit and hold the same digit.
+    ///
+    /// Each pair is listed once, with the smaller index first, even if the two cells share more
+    /// than one unit. Only units are checked here; a board can still break one of its other
+    /// constraints without any conflicts showing up.
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let mut conflicts: Vec<(usize, usize)> = self
+            .units()
+            .into_iter()
+            .flat_map(|unit| {
+                self.variant
+                    .cells(unit)
+                    .into_iter()
+                    .filter(|&index| self.cells[index].is_some())
+                    .tuple_combinations()
+                    .filter(|&(first, second)| self.cells[first] == self.cells[second])
+            })
+            .map(|(first, second)| (first.min(second), first.max(second)))
+            .collect();
+        conflicts.sort_unstable();
+        conflicts.dedup();
+        conflicts
+    }
+
+    /// Find every cell which is filled in on both boards, but with different digits.
+    ///
+    /// Comparing a board being played against its solution gives the digits which are wrong,
+    /// without giving away anything about the cells which are still empty.
+    pub fn diff(&self, other: &Board) -> Vec<usize> {
+        (0..81)
+            .filter(|&index| match (self.cells[index], other.cells[index]) {
+                (Some(mine), Some(theirs)) => mine != theirs,
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Get the digits which could still be written into a cell.
+    ///
+    /// The candidates for an empty cell are the digits which do not already appear in the cell's
+    /// row, column, big cell, or any extra unit, and which no other rule rules out. Filled cells
+    /// have no candidates.
+    pub fn candidates(&self, index: usize) -> Candidates {
+        if self.cells[index].is_some() {
+            return Candidates::NONE;
+        }
+
+        let mut candidates = Candidates::ALL;
+        for rule in self.rules() {
+            rule.prune(self, index, &mut candidates);
+        }
+        candidates
+    }
+
+    /// Write the board on a single line of 81 characters, with a `0` for every empty cell.
+    ///
+    /// This is the format used by `.sdm` files, which hold one puzzle per line. The line can be
+    /// parsed back into an identical board with [`str::parse`].
+    pub fn to_line(&self) -> String {
+        self.cells
+            .iter()
+            .map(|cell| match cell {
+                Some(entry) => char::from(b'0' + (*entry as u8) + 1),
+                None => '0',
+            })
+            .collect()
+    }
+
+    /// Write the board as a JSON object, with the rows in an array under `rows`. Each row is an
+    /// array of nine digits, with `null` for every empty cell.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = (0..9)
+            .map(|row| {
+                let cells: Vec<String> = (0..9)
+                    .map(|column| match self.get_cell(row, column) {
+                        Some(entry) => entry.to_string(),
+                        None => "null".to_string(),
+                    })
+                    .collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+        format!("{{\"rows\":[{}]}}", rows.join(","))
+    }
+
+    /// Write the board as comma-separated values, with one line for each row. Empty cells are
+    /// left as empty fields.
+    pub fn to_csv(&self) -> String {
+        (0..9)
+            .map(|row| {
+                let cells: Vec<String> = (0..9)
+                    .map(|column| match self.get_cell(row, column) {
+                        Some(entry) => entry.to_string(),
+                        None => String::new(),
+                    })
+                    .collect();
+                cells.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Get everything that the grid and the givens look like when drawn into `rect`.
+    fn static_layer(&self, rect: Rectangle) -> StaticLayer {
+        StaticLayer {
+            givens: std::array::from_fn(|index| self.cells[index].filter(|_| self.givens[index])),
+            size: (rect.width.ceil() as u32, rect.height.ceil() as u32),
+            boxes: self.variant.regions.is_none(),
+            font_size: ui::font_size(),
+            scale: ui::scale(),
+        }
+    }
+
+    /// Draw the grid and the givens, which are the parts of the board that hardly ever change.
+    fn draw_static<D>(&self, d: &mut D, rect: Rectangle)
+    where
+        D: RaylibDraw + std::ops::Deref<Target = RaylibHandle>,
+    {
+        let cell_rects = cell_rects(rect);
+        for (index, entry) in self.static_layer(rect).givens.into_iter().enumerate() {
+            if let Some(entry) = entry {
+                draw_cell_entry(d, cell_rects[index], entry, true, Color::BLACK, 1.0);
+            }
+        }
+        draw_grid(d, rect, self.variant.regions.is_none());
+    }
+}
+
+impl std::str::FromStr for Board {
+    type Err = ();
+
+    /// Convert a string into a board.
+    ///
+    /// Strictly speaking, the string does not need a particular format. The function skips past
+    /// any characters that are not digits, dashes, underscores, or periods. Zeros, dashes,
+    /// underscores, and periods all mark empty cells. Lines starting with a `+` are treated as
+    /// decorative borders and skipped entirely, since they are made of dashes. All of the cells in
+    /// the board are initialized one by one as digits are found in the string. For example, the
+    /// strings "16_9____52___456_9_9__3_7_2 6____7_939___1___747_3_9__8 7_2_8_956__629___4__9_____1" and
+    ///
+    /// +-------+-------+-------+
+    /// | 1 6 _ | 9 _ _ | _ _ 5 |
+    /// | 2 _ _ | _ 4 5 | 6 _ 9 |
+    /// | _ 9 _ | _ 3 _ | 7 _ 2 |
+    /// +-------+-------+-------+
+    /// | 6 _ _ | _ _ 7 | _ 9 3 |
+    /// | 9 _ _ | _ 1 _ | _ _ 7 |
+    /// | 4 7 _ | 3 _ 9 | _ _ 8 |
+    /// +-------+-------+-------+
+    /// | 7 _ 2 | _ 8 _ | 9 5 6 |
+    /// | _ _ 6 | 2 9 _ | _ _ 4 |
+    /// | _ _ 9 | _ _ _ | _ _ 1 |
+    /// +-------+-------+-------+
+    ///
+    /// parse to the same board. If the string describes more than 81 cells, `Err(())` is
+    /// returned.
+    ///
+    /// Variants are declared after the cells, one per line. A line reading `diagonals` or `windows`
+    /// adds those units. A jigsaw board has a line reading `regions:`, followed by the region map
+    /// in the format that [`Regions`] is parsed from. Any other rule is written the way that
+    /// [`constraint::parse_directive`] reads it, such as `cage: 15 r1c1 r1c2`. If a declaration
+    /// doesn't make sense, `Err(())` is returned.
+    ///
+    /// Every digit in the string is taken to be a given.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut board = Board::empty();
+        let mut regions: Option<String> = None;
+        let mut in_regions = false;
+
+        let mut index = 0;
+        for line in s.lines() {
+            if line.trim_start().starts_with('+') {
+                continue;
+            }
+
+            let (keyword, arguments) = line.split_once(':').unwrap_or((line, ""));
+            let (keyword, arguments) = (keyword.trim(), arguments.trim());
+            let is_directive = match keyword {
+                "diagonals" => {
+                    board.variant.diagonals = true;
+                    true
+                }
+                "windows" => {
+                    board.variant.windows = true;
+                    true
+                }
+                "regions" => {
+                    regions = Some(arguments.to_string());
+                    true
+                }
+                _ => match constraint::parse_directive(keyword, arguments) {
+                    Some(constraint) => {
+                        board.constraints.push(constraint?);
+                        true
+                    }
+                    None => false,
+                },
+            };
+            if is_directive {
+                in_regions = keyword == "regions";
+                continue;
+            }
+            if in_regions {
+                regions.get_or_insert_default().push_str(line);
+                continue;
+            }
+
+            for c in line.chars() {
+                let entry = match c {
+                    '0' | '-' | '_' | '.' => None,
+                    '1'..='9' => Some(Entry::try_from(c as i32 - '0' as i32).unwrap()),
+                    _ => continue,
+                };
+
+                if index >= board.cells.len() {
+                    return Err(());
+                }
+                board.cells[index] = entry;
+                index += 1;
+            }
+        }
+
+        if let Some(regions) = regions {
+            board.variant.regions = Some(regions.parse()?);
+        }
+        board.mark_givens();
+        Ok(board)
+    }
+}
+
+impl std::fmt::Display for Board {
+    /// Write the board in the same format that the puzzle files use.
+    ///
+    /// Empty cells are written as dashes, every group of three columns is separated by a space,
+    /// and every group of three rows is separated by a blank line. Any variant rules are declared
+    /// after a blank line, except for rules which can't be written down (see
+    /// [`Constraint::directive`]). The output can be parsed back into an identical board with
+    /// [`str::parse`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in 0..9 {
+            if row != 0 && row % 3 == 0 {
+                writeln!(f)?;
+            }
+
+            for column in 0..9 {
+                if column != 0 && column % 3 == 0 {
+                    write!(f, " ")?;
+                }
+
+                match self.get_cell(row, column) {
+                    Some(entry) => write!(f, "{entry}")?,
+                    None => write!(f, "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        let mut directives = Vec::new();
+        if self.variant.diagonals {
+            directives.push("diagonals".to_string());
+        }
+        if self.variant.windows {
+            directives.push("windows".to_string());
+        }
+        if let Some(regions) = &self.variant.regions {
+            directives.push(format!("regions:\n{}", regions.to_string().trim_end()));
+        }
+        directives.extend(self.constraints.iter().filter_map(|rule| rule.directive()));
+
+        if !directives.is_empty() {
+            writeln!(f)?;
+        }
+        for directive in directives {
+            writeln!(f, "{directive}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything about how a board is shown in a tab, apart from the digits in it.
+///
+/// This is the selection, the pencil marks, and whatever is highlighted or animated. The solvers
+/// copy boards all the time and have no use for any of it, so it is kept apart from the
+/// [`Board`], and every tab has one of these alongside its board.
+#[derive(Debug, Clone)]
+pub struct BoardView {
+    /// The color that filled in digits are drawn in, to tell them apart from the givens.
+    filled_color: Color,
+
+    /// Whether empty cells are tinted by how many candidates they have left.
+    heatmap: bool,
+
+    /// The hint being shown on the board, if any.
+    hint: Option<Hint>,
+
+    /// The digit that was last typed, which is highlighted everywhere on the board unless the
+    /// selected cell holds a digit of its own.
+    typed_digit: Option<Entry>,
+
+    /// This variable is for the UI, it controls which square is currently selected. The selected
+    /// square is highlighted with a red border, and the user can edit the number in that square.
+    selected_square: Option<usize>,
+
+    /// Every selected cell, including the selected square. Pencil marks are toggled in all of them
+    /// at once.
+    selection: [bool; 81],
+
+    /// Whether the mouse was pressed on a cell and hasn't been let go yet, so that the cells it is
+    /// dragged over are added to the selection.
+    dragging: bool,
+
+    /// The cell under the mouse, which is lightly highlighted so that it is clear which cell a
+    /// click would select.
+    hovered_square: Option<usize>,
+
+    /// The pencil marks that the user has jotted down in each cell. These are only notes, so the
+    /// solvers ignore them, and they are only drawn in empty cells.
+    pencil_marks: [Candidates; 81],
+
+    /// The digits which are still appearing in or disappearing from each cell.
+    animations: [Option<CellAnimation>; 81],
+
+    /// The reasoning behind the last logical step or hint, while it is still being shown.
+    annotation: Option<Annotation>,
+
+    /// The cells that were found to be wrong the last time the player checked their progress,
+    /// while they are still being flagged.
+    flagged: Option<FlaggedCells>,
+}
+
+impl BoardView {
+    /// Create a view with nothing selected, highlighted, or pencil marked.
+    pub const fn new() -> BoardView {
+        BoardView {
+            filled_color: Color::BLUE,
+            heatmap: false,
+            hint: None,
+            typed_digit: None,
+            selected_square: None,
+            selection: [false; 81],
+            dragging: false,
+            hovered_square: None,
+            pencil_marks: [Candidates::NONE; 81],
+            animations: [None; 81],
+            annotation: None,
+            flagged: None,
+        }
+    }
+
+    /// Rub out the pencil marks, and stop showing any hint, annotation, or flagged cells, for when
+    /// the board is reset. The selection is left alone.
+    pub fn reset(&mut self) {
+        self.pencil_marks = [Candidates::NONE; 81];
+        self.hint = None;
+        self.annotation = None;
+        self.flagged = None;
+    }
+
+    /// Animate a change to a cell which has just been made, given what the cell held before.
+    ///
+    /// New digits grow and fade in, and digits which were rubbed out fade away. Nothing calls this
+    /// automatically, since the solvers change boards far too often for every change to be worth
+    /// animating. It is up to whatever shows the board to pick out the changes to animate.
+    pub fn animate_change(&mut self, board: &Board, index: usize, before: Option<Entry>) {
+        let erased = match (board.cells[index], before) {
+            (Some(_), _) => None,
+            (None, Some(before)) => Some(before),
+            (None, None) => return,
+        };
+        self.animations[index] = Some(CellAnimation {
+            started: Instant::now(),
+            erased,
+        });
+    }
+
+    /// Set the color that filled in digits are drawn in. Givens are always drawn in black.
+    pub fn set_filled_color(&mut self, color: Color) {
+        self.filled_color = color;
+    }
+
+    /// Check whether empty cells are tinted by how many candidates they have left.
+    pub const fn heatmap(&self) -> bool {
+        self.heatmap
+    }
+
+    /// Turn the candidate heatmap on or off. While it is on, each empty cell is tinted from green
+    /// (only one candidate left) to red (every digit is still possible), and cells with no
+    /// candidates at all are gray.
+    pub fn set_heatmap(&mut self, heatmap: bool) {
+        self.heatmap = heatmap;
+    }
+
+    /// Show a hint on the board, or stop showing one with [`None`].
+    ///
+    /// The cell that the hint fills in is highlighted, and so are the cells that the reasoning
+    /// depends on. The hint stops being shown once its cell is filled in.
+    pub fn set_hint(&mut self, hint: Option<Hint>) {
+        self.hint = hint;
+    }
+
+    /// Show the reasoning behind some logical steps on the board for a moment, replacing whatever
+    /// was shown before. Like [`BoardView::animate_change`], nothing calls this automatically.
+    pub fn annotate(&mut self, deductions: Vec<Deduction>) {
+        self.annotation = Some(Annotation::new(deductions));
+    }
+
+    /// Flag some cells as wrong for a moment, without saying what should go in them.
+    pub fn flag(&mut self, cells: Vec<usize>) {
+        self.flagged = Some(FlaggedCells {
+            cells,
+            started: Instant::now(),
+        });
+    }
+
+    /// Get the digit which is highlighted in every cell that holds it.
+    ///
+    /// This is the digit in the selected cell, or if that is empty, the digit that was typed last.
+    pub fn highlighted_digit(&self, board: &Board) -> Option<Entry> {
+        self.selected_square
+            .and_then(|index| board.cells[index])
+            .or(self.typed_digit)
+    }
+
+    /// Get the index of the selected cell, if there is one.
+    pub const fn selected(&self) -> Option<usize> {
+        self.selected_square
+    }
+
+    /// Select the cell at the supplied index on its own, or clear the selection with [`None`].
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected_square = index.filter(|&index| index < 81);
+        self.selection = [false; 81];
+        if let Some(index) = self.selected_square {
+            self.selection[index] = true;
+        }
+    }
+
+    /// Add the cell at the supplied index to the selection, or take it out if it is already
+    /// selected.
+    ///
+    /// A cell which is added becomes the selected square. If the selected square is taken out,
+    /// another selected cell takes its place.
+    pub fn toggle_selected(&mut self, index: usize) {
+        self.selection[index] = !self.selection[index];
+        if self.selection[index] {
+            self.selected_square = Some(index);
+        } else if self.selected_square == Some(index) {
+            self.selected_square = (0..81).find(|&index| self.selection[index]);
+        }
+    }
+
+    /// Check whether the cell at the supplied index is selected, either as the selected square
+    /// or along with it.
+    pub const fn is_selected(&self, index: usize) -> bool {
+        self.selection[index]
+    }
+
+    /// Get the index of every selected cell, in index order.
+    pub fn selection_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..81).filter(|&index| self.selection[index])
+    }
+
+    /// Toggle a pencil mark in every selected cell which is empty, as a single move which can be
+    /// undone in one go.
+    ///
+    /// If every one of those cells already has the mark, it is taken away from all of them.
+    /// Otherwise, it is added to the ones which don't.
+    pub fn toggle_selected_pencil_marks(
+        &mut self,
+        board: &mut Board,
+        history: &mut MoveHistory,
+        entry: Entry,
+    ) {
+        let cells: Vec<_> = self
+            .selection_indices()
+            .filter(|&index| board.cells[index].is_none())
+            .collect();
+        let remove = cells
+            .iter()
+            .all(|&index| self.pencil_marks[index].contains(entry));
+        let moves = cells
+            .into_iter()
+            .filter_map(|index| {
+                let before = self.pencil_marks[index];
+                let mut after = before;
+                if remove {
+                    after.remove(entry);
+                } else {
+                    after.insert(entry);
+                }
+                let change = Change::PencilMarks { before, after };
+                (before != after).then_some(Move { index, change })
+            })
+            .collect();
+        self.make_moves(board, history, moves);
+    }
+
+    /// Get the pencil marks in the cell at the supplied index.
+    pub const fn pencil_marks(&self, index: usize) -> Candidates {
+        self.pencil_marks[index]
+    }
+
+    /// Add a pencil mark to the cell at the supplied index, or take it away if it is already there.
+    pub fn toggle_pencil_mark(&mut self, index: usize, entry: Entry) {
+        self.pencil_marks[index].toggle(entry);
+    }
+
+    /// Make a change by hand, remembering it in the history so that it can be undone.
     ///
-    /// # Panics
-    ///
-    /// This function panics if the index is at least 9.
-    pub fn get_big_cell(&self, index: usize) -> Vec<Option<Entry>> {
-        let small_index = as_small_index(index);
-        vec![
-            self.cells[small_index],
-            self.cells[small_index + 1],
-            self.cells[small_index + 2],
-            self.cells[small_index + 9],
-            self.cells[small_index + 10],
-            self.cells[small_index + 11],
-            self.cells[small_index + 18],
-            self.cells[small_index + 19],
-            self.cells[small_index + 20],
-        ]
+    /// The history is kept apart from the board, since the solvers clone boards all the time and
+    /// have no use for it.
+    pub fn make_move(&mut self, board: &mut Board, history: &mut MoveHistory, mv: Move) {
+        self.apply_move(board, mv);
+        history.push(mv);
     }
 
-    /// Set the cell at the target index to the specified value.
-    ///
-    /// The board has exactly 81 cells, so this function will do nothing if the index is greater
-    /// than 80. Additionally, all cells must be in the range [1, 9], so if the supplied entry is
-    /// not in that range, the function will do nothing. To clear the entry at the target index,
-    /// you can pass [`None`].
-    pub fn set_cell_index(&mut self, index: usize, entry: Option<Entry>) {
-        if index < self.cells.len() {
-            self.cells[index] = entry;
+    /// Make several changes by hand, remembering them so that they can be undone together.
+    pub fn make_moves(&mut self, board: &mut Board, history: &mut MoveHistory, moves: Vec<Move>) {
+        for &mv in &moves {
+            self.apply_move(board, mv);
         }
+        history.push_group(moves);
     }
 
-    /// Retrieve the index of the first unfilled cell.
-    ///
-    /// Imagine that the rows of the board are positioned one after another. The first unfilled
-    /// cell is the first cell from the left which contains no entry. If there is no such cell,
-    /// e.g. all cells have been filled, then [`None`] is returned.
-    pub fn first_unfilled_index(&self) -> Option<usize> {
-        self.cells
-            .into_iter()
-            .enumerate()
-            .find(|(_, x)| x.is_none())
-            .map(|(index, _)| index)
+    /// Take back the last change in the history, which was made with [`BoardView::make_move`] or
+    /// [`BoardView::make_moves`]. If there is nothing to undo, `false` is returned.
+    pub fn undo(&mut self, board: &mut Board, history: &mut MoveHistory) -> bool {
+        let Some(moves) = history.undo() else {
+            return false;
+        };
+        for mv in moves {
+            self.apply_move(board, mv);
+        }
+        true
     }
 
-    /// Check whether or not a board is valid.
-    ///
-    /// A board is valid if every row, column, and big cell contains every digit at most once. For
-    /// instance, a board is not valid if a row contains two 2's.
-    pub fn is_valid(&self) -> bool {
-        let mut result = true;
+    /// Make the last undone change again. If there is nothing to redo, `false` is returned.
+    pub fn redo(&mut self, board: &mut Board, history: &mut MoveHistory) -> bool {
+        let Some(moves) = history.redo() else {
+            return false;
+        };
+        for mv in moves {
+            self.apply_move(board, mv);
+        }
+        true
+    }
+
+    /// Toggle pencil marks in the selected cells when a digit is typed with Shift held down, and
+    /// undo and redo those edits with Ctrl+Z and Ctrl+Y, keeping track of them in the history.
+    pub fn edit(&mut self, board: &mut Board, input: &InputState, history: &mut MoveHistory) {
+        if input.shift {
+            for (key, entry) in DIGIT_KEYS.into_iter().zip(Entry::ALL) {
+                if input.key_pressed(key) {
+                    self.toggle_selected_pencil_marks(board, history, entry);
+                }
+            }
+        }
 
-        for index in 0..9 {
-            let row = self.get_row(index);
-            let column = self.get_column(index);
-            let big_cell = self.get_big_cell(index);
+        if Action::Undo.pressed(input) {
+            self.undo(board, history);
+        }
+        if Action::Redo.pressed(input) {
+            self.redo(board, history);
+        }
+    }
 
-            result = result && !has_duplicates(row.iter().filter_map(|&x| x));
-            result = result && !has_duplicates(column.iter().filter_map(|&x| x));
-            result = result && !has_duplicates(big_cell.iter().filter_map(|&x| x));
+    /// Change the board as a move says to, without touching the history.
+    fn apply_move(&mut self, board: &mut Board, mv: Move) {
+        match mv.change {
+            Change::Entry { after, .. } => board.set_cell_index(mv.index, after),
+            Change::PencilMarks { after, .. } => self.pencil_marks[mv.index] = after,
         }
+    }
 
-        result
+    /// Draw the board like [`BoardView::draw`], except that the grid and the givens are taken from
+    /// the cache if it is up to date. Otherwise the whole board is drawn, as usual.
+    pub fn draw_cached(
+        &self,
+        d: &mut RaylibDrawHandle,
+        board: &Board,
+        rect: Rectangle,
+        cache: &BoardCache,
+    ) {
+        let layer = board.static_layer(rect);
+        let cached = cache
+            .texture
+            .as_ref()
+            .filter(|_| cache.drawn_for.as_ref() == Some(&layer));
+        self.draw_layers(d, board, rect, cached);
     }
 
-    /// Get the color of the cell at the supplied index.
-    fn get_cell_color(&self, d: &mut RaylibDrawHandle, rect: Rectangle, index: usize) -> Color {
-        let mouse_position = d.get_mouse_position();
-        let mouse_index = point_to_index(rect, mouse_position);
+    /// Draw the board, taking the grid and the givens from `cached` if it is supplied, and drawing
+    /// them from scratch if not.
+    fn draw_layers(
+        &self,
+        d: &mut RaylibDrawHandle,
+        board: &Board,
+        rect: Rectangle,
+        cached: Option<&RenderTexture2D>,
+    ) {
+        let extra_units = board.variant.extra_units();
+        let conflicts = board.conflicts();
+        let conflicting: HashSet<usize> = conflicts
+            .iter()
+            .flat_map(|&(first, second)| [first, second])
+            .collect();
+        let hint = self
+            .hint
+            .as_ref()
+            .filter(|hint| board.cells[hint.index].is_none());
+        let highlighted_digit = self.highlighted_digit(board);
+        let selected_units: HashSet<usize> = match self.selected_square {
+            Some(selected) => [
+                Unit::Row(selected / 9),
+                Unit::Column(selected % 9),
+                Unit::Box(board.variant.box_of(selected)),
+            ]
+            .into_iter()
+            .flat_map(|unit| board.variant.cells(unit))
+            .collect(),
+            None => HashSet::new(),
+        };
+
+        let cell_rects = cell_rects(rect);
+        let flagged = self
+            .flagged
+            .as_ref()
+            .and_then(|flagged| Some((&flagged.cells, flagged.visibility()?)));
+
+        for (row, column) in (0..9).cartesian_product(0..9) {
+            let index = (row * 9) + (column % 9);
+            let cell_rect = cell_rects[index];
+            let cell_color = self.get_cell_color(index);
+            // Windows are drawn over diagonals, since they are the bigger shapes to pick out.
+            let shaded: Vec<_> = extra_units
+                .iter()
+                .filter(|unit| unit.cells().contains(&index))
+                .collect();
+            let fill = if conflicting.contains(&index) {
+                CONFLICT_CELL_COLOR
+            } else if hint.is_some_and(|hint| hint.index == index) {
+                HINT_CELL_COLOR
+            } else if hint.is_some_and(|hint| hint.support.contains(&index)) {
+                HINT_SUPPORT_COLOR
+            } else if highlighted_digit.is_some() && board.cells[index] == highlighted_digit {
+                HIGHLIGHTED_DIGIT_COLOR
+            } else if self.heatmap && board.cells[index].is_none() {
+                heatmap_color(board.candidates(index).len())
+            } else if shaded.iter().any(|unit| matches!(unit, Unit::Window(_))) {
+                WINDOW_CELL_COLOR
+            } else if !shaded.is_empty() {
+                DIAGONAL_CELL_COLOR
+            } else if let Some(regions) = &board.variant.regions {
+                REGION_CELL_COLORS[regions.region_of(index)]
+            } else {
+                Color::WHITE
+            };
+            let fill = if selected_units.contains(&index) {
+                shade(fill)
+            } else {
+                fill
+            };
+            let fill = if self.hovered_square == Some(index) {
+                mix(fill, HOVERED_CELL_COLOR, 0.3)
+            } else {
+                fill
+            };
+            let fill = match flagged {
+                Some((cells, visible)) if cells.contains(&index) => {
+                    mix(fill, FLAGGED_CELL_COLOR, visible)
+                }
+                _ => fill,
+            };
+
+            draw_cell(d, cell_rect, cell_color, fill);
+            let animation = self.animations[index]
+                .and_then(|animation| Some((animation.erased, animation.visibility()?)));
+            match (board.get_cell_index(index), animation) {
+                // Givens are drawn along with the grid.
+                (Some(_), _) if board.givens[index] => {}
+                (Some(entry), animation) => {
+                    // A digit can be written over one which is still fading away, and that
+                    // shouldn't fade the new digit too.
+                    let visible = match animation {
+                        Some((None, visible)) => visible,
+                        _ => 1.0,
+                    };
+                    draw_cell_entry(d, cell_rect, entry, false, self.filled_color, visible);
+                }
+                (None, Some((Some(erased), visible))) => {
+                    draw_pencil_marks(d, cell_rect, self.pencil_marks[index]);
+                    draw_cell_entry(d, cell_rect, erased, false, self.filled_color, visible);
+                }
+                (None, _) => draw_pencil_marks(d, cell_rect, self.pencil_marks[index]),
+            }
+        }
+
+        match cached {
+            Some(texture) => {
+                // Textures are stored upside down, so the source is flipped to draw them upright.
+                let (width, height) = (texture.width() as f32, texture.height() as f32);
+                let source = Rectangle::new(0.0, 0.0, width, -height);
+                let position = Vector2::new(rect.x, rect.y);
+                d.draw_texture_rec(texture.texture(), source, position, Color::WHITE);
+            }
+            None => board.draw_static(d, rect),
+        }
+        for constraint in &board.constraints {
+            constraint.draw(d, &cell_rects);
+        }
+        if let Some(annotation) = &self.annotation {
+            annotation.draw(d, board, &cell_rects);
+        }
 
-        match (self.selected_square, mouse_index) {
-            (Some(_), _) => Color::RED,
-            (None, Some(mouse_idx)) if mouse_idx == index => Color::LIGHTPINK,
-            _ => Color::RAYWHITE,
+        // Joining up each pair makes it clear which cells are fighting over a digit.
+        let center =
+            |rect: Rectangle| Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+        for (first, second) in conflicts {
+            let (from, to) = (center(cell_rects[first]), center(cell_rects[second]));
+            d.draw_line_ex(from, to, 3.0, Color::RED.alpha(0.6));
         }
     }
-}
 
-impl std::str::FromStr for Board {
-    type Err = ();
+    /// Get the color of the border of the cell at the supplied index.
+    fn get_cell_color(&self, index: usize) -> Color {
+        if self.selection[index] {
+            Color::RED
+        } else if self.hovered_square == Some(index) {
+            Color::LIGHTPINK
+        } else {
+            Color::RAYWHITE
+        }
+    }
 
-    /// Convert a string into a board.
-    ///
-    /// Strictly speaking, the string does not need a particular format. The function skips past
-    /// any characters that are not the digits 1 through 9, or an underscore. All of the cells in
-    /// the board are initialized one by one as digits are found in the string. For example, the
-    /// strings "16_9____52___456_9_9__3_7_2 6____7_939___1___747_3_9__8
-    /// 7_2_8_956__629___4__9_____1" and
-    ///
-    /// +-------+-------+-------+
-    /// | 1 6 _ | 9 _ _ | _ _ 5 |
-    /// | 2 _ _ | _ 4 5 | 6 _ 9 |
-    /// | _ 9 _ | _ 3 _ | 7 _ 2 |
-    /// +-------+-------+-------+
-    /// | 6 _ _ | _ _ 7 | _ 9 3 |
-    /// | 9 _ _ | _ 1 _ | _ _ 7 |
-    /// | 4 7 _ | 3 _ 9 | _ _ 8 |
-    /// +-------+-------+-------+
-    /// | 7 _ 2 | _ 8 _ | 9 5 6 |
-    /// | _ _ 6 | 2 9 _ | _ _ 4 |
-    /// | _ _ 9 | _ _ _ | _ _ 1 |
-    /// +-------+-------+-------+
+    /// Select a cell when it is clicked on. Typing a digit on its own highlights it everywhere on
+    /// the board. Edits which can be undone are made by [`BoardView::edit`] instead.
     ///
-    /// parse to the same board.
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut board = Board::empty();
-        let mut index = 0;
-        for c in s.chars() {
-            match c {
-                '-' => {
-                    board.cells[index] = None;
-                    index += 1;
-                }
-                '1'..='9' => {
-                    let entry = Entry::try_from(c as i32 - '0' as i32).unwrap();
-                    board.cells[index] = Some(entry);
-                    index += 1;
+    /// Dragging the mouse across cells selects all of them, and Ctrl+clicking a cell adds it to
+    /// the selection or takes it out.
+    pub fn update(&mut self, input: &InputState, rect: Rectangle) {
+        self.hovered_square = point_to_index(rect, input.mouse_position);
+        if input.mouse_pressed {
+            match self.hovered_square {
+                Some(index) if input.ctrl => self.toggle_selected(index),
+                hovered => self.select(hovered),
+            }
+            self.dragging = self.hovered_square.is_some() && !input.ctrl;
+        }
+        if !input.mouse_down {
+            self.dragging = false;
+        }
+        if self.dragging
+            && let Some(index) = self.hovered_square
+        {
+            self.selection[index] = true;
+        }
+
+        if !input.shift && !input.ctrl {
+            for (key, entry) in DIGIT_KEYS.into_iter().zip(Entry::ALL) {
+                if input.key_pressed(key) {
+                    self.typed_digit = Some(entry);
                 }
-                _ => {}
             }
         }
-        Ok(board)
+    }
+
+    /// Draw the board into `rect`, the way that the view shows it.
+    pub fn draw(&self, d: &mut RaylibDrawHandle, board: &Board, rect: Rectangle) {
+        self.draw_layers(d, board, rect, None);
     }
 }
 
-/// Compute the size of each cell.
-const fn compute_cell_size(board_size: Vector2) -> Vector2 {
-    Vector2 {
-        x: (board_size.x - ui::LINE_WIDTH * 4.0) / 9.0,
-        y: (board_size.y - ui::LINE_WIDTH * 4.0) / 9.0,
+impl Default for BoardView {
+    fn default() -> BoardView {
+        BoardView::new()
     }
 }
 
-/// Get the line width offset for the specified cell.
-///
-/// In order to get the cells lined up correctly inside of the grid, this function will account
-/// for the line width and return the corrected position. That was a horrible way of explaining
-/// it, but nobody is looking at this code anyway.
-fn line_width_offset(cell_index: usize) -> f32 {
-    (cell_index / 3 + 1) as f32 * ui::LINE_WIDTH
+/// Get where each cell of a board drawn into the supplied rectangle ends up, in index order.
+pub fn cell_rects(rect: Rectangle) -> [Rectangle; 81] {
+    std::array::from_fn(|index| ui::cell_rect(rect, index))
 }
 
-fn compute_cell_rect(row: usize, column: usize, cell_size: Vector2) -> Rectangle {
-    Rectangle {
-        x: column as f32 * cell_size.x + line_width_offset(column),
-        y: row as f32 * cell_size.y + line_width_offset(row),
-        width: cell_size.x,
-        height: cell_size.y,
+/// The colors of the regions on a jigsaw board. Neighbouring regions are told apart by color, since
+/// the thick lines only work for square boxes.
+const REGION_CELL_COLORS: [Color; 9] = [
+    Color::new(255, 235, 235, 255),
+    Color::new(235, 245, 255, 255),
+    Color::new(255, 250, 225, 255),
+    Color::new(235, 255, 240, 255),
+    Color::new(245, 235, 255, 255),
+    Color::new(255, 240, 225, 255),
+    Color::new(225, 250, 250, 255),
+    Color::new(250, 235, 245, 255),
+    Color::new(240, 240, 240, 255),
+];
+
+/// The color of cells which hold the same digit as another cell in one of their units.
+const CONFLICT_CELL_COLOR: Color = Color::new(255, 170, 170, 255);
+
+/// The color of the cell that a hint fills in.
+const HINT_CELL_COLOR: Color = Color::new(255, 215, 90, 255);
+
+/// The color of the cells that a hint's reasoning depends on.
+const HINT_SUPPORT_COLOR: Color = Color::new(255, 240, 190, 255);
+
+/// The color of cells holding the highlighted digit.
+const HIGHLIGHTED_DIGIT_COLOR: Color = Color::new(190, 220, 255, 255);
+
+/// The color of cells on the diagonals in Sudoku X.
+const DIAGONAL_CELL_COLOR: Color = Color::new(225, 225, 245, 255);
+
+/// The color of cells in the windows of hyper Sudoku.
+const WINDOW_CELL_COLOR: Color = Color::new(215, 240, 220, 255);
+
+/// The color that the cell under the mouse is tinted towards.
+const HOVERED_CELL_COLOR: Color = Color::LIGHTPINK;
+
+/// The color that wrong cells are flagged in when the player checks their progress.
+const FLAGGED_CELL_COLOR: Color = Color::new(240, 90, 90, 255);
+
+/// Get the heatmap color for a cell with the supplied number of candidates.
+fn heatmap_color(candidates: usize) -> Color {
+    const FEW: Color = Color::new(170, 235, 170, 255);
+    const MANY: Color = Color::new(255, 160, 160, 255);
+    if candidates == 0 {
+        return Color::GRAY;
     }
+
+    let t = (candidates - 1) as f32 / 8.0;
+    let mix = |few: u8, many: u8| (few as f32 + (many as f32 - few as f32) * t).round() as u8;
+    Color::new(
+        mix(FEW.r, MANY.r),
+        mix(FEW.g, MANY.g),
+        mix(FEW.b, MANY.b),
+        255,
+    )
+}
+
+/// Darken a color a little, for shading the row, column, and box of the selected cell. Darkening
+/// rather than replacing the color keeps any other shading visible underneath.
+fn shade(color: Color) -> Color {
+    let darken = |channel: u8| (channel as f32 * 0.9) as u8;
+    Color::new(darken(color.r), darken(color.g), darken(color.b), color.a)
+}
+
+/// Blend `amount` of `other` into a color, keeping the first color's alpha.
+fn mix(color: Color, other: Color, amount: f32) -> Color {
+    let blend = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount).round() as u8;
+    Color::new(
+        blend(color.r, other.r),
+        blend(color.g, other.g),
+        blend(color.b, other.b),
+        color.a,
+    )
 }
 
 /// Draw the cell decoration.
-fn draw_cell(d: &mut RaylibDrawHandle, rect: Rectangle, color: Color) {
+fn draw_cell(d: &mut RaylibDrawHandle, rect: Rectangle, color: Color, fill: Color) {
     let padding_x = rect.width / 10.0;
     let padding_y = rect.height / 10.0;
     let inner_rect = Rectangle {
@@ -376,80 +1698,221 @@ fn draw_cell(d: &mut RaylibDrawHandle, rect: Rectangle, color: Color) {
     };
 
     d.draw_rectangle_rec(rect, color);
-    d.draw_rectangle_rec(inner_rect, Color::WHITE);
+    d.draw_rectangle_rec(inner_rect, fill);
 }
 
-fn draw_cell_entry(d: &mut RaylibDrawHandle, rect: Rectangle, entry: Entry) {
-    let font = d.get_font_default();
-    let text = entry.to_string();
-    let dimensions = font.measure_text(&text, ui::FONT_SIZE, ui::FONT_SPACING);
+/// How long it takes for a digit to appear in or disappear from a cell.
+const ANIMATION_LENGTH: Duration = Duration::from_millis(150);
 
-    d.draw_text_ex(
-        font,
-        &text,
-        Vector2 {
-            x: rect.x + (rect.width - dimensions.x) / 2.0,
-            y: rect.y + (rect.height - dimensions.y) / 2.0,
-        },
-        ui::FONT_SIZE,
-        ui::FONT_SPACING,
-        Color::BLACK,
-    );
+/// A digit which has just been written into a cell or rubbed out of one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CellAnimation {
+    started: Instant,
+
+    /// The digit which was rubbed out, and is fading away. If this is [`None`], the digit in the
+    /// cell is appearing instead.
+    erased: Option<Entry>,
 }
 
-/// Draw the board outline.
-///
-/// The outline helps to see the big cells. Without it, the small cells floating around on the
-/// screen are pretty hard to visually parse.
-fn draw_board_outline(d: &mut RaylibDrawHandle, rect: Rectangle) {
-    // This looks odd, but it just makes sure that the lines are evenly spaced horizontally and
-    // vertically.
-    let x_jump = (rect.width - ui::LINE_WIDTH) / 3.0;
-    for x in 0..4 {
-        d.draw_rectangle_rec(
-            Rectangle {
-                x: x as f32 * x_jump,
-                y: 0.0,
-                width: ui::LINE_WIDTH,
-                height: rect.height,
-            },
-            Color::BLACK,
-        );
+/// How long wrong cells stay flagged after checking progress.
+const FLAG_LENGTH: Duration = Duration::from_millis(2000);
+
+/// The cells which were wrong when the player last checked their progress.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FlaggedCells {
+    cells: Vec<usize>,
+    started: Instant,
+}
+
+impl FlaggedCells {
+    /// Get how strongly the cells are flagged, from one when they are first flagged down to zero,
+    /// or [`None`] once the flags have faded away.
+    fn visibility(&self) -> Option<f32> {
+        let progress = self.started.elapsed().as_secs_f32() / FLAG_LENGTH.as_secs_f32();
+        (progress < 1.0).then(|| f32::min(1.0, (1.0 - progress) * 2.0))
     }
+}
 
-    let y_jump = (rect.height - ui::LINE_WIDTH) / 3.0;
-    for y in 0..4 {
-        d.draw_rectangle_rec(
-            Rectangle {
-                x: 0.0,
-                y: y as f32 * y_jump,
-                width: rect.width,
-                height: ui::LINE_WIDTH,
-            },
-            Color::BLACK,
-        );
+impl CellAnimation {
+    /// Get how visible the digit is, from zero for invisible to one for fully drawn, or [`None`]
+    /// once the animation is over.
+    ///
+    /// The animation eases out, so that digits move quickly at first and then settle.
+    fn visibility(&self) -> Option<f32> {
+        let progress = self.started.elapsed().as_secs_f32() / ANIMATION_LENGTH.as_secs_f32();
+        if progress >= 1.0 {
+            return None;
+        }
+        let eased = 1.0 - (1.0 - progress).powi(3);
+        Some(match self.erased {
+            Some(_) => 1.0 - eased,
+            None => eased,
+        })
     }
 }
 
-impl Widget for Board {
-    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
-        let cell_size = compute_cell_size(Vector2::new(rect.width, rect.height));
+/// What the grid and the givens looked like when they were drawn into a [`BoardCache`].
+#[derive(Debug, Clone, PartialEq)]
+struct StaticLayer {
+    givens: [Option<Entry>; 81],
 
-        for (row, column) in (0..9).cartesian_product(0..9) {
-            let index = (row * 9) + (column % 9);
-            let cell_rect = compute_cell_rect(row, column, cell_size);
-            let cell_color = self.get_cell_color(d, rect, index);
+    /// The size of the texture, in pixels.
+    size: (u32, u32),
+    boxes: bool,
+    font_size: f32,
+    scale: f32,
+}
 
-            draw_cell(d, cell_rect, cell_color);
-            if let Some(entry) = self.get_cell_index(index) {
-                draw_cell_entry(d, cell_rect, entry);
-            }
+/// The grid and the givens of a board, drawn into a texture once and then reused every frame.
+///
+/// At high step rates, drawing the whole board from scratch every frame is what holds the program
+/// back. The grid and the givens only change when a new puzzle is opened or the window is
+/// resized, so they are kept in a texture, and the cells and everything that moves are drawn
+/// around them with [`BoardView::draw_cached`].
+#[derive(Debug, Default)]
+pub struct BoardCache {
+    texture: Option<RenderTexture2D>,
+    drawn_for: Option<StaticLayer>,
+}
+
+impl BoardCache {
+    /// Create an empty cache, which draws the board from scratch until it is first updated.
+    pub fn new() -> BoardCache {
+        BoardCache::default()
+    }
+
+    /// Draw the grid and the givens of the board into the texture again, if they have changed
+    /// since last time. This has to be called before drawing starts for the frame.
+    ///
+    /// If the texture can't be made, the board is drawn from scratch every frame instead.
+    pub fn update(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        board: &Board,
+        rect: Rectangle,
+    ) {
+        let layer = board.static_layer(rect);
+        if self.drawn_for.as_ref() == Some(&layer) {
+            return;
+        }
+        let (width, height) = layer.size;
+        if self
+            .drawn_for
+            .as_ref()
+            .is_none_or(|drawn| drawn.size != layer.size)
+        {
+            self.texture = rl.load_render_texture(thread, width, height).ok();
         }
+        self.drawn_for = None;
+        let Some(texture) = &mut self.texture else {
+            return;
+        };
+        let mut d = rl.begin_texture_mode(thread, texture);
+        d.clear_background(Color::BLANK);
+        board.draw_static(&mut d, Rectangle::new(0.0, 0.0, rect.width, rect.height));
+        self.drawn_for = Some(layer);
+    }
+}
+
+/// Draw the digit in a cell. Givens are drawn in bold black, and anything else in `color`.
+///
+/// Digits which are only partly `visible` are drawn smaller and fainter, for animating them.
+fn draw_cell_entry<D>(
+    d: &mut D,
+    rect: Rectangle,
+    entry: Entry,
+    given: bool,
+    color: Color,
+    visible: f32,
+) where
+    D: RaylibDraw + std::ops::Deref<Target = RaylibHandle>,
+{
+    // The fonts have no bold face, so givens are drawn twice, a pixel apart.
+    let (offsets, color): (&[f32], _) = if given {
+        (&[0.0, 1.0], Color::BLACK)
+    } else {
+        (&[0.0], color)
+    };
+    let font_size = ui::font_size() * (0.5 + 0.5 * visible);
+    for &offset in offsets {
+        let rect = Rectangle {
+            x: rect.x + offset,
+            ..rect
+        };
+        ui::draw_text_centered(d, &entry.to_string(), rect, font_size, color.alpha(visible));
+    }
+}
+
+/// Draw pencil marks in a 3x3 grid inside a cell, with each digit in the same spot as on a phone
+/// keypad.
+fn draw_pencil_marks(d: &mut RaylibDrawHandle, rect: Rectangle, marks: Candidates) {
+    let font_size = ui::font_size() * 0.4;
+    for entry in marks.iter() {
+        let digit = entry as usize;
+        let slot = Rectangle {
+            x: rect.x + (digit % 3) as f32 * rect.width / 3.0,
+            y: rect.y + (digit / 3) as f32 * rect.height / 3.0,
+            width: rect.width / 3.0,
+            height: rect.height / 3.0,
+        };
+        ui::draw_text_centered(d, &entry.to_string(), slot, font_size, Color::DARKGRAY);
+    }
+}
+
+/// Get where one of the lines of the grid goes across or down a board, as its offset from the
+/// edge of the board and its width. The lines are numbered from 0 at the top or left edge to 9 at
+/// the bottom or right edge.
+///
+/// The edges and the lines between boxes are thick, and the cells are laid out with room left
+/// for them. The lines between cells in the same box are thin, and are drawn over the edges of the
+/// cells on either side. Without `boxes`, the lines between boxes are thin as well, in the middle
+/// of the room left for the thick ones.
+fn gridline(line: usize, cell_size: f32, boxes: bool) -> (f32, f32) {
+    let room = if line.is_multiple_of(3) {
+        ui::line_width()
+    } else {
+        0.0
+    };
+    let center = line as f32 * cell_size + line.div_ceil(3) as f32 * ui::line_width() + room / 2.0;
+    let width = if line.is_multiple_of(3) && (boxes || line.is_multiple_of(9)) {
+        ui::line_width()
+    } else {
+        ui::thin_line_width()
+    };
+    (center - width / 2.0, width)
+}
+
+/// Draw the grid of the board.
+///
+/// The thick lines help to see the boxes. Without them, the small cells floating around on the
+/// screen are pretty hard to visually parse. Without `boxes`, only the outer border is thick.
+fn draw_grid(d: &mut impl RaylibDraw, rect: Rectangle, boxes: bool) {
+    let cell_size = ui::cell_size(Vector2::new(rect.width, rect.height));
+    for line in 0..=9 {
+        let (x, width) = gridline(line, cell_size.x, boxes);
+        let column = Rectangle::new(rect.x + x, rect.y, width, rect.height);
+        d.draw_rectangle_rec(column, Color::BLACK);
 
-        draw_board_outline(d, rect);
+        let (y, height) = gridline(line, cell_size.y, boxes);
+        let row = Rectangle::new(rect.x, rect.y + y, rect.width, height);
+        d.draw_rectangle_rec(row, Color::BLACK);
     }
 }
 
+/// The keys for the digits 1 to 9, in order.
+pub const DIGIT_KEYS: [KeyboardKey; 9] = [
+    KeyboardKey::KEY_ONE,
+    KeyboardKey::KEY_TWO,
+    KeyboardKey::KEY_THREE,
+    KeyboardKey::KEY_FOUR,
+    KeyboardKey::KEY_FIVE,
+    KeyboardKey::KEY_SIX,
+    KeyboardKey::KEY_SEVEN,
+    KeyboardKey::KEY_EIGHT,
+    KeyboardKey::KEY_NINE,
+];
+
 /// Convert a cell's position to an index.
 ///
 /// In board space, points are pairs of integers 0-8. In other words, a point is a pair of indices
@@ -462,13 +1925,18 @@ fn cell_pos_to_index(x: usize, y: usize) -> Option<usize> {
     }
 }
 
-/// Convert a point in screen space to a board index.
+/// Convert a point in screen space to the index of the cell under it, where `rect` is the rectangle
+/// that the board is drawn into.
+///
+/// The lines between boxes take up room of their own, so [`None`] is returned for a point on one
+/// of them, as well as for a point outside of the board. Every other point lands in the cell that
+/// [`ui::cell_rect`] puts there.
 pub fn point_to_index(rect: Rectangle, point: Vector2) -> Option<usize> {
     let board_size = Vector2::new(rect.width, rect.height);
     let relative_point = point - Vector2::new(rect.x, rect.y);
     let point_no_grid = ui::without_gridlines(board_size, relative_point)?;
-    let cell_size = compute_cell_size(board_size);
-    
+    let cell_size = ui::cell_size(board_size);
+
     cell_pos_to_index(
         (point_no_grid.x / cell_size.x) as usize,
         (point_no_grid.y / cell_size.y) as usize,
@@ -480,6 +1948,7 @@ mod tests {
     use std::str::FromStr;
 
     use super::*;
+    use crate::exact_cover::SudokuCover;
 
     fn create_board() -> Board {
         Board::from_str(
@@ -656,5 +2125,346 @@ mod tests {
         assert!(board.is_valid());
         board.set_cell_index(2, Some(Entry::Six));
         assert!(!board.is_valid());
+
+        // Digits past 9 only fit on bigger boards.
+        board.set_cell_index(2, Some(Entry::Ten));
+        assert_eq!(board.get_cell_index(2), Some(Entry::Six));
+    }
+
+    #[test]
+    fn test_diagonal_variant() {
+        // The two cells only share the main diagonal.
+        let mut board = Board::empty();
+        board.set_cell_index(0, Some(Entry::One));
+        board.set_cell_index(40, Some(Entry::One));
+        assert!(board.is_valid());
+
+        board.set_variant(Variant {
+            diagonals: true,
+            ..Variant::STANDARD
+        });
+        assert!(!board.is_valid());
+        assert!(!board.candidates(80).contains(Entry::One));
+
+        let mut board = Board::empty();
+        board.set_variant(Variant {
+            diagonals: true,
+            ..Variant::STANDARD
+        });
+        assert!(crate::solver::solve(&mut board));
+        assert!(board.is_valid());
+        let anti_diagonal = Unit::Diagonal(1)
+            .cells()
+            .map(|index| board.get_cell_index(index));
+        assert!(anti_diagonal.iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn test_window_variant() {
+        let variant = Variant {
+            windows: true,
+            ..Variant::STANDARD
+        };
+        assert_eq!(variant.units_containing(10).len(), 4);
+        assert_eq!(variant.units_containing(0).len(), 3);
+
+        let mut board = Board::empty();
+        board.set_variant(variant);
+        let solution = SudokuCover::for_variant(variant).solve(&board).unwrap();
+        assert!(solution.is_valid());
+        let window = Unit::Window(3)
+            .cells()
+            .map(|index| solution.get_cell_index(index));
+        assert!(window.iter().all_unique());
+    }
+
+    #[test]
+    fn test_jigsaw_regions() {
+        let regions: Regions = "
+            aaaabbccc
+            aaabbbccc
+            aabbbbccc
+            dddeeefff
+            dddeeefff
+            dddeeefff
+            ggghhhiii
+            ggghhhiii
+            ggghhhiii"
+            .parse()
+            .unwrap();
+        assert_eq!(regions.cells(0), [0, 1, 2, 3, 9, 10, 11, 18, 19]);
+        // Swapping two cells between regions far apart leaves both regions in pieces.
+        let mut map = regions.0;
+        map.swap(0, 80);
+        assert_eq!(Regions::new(map), None);
+
+        let mut board = Board::empty();
+        board.set_variant(Variant {
+            regions: Some(regions),
+            ..Variant::STANDARD
+        });
+        let solution = SudokuCover::for_variant(board.variant())
+            .solve(&board)
+            .unwrap();
+        assert!(solution.is_valid());
+        assert_eq!(
+            solution.to_string().parse::<Board>().unwrap().variant(),
+            board.variant()
+        );
+
+        // The fourth cell of the top row has moved into the first region.
+        board.set_cell_index(3, Some(Entry::One));
+        board.set_cell_index(9, Some(Entry::One));
+        assert!(!board.is_valid());
+    }
+
+    #[test]
+    fn test_variant_declarations() {
+        let text = "
+            --- --- ---
+            --- --- ---
+            --- --- ---
+
+            --- --- ---
+            --- -5- ---
+            --- --- ---
+
+            --- --- ---
+            --- --- ---
+            --- --- ---
+
+            diagonals
+            anti-knight
+            cage: 10 r1c1 r1c2
+            greater: r9c9 r9c8
+            thermo: r2c1 r3c1 r4c1";
+        let board: Board = text.parse().unwrap();
+        assert!(board.variant().diagonals);
+        assert_eq!(board.constraints().len(), 4);
+        assert_eq!(board.get_cell_index(40), Some(Entry::Five));
+        assert_eq!(board.cells.iter().flatten().count(), 1);
+
+        let written = board.to_string();
+        assert_eq!(written.parse::<Board>().unwrap().to_string(), written);
+        assert!(text.replace("r1c2", "r1c0").parse::<Board>().is_err());
+
+        // The bulb of the thermometer needs room for two bigger digits after it.
+        assert!(!board.candidates(9).contains(Entry::Eight));
+        assert!(!board.candidates(27).contains(Entry::Two));
+    }
+
+    #[test]
+    fn test_pencil_marks() {
+        let board = Board::empty();
+        let mut view = BoardView::new();
+        view.toggle_pencil_mark(4, Entry::Two);
+        view.toggle_pencil_mark(4, Entry::Seven);
+        view.toggle_pencil_mark(4, Entry::Two);
+        assert_eq!(view.pencil_marks(4), Candidates::only(Entry::Seven));
+        // Pencil marks are only notes, so they don't narrow down the real candidates.
+        assert_eq!(board.candidates(4), Candidates::ALL);
+
+        // Resetting the board rubs them out.
+        view.reset();
+        assert!(view.pencil_marks(4).is_empty());
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut board = create_board();
+        assert!(board.conflicts().is_empty());
+
+        board.set_cell_index(2, Some(Entry::Five));
+        assert_eq!(board.conflicts(), vec![(2, 8)]);
+        // These two share both a column and a box, but are only listed once.
+        board.set_cell_index(20, Some(Entry::Five));
+        assert_eq!(board.conflicts(), vec![(2, 8), (2, 20)]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let board = create_board();
+        let mut other = board.clone();
+        assert!(board.diff(&other).is_empty());
+
+        other.set_cell_index(1, Some(Entry::One));
+        other.set_cell_index(2, Some(Entry::Four));
+        // Cell 2 is empty on the first board, so it can't be wrong.
+        assert_eq!(board.diff(&other), vec![1]);
+    }
+
+    #[test]
+    fn test_givens() {
+        let mut board = create_board();
+        assert!(board.is_given(0));
+        assert!(!board.is_given(2));
+
+        board.set_cell_index(2, Some(Entry::Four));
+        board.set_cell_index(0, Some(Entry::One));
+        assert!(!board.is_given(2));
+        assert!(!board.is_given(0));
+
+        board.mark_givens();
+        assert!(board.is_given(2));
+
+        board.set_cell_index(4, Some(Entry::Seven));
+        let original = board.original();
+        assert_eq!(original.get_cell_index(2), Some(Entry::Four));
+        assert_eq!(original.get_cell_index(4), None);
+
+        board.reset();
+        assert_eq!(board.get_cell_index(2), Some(Entry::Four));
+        assert_eq!(board.get_cell_index(4), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let board = create_board();
+        let json = board.to_json();
+        assert!(json.starts_with(r#"{"rows":[[1,6,null,9,null,null,null,null,5],[2,"#));
+        assert!(json.ends_with("[null,null,9,null,null,null,null,null,1]]}"));
+
+        let csv = board.to_csv();
+        assert_eq!(csv.lines().count(), 9);
+        assert_eq!(csv.lines().next(), Some("1,6,,9,,,,,5"));
+        assert_eq!(csv.lines().last(), Some(",,9,,,,,,1"));
+    }
+
+    #[test]
+    fn test_edit() {
+        let mut board = create_board();
+        let mut view = BoardView::new();
+        let mut history = MoveHistory::new();
+        view.select(Some(2));
+        let input = InputState {
+            shift: true,
+            keys_pressed: vec![KeyboardKey::KEY_FOUR, KeyboardKey::KEY_A],
+            ..InputState::default()
+        };
+        view.edit(&mut board, &input, &mut history);
+        assert!(view.pencil_marks(2).contains(Entry::Four));
+        assert_eq!(view.pencil_marks(2).len(), 1);
+
+        let undo = InputState {
+            ctrl: true,
+            keys_pressed: vec![KeyboardKey::KEY_Z],
+            ..InputState::default()
+        };
+        view.edit(&mut board, &undo, &mut history);
+        assert!(view.pencil_marks(2).is_empty());
+        assert!(view.redo(&mut board, &mut history));
+        assert!(view.pencil_marks(2).contains(Entry::Four));
+    }
+
+    #[test]
+    fn test_multiple_selection() {
+        let mut board = create_board();
+        let mut view = BoardView::new();
+        let mut history = MoveHistory::new();
+        view.select(Some(2));
+        view.toggle_selected(3);
+        view.toggle_selected(4);
+        assert_eq!(view.selected(), Some(4));
+        view.toggle_selected(4);
+        assert_eq!(view.selected(), Some(2));
+        assert_eq!(view.selection_indices().collect::<Vec<_>>(), vec![2, 3]);
+
+        // Cell 3 holds a 9, so it is left alone.
+        view.toggle_pencil_mark(2, Entry::Four);
+        view.toggle_selected(5);
+        view.toggle_selected_pencil_marks(&mut board, &mut history, Entry::Four);
+        assert!(view.pencil_marks(2).contains(Entry::Four));
+        assert!(view.pencil_marks(5).contains(Entry::Four));
+        assert!(view.pencil_marks(3).is_empty());
+        view.toggle_selected_pencil_marks(&mut board, &mut history, Entry::Four);
+        assert!(view.pencil_marks(2).is_empty());
+        assert!(view.pencil_marks(5).is_empty());
+
+        // Both toggles come undone in one go each.
+        assert!(view.undo(&mut board, &mut history));
+        assert!(view.pencil_marks(5).contains(Entry::Four));
+        assert!(view.undo(&mut board, &mut history));
+        assert!(view.pencil_marks(5).is_empty());
+
+        view.select(Some(7));
+        assert!(!view.is_selected(2));
+    }
+
+    #[test]
+    fn test_highlighted_digit() {
+        let board = create_board();
+        let mut view = BoardView::new();
+        assert_eq!(view.highlighted_digit(&board), None);
+
+        let input = InputState {
+            keys_pressed: vec![KeyboardKey::KEY_SEVEN],
+            ..InputState::default()
+        };
+        view.update(&input, Rectangle::new(0.0, 0.0, 512.0, 512.0));
+        assert_eq!(view.highlighted_digit(&board), Some(Entry::Seven));
+
+        // The digit in the selected cell wins, unless the cell is empty.
+        view.select(Some(1));
+        assert_eq!(view.highlighted_digit(&board), Some(Entry::Six));
+        view.select(Some(2));
+        assert_eq!(view.highlighted_digit(&board), Some(Entry::Seven));
+    }
+
+    #[test]
+    fn test_point_to_index() {
+        let rect = Rectangle::new(30.0, 60.0, 490.0, 400.0);
+        for index in 0..81 {
+            let cell = ui::cell_rect(rect, index);
+            let middle = Vector2::new(cell.x + cell.width / 2.0, cell.y + cell.height / 2.0);
+            assert_eq!(point_to_index(rect, middle), Some(index));
+        }
+
+        // Between the first and second boxes, and outside the board entirely.
+        let gap = ui::cell_rect(rect, 2);
+        let gap = Vector2::new(gap.x + gap.width + 1.0, gap.y + 1.0);
+        assert_eq!(point_to_index(rect, gap), None);
+        assert_eq!(point_to_index(rect, Vector2::new(10.0, 100.0)), None);
+    }
+
+    #[test]
+    fn test_static_layer() {
+        let mut board = create_board();
+        let rect = Rectangle::new(0.0, 0.0, 512.0, 512.0);
+        let layer = board.static_layer(rect);
+        assert_eq!(layer.givens[0], Some(Entry::One));
+        assert_eq!(layer.givens[2], None);
+
+        // Filling in a digit leaves the cached layer alone, but resizing the board doesn't.
+        board.set_cell_index(2, Some(Entry::Three));
+        assert_eq!(board.static_layer(rect), layer);
+        assert_ne!(
+            board.static_layer(Rectangle::new(0.0, 0.0, 256.0, 256.0)),
+            layer
+        );
+    }
+
+    #[test]
+    fn test_gridline() {
+        let rects = cell_rects(Rectangle::new(0.0, 0.0, 490.0, 490.0));
+        let cell_size = rects[0].width;
+
+        // The thick lines fill the room between boxes exactly.
+        assert_eq!(gridline(0, cell_size, true), (0.0, ui::line_width()));
+        let (x, width) = gridline(3, cell_size, true);
+        assert_eq!(x + width, rects[3].x);
+        assert_eq!(x, rects[2].x + rects[2].width);
+
+        // The thin lines sit on the edge between two cells.
+        let (x, width) = gridline(1, cell_size, true);
+        assert_eq!(
+            (width, x + width / 2.0),
+            (ui::thin_line_width(), rects[1].x)
+        );
+        let (x, width) = gridline(3, cell_size, false);
+        assert_eq!(width, ui::thin_line_width());
+        assert_eq!(
+            x + width / 2.0,
+            (rects[2].x + rects[2].width + rects[3].x) / 2.0
+        );
     }
 }
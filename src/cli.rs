@@ -0,0 +1,444 @@
+//! The command line, which picks between opening puzzles in the window and the commands which
+//! work on puzzles without one.
+//!
+//! Puzzle files named on their own are opened in the window, as they always have been, so
+//! `sudoku-solver puzzle.txt` is short for `sudoku-solver gui puzzle.txt`. The older `--generate`
+//! and `--headless` flags still work too, as other names for `generate` and `solve`.
+
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+use crate::board::Board;
+use crate::rating::Difficulty;
+use crate::sized::SizedBoard;
+
+/// Watch Sudoku puzzles being solved step by step, or solve, make, and rate them from the command
+/// line.
+#[derive(Debug, Clone, PartialEq, Eq, Parser)]
+#[command(version, subcommand_negates_reqs = true)]
+pub struct Cli {
+    /// What to do. With no command, the puzzle files are opened in the window.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Puzzle files to open in the window, each in its own tab.
+    #[arg(required = true)]
+    pub boards: Vec<String>,
+
+    /// Reload each puzzle whenever its file changes.
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Say more about what the solver is doing on standard error. Once shows the techniques that
+    /// are applied and how long things take, and twice shows every step of the search.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Say nothing on standard error except for warnings and errors.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+impl Cli {
+    /// Get the most detailed level of log messages which should be shown.
+    pub fn log_level(&self) -> log::LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => log::LevelFilter::Warn,
+            (false, 0) => log::LevelFilter::Info,
+            (false, 1) => log::LevelFilter::Debug,
+            (false, _) => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// The commands that can be run from the command line.
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// Solve a puzzle and print the solution, without opening a window.
+    #[command(long_flag = "headless")]
+    Solve {
+        /// The puzzle file to solve, or with `--all`, a collection of puzzles one per line. Use `-`
+        /// to read from standard input.
+        board: String,
+
+        /// Solve every puzzle in the collection, printing each solution and how long it took.
+        #[arg(long)]
+        all: bool,
+
+        /// Solve the puzzles in the collection on every core at once.
+        #[arg(long, requires = "all")]
+        parallel: bool,
+
+        /// How to print the solutions. This is `grid` for a single puzzle, and `line` with
+        /// `--all` or when the puzzle comes from standard input.
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Give up with a timeout status if solving takes longer than this many seconds.
+        #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+        timeout: Option<Duration>,
+
+        /// Solve the puzzle again whenever its file changes, until interrupted.
+        #[arg(long, conflicts_with_all = ["all", "timeout"])]
+        watch: bool,
+    },
+
+    /// Make new puzzles with exactly one solution each, and write them to a file one per line, or
+    /// to a directory with one file each.
+    #[command(long_flag = "generate")]
+    Generate {
+        /// How many puzzles to make.
+        #[arg(value_name = "COUNT", conflicts_with = "count")]
+        number: Option<usize>,
+
+        /// The file to write the puzzles to.
+        #[arg(required_unless_present = "out", conflicts_with = "out")]
+        file: Option<String>,
+
+        /// How many puzzles to make, when writing them to a directory. One is made by default.
+        #[arg(long, requires = "out")]
+        count: Option<usize>,
+
+        /// Write each puzzle to its own file in this directory instead, which is made if needed.
+        #[arg(long, value_name = "DIR")]
+        out: Option<String>,
+
+        /// Only make puzzles that are rated this hard.
+        #[arg(long, value_enum)]
+        difficulty: Option<Difficulty>,
+
+        /// Write the solution to each puzzle to a file of its own as well.
+        #[arg(long, requires = "out")]
+        solutions: bool,
+
+        /// Make the same puzzles every time the same seed is used.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// Say how hard a puzzle is, by the hardest technique needed to solve it, and list the
+    /// techniques that were needed.
+    #[command(long_flag = "rate")]
+    Rate {
+        /// The puzzle file to rate.
+        board: String,
+    },
+
+    /// Check that a puzzle follows the rules and has exactly one solution.
+    Check {
+        /// The puzzle file to check.
+        board: String,
+    },
+
+    /// Count the solutions to a puzzle, stopping once there are as many as the limit.
+    #[command(long_flag = "count-solutions")]
+    Count {
+        /// The puzzle file to count the solutions of.
+        board: String,
+
+        /// Stop counting at this many solutions, and print the count with a `+` after it.
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        limit: u64,
+    },
+
+    /// Say which digit can be filled in next, and the technique that shows it.
+    Hint {
+        /// The puzzle file to give a hint for.
+        board: String,
+    },
+
+    /// Watch a puzzle being solved in the terminal, for machines without a display.
+    #[command(long_flag = "tui")]
+    Tui {
+        /// The puzzle file to solve.
+        board: String,
+    },
+
+    /// Time a solving strategy on every puzzle in a suite, and print a table of the results.
+    Bench {
+        /// The suite to run, with one puzzle per line.
+        suite: String,
+
+        /// The way that each puzzle is solved.
+        #[arg(long, value_enum, default_value_t = StrategyName::Backtracking)]
+        strategy: StrategyName,
+
+        /// The seed which shuffles the digit order for the `seeded` strategy.
+        #[arg(long, required_if_eq("strategy", "seeded"))]
+        seed: Option<u64>,
+
+        /// Print the report as JSON instead of a table, for keeping track of results over time.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Open puzzles in the window, each in its own tab.
+    Gui {
+        /// The puzzle files to open.
+        #[arg(required = true)]
+        boards: Vec<String>,
+
+        /// Reload each puzzle whenever its file changes.
+        #[arg(long)]
+        watch: bool,
+    },
+}
+
+/// The strategies that `bench` can run a suite with, as named on the command line. See
+/// [`Strategy`](crate::bench::Strategy) for what each of them does.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum StrategyName {
+    Backtracking,
+    Seeded,
+    Parallel,
+    Logic,
+}
+
+/// The ways that `solve` can print a solution. Only the grid and the line can be opened again as
+/// puzzles; the others are for handing the solution to other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Nine rows of nine digits, grouped into boxes, like the puzzle files.
+    Grid,
+
+    /// All 81 cells on one line, as written by [`Board::to_line`].
+    Line,
+
+    /// A JSON object, as written by [`Board::to_json`].
+    Json,
+
+    /// One line of comma-separated values for each row, as written by [`Board::to_csv`].
+    Csv,
+}
+
+impl OutputFormat {
+    /// Write the board in this format, ending with a newline.
+    pub fn write(self, board: &Board) -> String {
+        match self {
+            Self::Grid => board.to_string(),
+            Self::Line => board.to_line() + "\n",
+            Self::Json => board.to_json() + "\n",
+            Self::Csv => board.to_csv(),
+        }
+    }
+
+    /// Write a board of a size other than 9x9 in this format, ending with a newline.
+    pub fn write_sized(self, board: &SizedBoard) -> String {
+        match self {
+            Self::Grid => board.to_string(),
+            Self::Line => board.to_line() + "\n",
+            Self::Json => board.to_json() + "\n",
+            Self::Csv => board.to_csv(),
+        }
+    }
+}
+
+/// Parse a number of seconds, which may have a fractional part, into a duration.
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    s.parse()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("{s:?} is not a number of seconds"))
+}
+
+/// The status that the program exits with, so that scripts can tell what happened without reading
+/// the messages on standard error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// Everything went as asked.
+    Success,
+
+    /// Something else went wrong, such as a file that couldn't be read or written.
+    Failure,
+
+    /// The command line couldn't be understood. This is the status that clap exits with.
+    Usage,
+
+    /// A puzzle file doesn't contain a board.
+    ParseError,
+
+    /// A puzzle breaks the rules.
+    Invalid,
+
+    /// A puzzle has no solution.
+    Unsolvable,
+
+    /// Solving took longer than the `--timeout` allowed.
+    Timeout,
+}
+
+impl Status {
+    /// Get the exit code for this status.
+    pub fn code(self) -> i32 {
+        match self {
+            Status::Success => 0,
+            Status::Failure => 1,
+            Status::Usage => 2,
+            Status::ParseError => 3,
+            Status::Invalid => 4,
+            Status::Unsolvable => 5,
+            Status::Timeout => 6,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Option<Cli> {
+        Cli::try_parse_from(std::iter::once("sudoku-solver").chain(args.iter().copied())).ok()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cli = parse(&["a.txt", "b.txt"]).unwrap();
+        assert_eq!((cli.command, cli.boards.len()), (None, 2));
+
+        let solve = Some(Command::Solve {
+            board: "a.txt".to_string(),
+            all: false,
+            parallel: false,
+            format: None,
+            timeout: None,
+            watch: false,
+        });
+        assert_eq!(parse(&["solve", "a.txt"]).unwrap().command, solve);
+        assert_eq!(parse(&["--headless", "a.txt"]).unwrap().command, solve);
+
+        assert!(parse(&["solve", "-"]).is_some());
+        assert_eq!(parse(&["solve", "--watch", "--all", "puzzles.sdm"]), None);
+        assert!(parse(&["--watch", "a.txt"]).unwrap().watch);
+
+        let all = parse(&[
+            "solve",
+            "--all",
+            "--parallel",
+            "--format",
+            "json",
+            "puzzles.sdm",
+        ]);
+        assert_eq!(
+            all.unwrap().command,
+            Some(Command::Solve {
+                board: "puzzles.sdm".to_string(),
+                all: true,
+                parallel: true,
+                format: Some(OutputFormat::Json),
+                timeout: None,
+                watch: false,
+            })
+        );
+
+        let generate = parse(&["--generate", "10", "book.sdm", "--seed", "42"]).unwrap();
+        assert_eq!(
+            generate.command,
+            Some(Command::Generate {
+                number: Some(10),
+                file: Some("book.sdm".to_string()),
+                count: None,
+                out: None,
+                difficulty: None,
+                solutions: false,
+                seed: Some(42),
+            })
+        );
+
+        let generate = "generate --difficulty hard --count 10 --out dir/ --solutions";
+        let generate = parse(&generate.split(' ').collect::<Vec<_>>()).unwrap();
+        assert_eq!(
+            generate.command,
+            Some(Command::Generate {
+                number: None,
+                file: None,
+                count: Some(10),
+                out: Some("dir/".to_string()),
+                difficulty: Some(Difficulty::Hard),
+                solutions: true,
+                seed: None,
+            })
+        );
+        assert_eq!(parse(&["generate", "--count", "10"]), None);
+        assert_eq!(parse(&["generate", "--count", "10", "book.sdm"]), None);
+        assert_eq!(
+            parse(&["generate", "10", "--count", "10", "--out", "dir"]),
+            None
+        );
+        assert_eq!(parse(&["generate", "10", "book.sdm", "--out", "dir"]), None);
+        assert_eq!(parse(&["generate", "book.sdm", "--solutions"]), None);
+
+        assert_eq!(
+            parse(&["--count-solutions", "a.txt"]).unwrap().command,
+            Some(Command::Count {
+                board: "a.txt".to_string(),
+                limit: 2,
+            })
+        );
+        assert_eq!(parse(&["count", "--limit", "0", "a.txt"]), None);
+        assert_eq!(
+            parse(&["--rate", "a.txt"]).unwrap().command,
+            Some(Command::Rate {
+                board: "a.txt".to_string(),
+            })
+        );
+
+        assert_eq!(
+            parse(&["--tui", "a.txt"]).unwrap().command,
+            Some(Command::Tui {
+                board: "a.txt".to_string(),
+            })
+        );
+
+        let bench = parse(&["bench", "suite.sdm", "--strategy", "seeded", "--seed", "7"]);
+        assert_eq!(
+            bench.unwrap().command,
+            Some(Command::Bench {
+                suite: "suite.sdm".to_string(),
+                strategy: StrategyName::Seeded,
+                seed: Some(7),
+                json: false,
+            })
+        );
+        assert_eq!(parse(&["bench", "suite.sdm", "--strategy", "seeded"]), None);
+
+        let verbose = parse(&["rate", "a.txt", "-vv"]).unwrap();
+        assert_eq!(verbose.log_level(), log::LevelFilter::Trace);
+        let quiet = parse(&["--quiet", "a.txt"]).unwrap();
+        assert_eq!(quiet.log_level(), log::LevelFilter::Warn);
+        assert_eq!(parse(&["-q", "-v", "a.txt"]), None);
+
+        // There is nothing to do without a puzzle.
+        assert_eq!(parse(&[]), None);
+        assert_eq!(parse(&["gui"]), None);
+        assert_eq!(parse(&["rate"]), None);
+        assert_eq!(parse(&["solve", "--parallel", "a.txt"]), None);
+        assert_eq!(parse(&["solve", "--format", "xml", "a.txt"]), None);
+
+        let timeout = parse(&["solve", "--timeout", "1.5", "a.txt"]).unwrap();
+        let Some(Command::Solve { timeout, .. }) = timeout.command else {
+            panic!("expected the solve command");
+        };
+        assert_eq!(timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(parse(&["solve", "--timeout", "-1", "a.txt"]), None);
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let statuses = [
+            Status::Success,
+            Status::Failure,
+            Status::Usage,
+            Status::ParseError,
+            Status::Invalid,
+            Status::Unsolvable,
+            Status::Timeout,
+        ];
+        let mut codes: Vec<i32> = statuses.iter().map(|status| status.code()).collect();
+        assert_eq!(codes[0], 0);
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), statuses.len());
+    }
+}
@@ -0,0 +1,560 @@
+//! Rules which a board can be played with, as pieces that can be mixed and matched.
+//!
+//! A board checks its entries and works out its candidates by going through a list of
+//! constraints. The first is always the board's [`Variant`], which covers every rule that can be
+//! written as a unit holding each digit once. Anything else, like killer cages or the anti-knight
+//! rule, is added on top with [`Board::add_constraint`].
+//!
+//! In puzzle files, each constraint is written on a line of its own after the grid, as a keyword
+//! followed by a colon and its arguments, like `cage: 15 r1c1 r1c2`. Cells are named the same way
+//! as in the [solve log](crate::solve_log::cell_name).
+//!
+//! The backtracking search and the exact cover solver look at the whole list. The logic engine
+//! only knows about units, so it treats a board with extra constraints as if the extra rules
+//! weren't there. Its deductions are still sound, it just misses some.
+
+use std::sync::Arc;
+
+use raylib::prelude::*;
+
+use crate::board::{Board, Candidates, Entry, Variant, has_duplicates};
+use crate::exact_cover::SudokuCover;
+use crate::solve_log::{cell_name, parse_cell_name};
+
+/// A rule which the entries on a board have to follow.
+pub trait Constraint: std::fmt::Debug + Send + Sync {
+    /// Check that the entries on a board don't break the rule.
+    ///
+    /// Empty cells are allowed to be empty, so a board which can't possibly be finished may still
+    /// pass the check. It only has to fail once the entries themselves break the rule.
+    fn check(&self, board: &Board) -> bool;
+
+    /// Remove the digits which would break the rule from the candidates of the empty cell at the
+    /// supplied index.
+    fn prune(&self, board: &Board, index: usize, candidates: &mut Candidates);
+
+    /// Add the rule to the rules of an exact cover problem, so that
+    /// [`solver::solve_fast`](crate::solver::solve_fast) can follow it. Rules which can't be
+    /// written that way return `false`, which is the default, and boards with them are solved by
+    /// the usual search instead.
+    fn add_to_cover(&self, cover: &mut SudokuCover) -> bool {
+        let _ = cover;
+        false
+    }
+
+    /// Draw any markings that the rule needs on top of the board, given where each cell was drawn.
+    /// Most rules don't need any, so this does nothing by default.
+    fn draw(&self, d: &mut RaylibDrawHandle, cell_rects: &[Rectangle; 81]) {
+        let _ = (d, cell_rects);
+    }
+
+    /// Get the line which declares this rule in a puzzle file, which [`parse_directive`] reads
+    /// back. Rules which can't be written to a file return [`None`], which is the default.
+    fn directive(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Read a constraint from the keyword and arguments of a line in a puzzle file.
+///
+/// If the keyword isn't the name of a constraint, [`None`] is returned. If it is, but the
+/// arguments don't make sense, the result is `Some(Err(()))`.
+pub fn parse_directive(keyword: &str, arguments: &str) -> Option<Result<Arc<dyn Constraint>, ()>> {
+    fn shared(
+        constraint: Result<impl Constraint + 'static, ()>,
+    ) -> Result<Arc<dyn Constraint>, ()> {
+        Ok(Arc::new(constraint?))
+    }
+
+    Some(match keyword {
+        "anti-knight" if arguments.is_empty() => shared(Ok(AntiKnight)),
+        "anti-knight" => Err(()),
+        "cage" => shared(arguments.parse::<Killer>()),
+        "greater" => shared(arguments.parse::<Inequality>()),
+        "thermo" => shared(arguments.parse::<Thermo>()),
+        _ => return None,
+    })
+}
+
+/// Read a list of cell names separated by whitespace.
+fn parse_cells<'a>(names: impl Iterator<Item = &'a str>) -> Result<Vec<usize>, ()> {
+    names.map(|name| parse_cell_name(name).ok_or(())).collect()
+}
+
+/// Write a list of cell names separated by spaces.
+fn write_cells(cells: &[usize]) -> String {
+    cells
+        .iter()
+        .map(|&index| cell_name(index))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Constraint for Variant {
+    /// Check that no unit holds the same digit twice.
+    fn check(&self, board: &Board) -> bool {
+        self.units().into_iter().all(|unit| {
+            let cells = self.cells(unit);
+            !has_duplicates(
+                cells
+                    .into_iter()
+                    .filter_map(|index| board.get_cell_index(index)),
+            )
+        })
+    }
+
+    /// Remove the digits which already appear in one of the cell's units.
+    fn prune(&self, board: &Board, index: usize, candidates: &mut Candidates) {
+        for unit in self.units_containing(index) {
+            for peer in self.cells(unit) {
+                if let Some(entry) = board.get_cell_index(peer) {
+                    candidates.remove(entry);
+                }
+            }
+        }
+    }
+
+    fn add_to_cover(&self, cover: &mut SudokuCover) -> bool {
+        for unit in self.units() {
+            cover.add_region(self.cells(unit));
+        }
+        true
+    }
+}
+
+/// The anti-knight rule: two cells a chess knight's move apart can't hold the same digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AntiKnight;
+
+impl AntiKnight {
+    /// Get the cells which are a knight's move away from the cell at the supplied index.
+    fn attacks(index: usize) -> impl Iterator<Item = usize> {
+        const MOVES: [(isize, isize); 8] = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+
+        let row = (index / 9) as isize;
+        let column = (index % 9) as isize;
+        MOVES.into_iter().filter_map(move |(down, across)| {
+            let (row, column) = (row + down, column + across);
+            let on_board = (0..9).contains(&row) && (0..9).contains(&column);
+            on_board.then_some((row * 9 + column) as usize)
+        })
+    }
+}
+
+impl Constraint for AntiKnight {
+    fn check(&self, board: &Board) -> bool {
+        (0..81).all(|index| {
+            let Some(entry) = board.get_cell_index(index) else {
+                return true;
+            };
+            AntiKnight::attacks(index).all(|other| board.get_cell_index(other) != Some(entry))
+        })
+    }
+
+    fn prune(&self, board: &Board, index: usize, candidates: &mut Candidates) {
+        for other in AntiKnight::attacks(index) {
+            if let Some(entry) = board.get_cell_index(other) {
+                candidates.remove(entry);
+            }
+        }
+    }
+
+    fn add_to_cover(&self, cover: &mut SudokuCover) -> bool {
+        for index in 0..81 {
+            for other in AntiKnight::attacks(index).filter(|&other| other > index) {
+                cover.add_distinct(vec![index, other]);
+            }
+        }
+        true
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some("anti-knight".to_string())
+    }
+}
+
+/// A killer cage: a group of cells which can't repeat a digit and whose digits add up to a total.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Killer {
+    cells: Vec<usize>,
+    sum: u32,
+}
+
+impl Killer {
+    /// Create a cage covering the cells at the supplied indices, adding up to `sum`.
+    pub const fn new(cells: Vec<usize>, sum: u32) -> Killer {
+        Killer { cells, sum }
+    }
+
+    /// Get the indices of the cells in the cage.
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    /// Get the total that the cage adds up to.
+    pub const fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    /// Get the digits already written in the cage.
+    fn entries(&self, board: &Board) -> Vec<Entry> {
+        self.cells
+            .iter()
+            .filter_map(|&index| board.get_cell_index(index))
+            .collect()
+    }
+}
+
+/// Get the value of a digit, counting from 1.
+fn value(entry: Entry) -> u32 {
+    entry as u32 + 1
+}
+
+/// Get the smallest and largest totals that `count` different digits can add up to.
+const fn total_range(count: u32) -> (u32, u32) {
+    (count * (count + 1) / 2, count * (19 - count) / 2)
+}
+
+impl std::str::FromStr for Killer {
+    type Err = ();
+
+    /// Read a cage written as its total followed by the names of its cells, like `15 r1c1 r1c2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let sum = words.next().ok_or(())?.parse().map_err(|_| ())?;
+        let cells = parse_cells(words)?;
+        if cells.is_empty() || cells.len() > 9 {
+            return Err(());
+        }
+        Ok(Killer::new(cells, sum))
+    }
+}
+
+impl std::fmt::Display for Killer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.sum, write_cells(&self.cells))
+    }
+}
+
+impl Constraint for Killer {
+    fn check(&self, board: &Board) -> bool {
+        let entries = self.entries(board);
+        if has_duplicates(entries.iter()) {
+            return false;
+        }
+
+        let total: u32 = entries.iter().map(|&entry| value(entry)).sum();
+        let empty = (self.cells.len() - entries.len()) as u32;
+        let (low, high) = total_range(empty);
+        total + low <= self.sum && self.sum <= total + high
+    }
+
+    fn prune(&self, board: &Board, index: usize, candidates: &mut Candidates) {
+        if !self.cells.contains(&index) {
+            return;
+        }
+
+        let entries = self.entries(board);
+        let total: u32 = entries.iter().map(|&entry| value(entry)).sum();
+        let Some(remaining) = self.sum.checked_sub(total) else {
+            *candidates = Candidates::NONE;
+            return;
+        };
+
+        // Whatever goes in this cell, the other empty cells still have to make up the rest.
+        let others = (self.cells.len() - entries.len()) as u32 - 1;
+        let (low, high) = total_range(others);
+        for entry in candidates.iter() {
+            let fits = value(entry) + low <= remaining && remaining <= value(entry) + high;
+            if entries.contains(&entry) || !fits {
+                candidates.remove(entry);
+            }
+        }
+    }
+
+    fn add_to_cover(&self, cover: &mut SudokuCover) -> bool {
+        cover.add_cage(self.cells.clone(), self.sum);
+        true
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some(format!("cage: {self}"))
+    }
+}
+
+/// A "greater than" sign between two neighbouring cells, as in futoshiki.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Inequality {
+    greater: usize,
+    lesser: usize,
+}
+
+impl Inequality {
+    /// Create a sign saying that the cell at `greater` holds a bigger digit than the cell at
+    /// `lesser`.
+    ///
+    /// The two cells have to share an edge, otherwise [`None`] is returned.
+    pub const fn new(greater: usize, lesser: usize) -> Option<Inequality> {
+        let same_row = greater / 9 == lesser / 9 && greater.abs_diff(lesser) == 1;
+        let same_column = greater.abs_diff(lesser) == 9;
+        if greater < 81 && lesser < 81 && (same_row || same_column) {
+            Some(Inequality { greater, lesser })
+        } else {
+            None
+        }
+    }
+
+    /// Get the index of the cell holding the bigger digit.
+    pub const fn greater(&self) -> usize {
+        self.greater
+    }
+
+    /// Get the index of the cell holding the smaller digit.
+    pub const fn lesser(&self) -> usize {
+        self.lesser
+    }
+}
+
+impl std::str::FromStr for Inequality {
+    type Err = ();
+
+    /// Read a sign written as the names of the greater cell and then the lesser cell, like
+    /// `r1c1 r1c2`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let [greater, lesser] = parse_cells(s.split_whitespace())?[..] else {
+            return Err(());
+        };
+        Inequality::new(greater, lesser).ok_or(())
+    }
+}
+
+impl std::fmt::Display for Inequality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", write_cells(&[self.greater, self.lesser]))
+    }
+}
+
+impl Constraint for Inequality {
+    fn check(&self, board: &Board) -> bool {
+        match (
+            board.get_cell_index(self.greater),
+            board.get_cell_index(self.lesser),
+        ) {
+            (Some(greater), Some(lesser)) => greater as u8 > lesser as u8,
+            _ => true,
+        }
+    }
+
+    fn prune(&self, board: &Board, index: usize, candidates: &mut Candidates) {
+        // An empty neighbour still rules out the digit at the far end, since nothing can go past
+        // it.
+        let keep = |entry: Entry| {
+            if index == self.greater {
+                let lesser = board.get_cell_index(self.lesser).unwrap_or(Entry::One);
+                entry as u8 > lesser as u8
+            } else if index == self.lesser {
+                let greater = board.get_cell_index(self.greater).unwrap_or(Entry::Nine);
+                (entry as u8) < greater as u8
+            } else {
+                true
+            }
+        };
+        for entry in candidates.iter() {
+            if !keep(entry) {
+                candidates.remove(entry);
+            }
+        }
+    }
+
+    fn add_to_cover(&self, cover: &mut SudokuCover) -> bool {
+        for greater in Entry::ALL {
+            for lesser in Entry::ALL
+                .into_iter()
+                .filter(|&lesser| lesser as u8 >= greater as u8)
+            {
+                cover.add_conflict((self.greater, greater), (self.lesser, lesser));
+            }
+        }
+        true
+    }
+
+    /// Draw a small chevron on the edge between the cells, pointing at the smaller one.
+    fn draw(&self, d: &mut RaylibDrawHandle, cell_rects: &[Rectangle; 81]) {
+        let center =
+            |rect: Rectangle| Vector2::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+        let from = center(cell_rects[self.greater]);
+        let to = center(cell_rects[self.lesser]);
+        let length = (to - from).length();
+        let along = (to - from) / length;
+        let across = Vector2::new(-along.y, along.x);
+
+        let size = length / 8.0;
+        let middle = from.lerp(to, 0.5);
+        let tip = middle + along * size;
+        let back = middle - along * size;
+        d.draw_line_ex(back + across * size, tip, 3.0, Color::DARKGRAY);
+        d.draw_line_ex(back - across * size, tip, 3.0, Color::DARKGRAY);
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some(format!("greater: {self}"))
+    }
+}
+
+/// A thermometer: the digits have to get strictly bigger along a path of cells, starting from
+/// the bulb.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Thermo {
+    cells: Vec<usize>,
+}
+
+impl Thermo {
+    /// Create a thermometer along the cells at the supplied indices, starting from the bulb.
+    ///
+    /// A path longer than nine cells can never be filled in, so [`None`] is returned for those,
+    /// and for empty paths.
+    pub fn new(cells: Vec<usize>) -> Option<Thermo> {
+        (1..=9).contains(&cells.len()).then_some(Thermo { cells })
+    }
+
+    /// Get the indices of the cells along the thermometer, starting from the bulb.
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    /// Get the digits written along the thermometer, with their positions.
+    fn entries(&self, board: &Board) -> Vec<(usize, u32)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(position, &index)| Some((position, value(board.get_cell_index(index)?))))
+            .collect()
+    }
+}
+
+impl std::str::FromStr for Thermo {
+    type Err = ();
+
+    /// Read a thermometer written as the names of its cells, starting from the bulb.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Thermo::new(parse_cells(s.split_whitespace())?).ok_or(())
+    }
+}
+
+impl std::fmt::Display for Thermo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", write_cells(&self.cells))
+    }
+}
+
+impl Constraint for Thermo {
+    fn check(&self, board: &Board) -> bool {
+        // Every digit needs room for the cells before and after it, and every pair of digits needs
+        // room for the cells in between.
+        let entries = self.entries(board);
+        let after = |position: usize| (self.cells.len() - 1 - position) as u32;
+        let room = entries
+            .iter()
+            .all(|&(position, digit)| digit > position as u32 && digit + after(position) <= 9);
+        let increasing = entries.windows(2).all(|pair| {
+            let [(first, low), (second, high)] = pair else {
+                unreachable!("windows of two always have two elements");
+            };
+            high.checked_sub(*low)
+                .is_some_and(|gap| gap >= (second - first) as u32)
+        });
+        room && increasing
+    }
+
+    fn prune(&self, board: &Board, index: usize, candidates: &mut Candidates) {
+        let Some(position) = self.cells.iter().position(|&cell| cell == index) else {
+            return;
+        };
+
+        let mut low = position as u32 + 1;
+        let mut high = 9 - (self.cells.len() - 1 - position) as u32;
+        for (other, digit) in self.entries(board) {
+            let distance = other.abs_diff(position) as u32;
+            if other < position {
+                low = low.max(digit + distance);
+            } else {
+                high = high.min(digit.saturating_sub(distance));
+            }
+        }
+        for entry in candidates.iter() {
+            if !(low..=high).contains(&value(entry)) {
+                candidates.remove(entry);
+            }
+        }
+    }
+
+    /// Only neighbouring cells along the path are compared, since the digits keep getting bigger
+    /// all the way along once each step does.
+    fn add_to_cover(&self, cover: &mut SudokuCover) -> bool {
+        for pair in self.cells.windows(2) {
+            for first in Entry::ALL {
+                for second in Entry::ALL
+                    .into_iter()
+                    .filter(|&second| second as u8 <= first as u8)
+                {
+                    cover.add_conflict((pair[0], first), (pair[1], second));
+                }
+            }
+        }
+        true
+    }
+
+    fn directive(&self) -> Option<String> {
+        Some(format!("thermo: {self}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    #[test]
+    fn test_constraints_are_solved() {
+        let mut board = Board::empty();
+        board.add_constraint(AntiKnight);
+        board.add_constraint(Killer::new(vec![0, 1, 2], 6));
+
+        board.set_cell_index(40, Some(Entry::Five));
+        assert!(!board.candidates(23).contains(Entry::Five));
+        assert_eq!(board.candidates(0).len(), 3);
+
+        assert!(solver::solve(&mut board));
+        assert!(board.is_valid());
+        let cage: u32 = (0..3)
+            .map(|index| value(board.get_cell_index(index).unwrap()))
+            .sum();
+        assert_eq!(cage, 6);
+    }
+
+    #[test]
+    fn test_inequality() {
+        assert_eq!(Inequality::new(8, 9), None);
+        let inequality = Inequality::new(10, 1).unwrap();
+
+        let mut board = Board::empty();
+        board.add_constraint(inequality);
+        assert!(!board.candidates(10).contains(Entry::One));
+        assert!(!board.candidates(1).contains(Entry::Nine));
+
+        board.set_cell_index(1, Some(Entry::Seven));
+        let candidates: Vec<Entry> = board.candidates(10).iter().collect();
+        assert_eq!(candidates, [Entry::Eight, Entry::Nine]);
+        assert!(solver::solve(&mut board));
+        assert!(inequality.check(&board));
+    }
+}
@@ -0,0 +1,157 @@
+//! Making new puzzles by hand.
+//!
+//! In edit mode the user fills in the clues of a puzzle on an empty board. The puzzle is checked
+//! after every change, so it is clear straight away whether it can be solved, and whether the
+//! solution is unique. Once the puzzle is done, its digits are locked in as the givens.
+
+use raylib::prelude::*;
+
+use crate::board::{Board, BoardView, Entry};
+use crate::history::{Change, Move, MoveHistory};
+use crate::locale::Message;
+use crate::solver;
+use crate::ui::{self, Widget};
+
+/// What the checks made of a puzzle being edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    /// The same digit appears twice in a row, column, box, or other unit.
+    Invalid,
+
+    /// The puzzle follows the rules so far, but can't be finished.
+    Unsolvable,
+
+    /// The puzzle has exactly one solution, like a proper puzzle should.
+    Unique,
+
+    /// The puzzle has more than one solution, so it needs more clues.
+    Multiple,
+}
+
+impl Verdict {
+    /// Check a puzzle.
+    pub fn of(board: &Board) -> Verdict {
+        if !board.is_valid() {
+            return Verdict::Invalid;
+        }
+        match solver::count_solutions(board, 2) {
+            0 => Verdict::Unsolvable,
+            1 => Verdict::Unique,
+            _ => Verdict::Multiple,
+        }
+    }
+}
+
+/// A puzzle being made by hand.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Editor {
+    verdict: Verdict,
+
+    /// The board that the verdict is for, as written by [`Board::to_line`].
+    checked: String,
+}
+
+impl Editor {
+    /// Start editing the supplied board.
+    pub fn new(board: &Board) -> Editor {
+        Editor {
+            verdict: Verdict::of(board),
+            checked: board.to_line(),
+        }
+    }
+
+    /// Get what the checks made of the puzzle when it was last checked.
+    pub const fn verdict(&self) -> Verdict {
+        self.verdict
+    }
+
+    /// Check the puzzle again if it has changed since it was last checked.
+    ///
+    /// Edits can also be undone and redone behind the editor's back, so this should be called
+    /// every frame.
+    pub fn check(&mut self, board: &Board) {
+        let line = board.to_line();
+        if line != self.checked {
+            self.verdict = Verdict::of(board);
+            self.checked = line;
+        }
+    }
+
+    /// Write a clue into a cell, or rub one out with [`None`], and check the puzzle again.
+    ///
+    /// The change can be undone like any other edit.
+    pub fn enter(
+        &mut self,
+        board: &mut Board,
+        view: &mut BoardView,
+        history: &mut MoveHistory,
+        index: usize,
+        entry: Option<Entry>,
+    ) {
+        let change = Change::Entry {
+            before: board.get_cell_index(index),
+            after: entry,
+        };
+        view.make_move(board, history, Move { index, change });
+        self.check(board);
+    }
+
+    /// Finish editing, and lock in every digit on the board as a given.
+    ///
+    /// Puzzles which break the rules can't be locked in, so `false` is returned and the board is
+    /// left as it is if the puzzle is [`Verdict::Invalid`].
+    pub fn lock(self, board: &mut Board, view: &mut BoardView, history: &mut MoveHistory) -> bool {
+        if self.verdict == Verdict::Invalid {
+            return false;
+        }
+        board.mark_givens();
+        // Undoing past this point would take away givens.
+        board.reset();
+        view.reset();
+        history.clear();
+        true
+    }
+}
+
+impl Widget for Editor {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let (message, color) = match self.verdict {
+            Verdict::Invalid => (Message::EditingInvalid, Color::PINK),
+            Verdict::Unsolvable => (Message::EditingUnsolvable, Color::ORANGE),
+            Verdict::Unique => (Message::EditingUnique, Color::LIME),
+            Verdict::Multiple => (Message::EditingMultiple, Color::SKYBLUE),
+        };
+        let text = message.to_string();
+        d.draw_rectangle_rec(rect, color);
+        ui::draw_text_centered(d, &text, rect, ui::font_size() * 0.6, Color::BLACK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verdict() {
+        let mut board = Board::empty();
+        let mut view = BoardView::new();
+        let mut history = MoveHistory::new();
+        let mut editor = Editor::new(&board);
+        assert_eq!(editor.verdict(), Verdict::Multiple);
+
+        editor.enter(&mut board, &mut view, &mut history, 0, Some(Entry::One));
+        editor.enter(&mut board, &mut view, &mut history, 1, Some(Entry::One));
+        assert_eq!(editor.verdict(), Verdict::Invalid);
+        assert!(!editor.clone().lock(&mut board, &mut view, &mut history));
+
+        view.undo(&mut board, &mut history);
+        editor.check(&board);
+        assert_eq!(editor.verdict(), Verdict::Multiple);
+        assert!(editor.lock(&mut board, &mut view, &mut history));
+        assert!(board.is_given(0));
+        assert!(!view.undo(&mut board, &mut history));
+
+        let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        assert_eq!(Verdict::of(&puzzle), Verdict::Unique);
+    }
+}
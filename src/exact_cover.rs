@@ -0,0 +1,458 @@
+//! A general exact cover solver, and a way of describing Sudoku variants in terms of it.
+//!
+//! An exact cover problem is a table of rows and columns, where each row covers some of the
+//! columns. The goal is to pick a set of rows which covers every column exactly once. Sudoku fits
+//! this shape nicely: each row is a choice of digit for a cell, and the columns are the rules (each
+//! cell holds one digit, and each row, column, and big cell holds each digit once). New variants
+//! only need new columns, so the same solver handles diagonals, jigsaw regions, and so on. Rules
+//! which aren't about units, like killer cages and thermometers, are added by each
+//! [`Constraint`] through [`Constraint::add_to_cover`].
+//!
+//! The solver is Knuth's Algorithm X, using dancing links to undo its work cheaply while it
+//! backtracks.
+
+use crate::board::{Board, Entry, Unit, Variant};
+use crate::constraint::Constraint;
+
+/// An exact cover problem.
+///
+/// Columns come in two kinds. Primary columns have to be covered exactly once, while secondary
+/// columns only have to be covered at most once, which is handy for rules like "no digit repeats
+/// in this cage" where the cage is too small to need every digit.
+#[derive(Debug, Clone)]
+pub struct ExactCover {
+    // Every node lives in these arrays, linked into circular lists both across and down. Node 0 is
+    // the root, and nodes 1 to the number of columns are the column headers.
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+
+    /// The number of rows in each column which are still in play, indexed by header node.
+    size: Vec<usize>,
+    rows: usize,
+}
+
+impl ExactCover {
+    /// Create a problem with the supplied numbers of primary and secondary columns, and no rows.
+    ///
+    /// Primary columns are numbered first, from zero, followed by the secondary columns.
+    pub fn new(primary: usize, secondary: usize) -> ExactCover {
+        let headers = primary + secondary;
+        let mut cover = ExactCover {
+            left: Vec::with_capacity(headers + 1),
+            right: Vec::with_capacity(headers + 1),
+            up: Vec::with_capacity(headers + 1),
+            down: Vec::with_capacity(headers + 1),
+            column: Vec::with_capacity(headers + 1),
+            row: Vec::with_capacity(headers + 1),
+            size: vec![0; headers + 1],
+            rows: 0,
+        };
+
+        for node in 0..=headers {
+            // Only the primary headers are linked to the root, so the search never has to cover
+            // the secondary ones.
+            let (left, right) = match node {
+                0 if primary == 0 => (0, 0),
+                0 => (primary, 1),
+                node if node <= primary => (node - 1, if node == primary { 0 } else { node + 1 }),
+                node => (node, node),
+            };
+            cover.left.push(left);
+            cover.right.push(right);
+            cover.up.push(node);
+            cover.down.push(node);
+            cover.column.push(node);
+            cover.row.push(usize::MAX);
+        }
+        cover
+    }
+
+    /// Add a row which covers the supplied columns, returning the number of the row.
+    ///
+    /// Rows are numbered from zero in the order they are added, and solutions are reported in
+    /// terms of these numbers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the columns does not exist.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        let row = self.rows;
+        self.rows += 1;
+
+        let mut first = None;
+        for &column in columns {
+            let header = column + 1;
+            assert!(header < self.size.len(), "column {column} does not exist");
+
+            let node = self.column.len();
+            self.column.push(header);
+            self.row.push(row);
+            self.size[header] += 1;
+
+            // Insert the node at the bottom of its column.
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+
+            // Insert the node at the end of its row.
+            match first {
+                None => {
+                    self.left.push(node);
+                    self.right.push(node);
+                    first = Some(node);
+                }
+                Some(first) => {
+                    let last = self.left[first];
+                    self.left.push(last);
+                    self.right.push(first);
+                    self.right[last] = node;
+                    self.left[first] = node;
+                }
+            }
+        }
+        row
+    }
+
+    /// Find a solution, as the numbers of the chosen rows in increasing order.
+    ///
+    /// If there is no solution, [`None`] is returned.
+    pub fn solve(&mut self) -> Option<Vec<usize>> {
+        let mut solution = None;
+        self.search(&mut Vec::new(), &mut |rows| {
+            let mut rows = rows.to_vec();
+            rows.sort_unstable();
+            solution = Some(rows);
+            false
+        });
+        solution
+    }
+
+    /// Count the solutions, stopping once `limit` of them have been found.
+    ///
+    /// Counting up to 2 is the usual way to check that a puzzle has exactly one solution.
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        let mut count = 0;
+        if limit > 0 {
+            self.search(&mut Vec::new(), &mut |_| {
+                count += 1;
+                count < limit
+            });
+        }
+        count
+    }
+
+    /// Search for solutions, handing each one to `found` until it returns `false`.
+    ///
+    /// Returns `false` if the search was stopped early. The links are always left the way they
+    /// were found.
+    fn search(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        found: &mut impl FnMut(&[usize]) -> bool,
+    ) -> bool {
+        if self.right[0] == 0 {
+            return found(chosen);
+        }
+
+        // Branching on the column with the fewest options keeps the search tree small.
+        let mut header = self.right[0];
+        let mut best = header;
+        while header != 0 {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        if self.size[best] == 0 {
+            return true;
+        }
+
+        self.cover(best);
+        let mut keep_going = true;
+        let mut node = self.down[best];
+        while node != best && keep_going {
+            chosen.push(self.row[node]);
+            let mut other = self.right[node];
+            while other != node {
+                self.cover(self.column[other]);
+                other = self.right[other];
+            }
+
+            keep_going = self.search(chosen, found);
+
+            let mut other = self.left[node];
+            while other != node {
+                self.uncover(self.column[other]);
+                other = self.left[other];
+            }
+            chosen.pop();
+            node = self.down[node];
+        }
+        self.uncover(best);
+        keep_going
+    }
+
+    /// Take a column out of play, along with every row which covers it.
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+
+        let mut node = self.down[header];
+        while node != header {
+            let mut other = self.right[node];
+            while other != node {
+                self.down[self.up[other]] = self.down[other];
+                self.up[self.down[other]] = self.up[other];
+                self.size[self.column[other]] -= 1;
+                other = self.right[other];
+            }
+            node = self.down[node];
+        }
+    }
+
+    /// Undo [`ExactCover::cover`]. Columns have to be uncovered in the reverse order that they
+    /// were covered.
+    fn uncover(&mut self, header: usize) {
+        let mut node = self.up[header];
+        while node != header {
+            let mut other = self.left[node];
+            while other != node {
+                self.size[self.column[other]] += 1;
+                self.down[self.up[other]] = other;
+                self.up[self.down[other]] = other;
+                other = self.left[other];
+            }
+            node = self.up[node];
+        }
+
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+}
+
+/// The rules of a Sudoku variant, expressed as groups of cells.
+///
+/// Every cell always holds exactly one digit. On top of that, each region holds every digit
+/// exactly once, each distinct group holds every digit at most once, each cage adds up to its
+/// total, and the two halves of a conflict never both turn up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SudokuCover {
+    regions: Vec<[usize; 9]>,
+    distinct: Vec<Vec<usize>>,
+    /// The totals of the cages, along with the distinct group that holds each cage's cells.
+    sums: Vec<(usize, u32)>,
+    conflicts: Vec<[(usize, Entry); 2]>,
+}
+
+impl SudokuCover {
+    /// Create the rules for an ordinary Sudoku, where the regions are the rows, columns, and big
+    /// cells.
+    pub fn standard() -> SudokuCover {
+        SudokuCover {
+            regions: Unit::all().map(|unit| unit.cells()).collect(),
+            ..SudokuCover::empty()
+        }
+    }
+
+    /// Create the rules for a board with the supplied variant, which adds the variant's extra units
+    /// to the regions of an ordinary Sudoku.
+    pub fn for_variant(variant: Variant) -> SudokuCover {
+        let mut cover = SudokuCover::empty();
+        variant.add_to_cover(&mut cover);
+        cover
+    }
+
+    /// Create the rules for a board, made up of its variant and every constraint added on top.
+    ///
+    /// If one of the constraints can't be written as an exact cover problem, [`None`] is returned.
+    pub fn for_board(board: &Board) -> Option<SudokuCover> {
+        let mut cover = SudokuCover::empty();
+        board
+            .rules()
+            .all(|rule| rule.add_to_cover(&mut cover))
+            .then_some(cover)
+    }
+
+    /// Create a set of rules with no regions at all, which the rules of a board are added to one
+    /// by one.
+    pub const fn empty() -> SudokuCover {
+        SudokuCover {
+            regions: Vec::new(),
+            distinct: Vec::new(),
+            sums: Vec::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    /// Add a region which has to hold every digit exactly once, like a diagonal.
+    pub fn add_region(&mut self, cells: [usize; 9]) {
+        self.regions.push(cells);
+    }
+
+    /// Add a group of cells which can't repeat a digit, like a killer cage.
+    pub fn add_distinct(&mut self, cells: Vec<usize>) {
+        self.distinct.push(cells);
+    }
+
+    /// Add a killer cage: a group of cells which can't repeat a digit and adds up to `sum`.
+    pub fn add_cage(&mut self, cells: Vec<usize>, sum: u32) {
+        self.sums.push((self.distinct.len(), sum));
+        self.distinct.push(cells);
+    }
+
+    /// Add a pair of placements which can't both be made, like a 9 and a 1 on either side of a
+    /// "less than" sign.
+    pub fn add_conflict(&mut self, first: (usize, Entry), second: (usize, Entry)) {
+        self.conflicts.push([first, second]);
+    }
+
+    /// Build the exact cover problem for a board. Row number `9 * index + digit` stands for the
+    /// digit (counting from zero) in the cell at that index. Any rows after the first 729 pick the
+    /// digits of a cage, and don't stand for a cell.
+    fn build(&self, board: &Board) -> ExactCover {
+        let primary = 81 + 9 * self.regions.len() + self.sums.len();
+        let conflicts = primary + 9 * self.distinct.len();
+        let mut cover = ExactCover::new(primary, 9 * self.distinct.len() + self.conflicts.len());
+
+        // Each conflict gets a column of its own, which both of its placements cover.
+        let mut conflicting = vec![Vec::new(); 729];
+        for (number, pair) in self.conflicts.iter().enumerate() {
+            for (index, entry) in pair {
+                conflicting[9 * index + *entry as usize].push(conflicts + number);
+            }
+        }
+
+        for index in 0..81 {
+            for entry in Entry::ALL {
+                let digit = entry as usize;
+                let allowed = board
+                    .get_cell_index(index)
+                    .is_none_or(|given| given == entry);
+                if !allowed {
+                    // Empty rows keep the numbering simple, and they can never be chosen.
+                    cover.add_row(&[]);
+                    continue;
+                }
+
+                let mut columns = vec![index];
+                for (region, cells) in self.regions.iter().enumerate() {
+                    if cells.contains(&index) {
+                        columns.push(81 + 9 * region + digit);
+                    }
+                }
+                for (group, cells) in self.distinct.iter().enumerate() {
+                    if cells.contains(&index) {
+                        columns.push(primary + 9 * group + digit);
+                    }
+                }
+                columns.extend(&conflicting[9 * index + digit]);
+                cover.add_row(&columns);
+            }
+        }
+
+        // Each cage picks one set of digits with the right total, and takes every other digit
+        // away from its cells. With one digit per cell and no repeats, the cells then have to hold
+        // exactly the digits in the set.
+        let first_sum = 81 + 9 * self.regions.len();
+        for (number, &(group, sum)) in self.sums.iter().enumerate() {
+            let size = self.distinct[group].len() as u32;
+            for digits in 0..1u32 << 9 {
+                let chosen = |digit: &usize| digits & 1 << digit != 0;
+                let total: u32 = (0..9).filter(chosen).map(|digit| digit as u32 + 1).sum();
+                if digits.count_ones() != size || total != sum {
+                    continue;
+                }
+                let mut columns = vec![first_sum + number];
+                columns.extend(
+                    (0..9)
+                        .filter(|digit| !chosen(digit))
+                        .map(|digit| primary + 9 * group + digit),
+                );
+                cover.add_row(&columns);
+            }
+        }
+        cover
+    }
+
+    /// Solve a board under these rules, returning the solution as a new board.
+    ///
+    /// If there is no solution, [`None`] is returned.
+    pub fn solve(&self, board: &Board) -> Option<Board> {
+        let rows = self.build(board).solve()?;
+        let mut solution = board.clone();
+        for row in rows.into_iter().filter(|&row| row < 729) {
+            solution.set_cell_index(row / 9, Some(Entry::ALL[row % 9]));
+        }
+        Some(solution)
+    }
+
+    /// Count the solutions of a board under these rules, stopping once `limit` have been found.
+    pub fn count_solutions(&self, board: &Board, limit: usize) -> usize {
+        self.build(board).count_solutions(limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::constraint::{AntiKnight, Inequality, Killer, Thermo};
+    use crate::solver;
+
+    #[test]
+    fn test_standard_matches_backtracking() {
+        let board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let expected = solver::solve_copy(&board).unwrap();
+        let cover = SudokuCover::standard();
+        assert_eq!(
+            cover.solve(&board).unwrap().to_string(),
+            expected.to_string()
+        );
+        assert_eq!(cover.count_solutions(&board, 2), 1);
+        assert_eq!(cover.count_solutions(&Board::empty(), 2), 2);
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let mut cover = SudokuCover::standard();
+        cover.add_region(std::array::from_fn(|i| i * 10));
+        cover.add_region(std::array::from_fn(|i| (i + 1) * 8));
+
+        let solution = cover.solve(&Board::empty()).unwrap();
+        assert!(solution.is_valid());
+        let diagonal: Vec<_> = (0..9).map(|i| solution.get_cell_index(i * 10)).collect();
+        assert!(diagonal.iter().all_unique());
+    }
+
+    #[test]
+    fn test_constraints() {
+        let mut board = Board::empty();
+        board.add_constraint(Killer::new(vec![0, 1, 2], 6));
+        board.add_constraint(AntiKnight);
+        board.add_constraint(Thermo::new(vec![9, 10, 11]).unwrap());
+        board.add_constraint(Inequality::new(19, 20).unwrap());
+
+        let cover = SudokuCover::for_board(&board).unwrap();
+        let solution = cover.solve(&board).unwrap();
+        assert!(solution.is_valid());
+        let cage: Vec<_> = (0..3)
+            .map(|index| solution.get_cell_index(index).unwrap())
+            .sorted_by_key(|&entry| entry as u8)
+            .collect();
+        assert_eq!(cage, [Entry::One, Entry::Two, Entry::Three]);
+
+        // Two different digits can't add up to 2, even though the cage's columns are secondary.
+        let mut board = Board::empty();
+        board.add_constraint(Killer::new(vec![0, 1], 2));
+        let cover = SudokuCover::for_board(&board).unwrap();
+        assert!(cover.solve(&board).is_none());
+        assert_eq!(cover.count_solutions(&board, 2), 0);
+    }
+}
@@ -0,0 +1,188 @@
+//! Playing with a gamepad, for when the keyboard is out of reach.
+//!
+//! The d-pad moves the selected cell around the board. Holding A opens a ring of digits around the
+//! selected cell, the left stick points at one of them, and letting go of A types it, the same as
+//! pressing its key would. Holding X does the same for pencil marks. Everything else that a gamepad
+//! can do, like Start for starting and pausing the solver, is an [`Action`](crate::keys::Action),
+//! listed alongside the keyboard shortcuts.
+
+use raylib::prelude::*;
+
+use crate::board::{self, BoardView, Entry};
+use crate::ui::{self, InputState};
+
+/// The button which opens the ring of digits for filling in cells.
+const DIGIT_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN;
+
+/// The button which opens the ring of digits for pencil marks.
+const PENCIL_BUTTON: GamepadButton = GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT;
+
+/// How far the stick has to be pushed before it points at a digit, out of 1.
+const STICK_DEAD_ZONE: f32 = 0.5;
+
+/// The cell in the middle of the board, which is selected if the gamepad is used while nothing
+/// else is.
+const MIDDLE_CELL: usize = 40;
+
+/// A ring of digits around the selected cell, open while A or X is held down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Picker {
+    /// Whether the digit is going to be pencil marked rather than filled in.
+    pencil: bool,
+
+    /// The digit that the stick is pointing at.
+    choice: Option<Entry>,
+}
+
+/// Turns gamepad input into moves around the board and typed digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gamepad {
+    picker: Option<Picker>,
+}
+
+impl Gamepad {
+    /// Create a gamepad with nothing held down.
+    pub const fn new() -> Gamepad {
+        Gamepad { picker: None }
+    }
+
+    /// Respond to the gamepad, moving the selection around the board.
+    ///
+    /// A digit picked from the ring is added to the input as the key press that would type it, so
+    /// that the board, the game, and the editor all deal with it the same way as the keyboard.
+    /// This means it has to be called before anything else looks at the input.
+    pub fn update(&mut self, input: &mut InputState, view: &mut BoardView) {
+        if let Some(picker) = &mut self.picker {
+            picker.choice = pick(input.left_stick);
+            let button = if picker.pencil {
+                PENCIL_BUTTON
+            } else {
+                DIGIT_BUTTON
+            };
+            if input.button_released(button) {
+                if let Some(entry) = picker.choice {
+                    input.keys_pressed.push(board::DIGIT_KEYS[entry as usize]);
+                    // Shift and a digit is how pencil marks are typed.
+                    input.shift = picker.pencil;
+                    input.ctrl = false;
+                }
+                self.picker = None;
+            }
+            return;
+        }
+
+        let index = view.selected().unwrap_or(MIDDLE_CELL);
+        let (row, column) = (index / 9, index % 9);
+        let moved = if input.button_pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP) {
+            Some((row.saturating_sub(1), column))
+        } else if input.button_pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN) {
+            Some(((row + 1).min(8), column))
+        } else if input.button_pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT) {
+            Some((row, column.saturating_sub(1)))
+        } else if input.button_pressed(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT) {
+            Some((row, (column + 1).min(8)))
+        } else {
+            None
+        };
+        if let Some((row, column)) = moved {
+            // The first press only picks out a cell to start from, if nothing was selected.
+            let target = if view.selected().is_some() {
+                row * 9 + column
+            } else {
+                index
+            };
+            view.select(Some(target));
+        }
+
+        for (button, pencil) in [(DIGIT_BUTTON, false), (PENCIL_BUTTON, true)] {
+            if input.button_pressed(button) {
+                if view.selected().is_none() {
+                    view.select(Some(MIDDLE_CELL));
+                }
+                self.picker = Some(Picker {
+                    pencil,
+                    choice: None,
+                });
+            }
+        }
+    }
+
+    /// Draw the ring of digits around the selected cell, if it is open, where `rect` is the
+    /// rectangle that the board was drawn in.
+    pub fn draw(&self, d: &mut RaylibDrawHandle, view: &BoardView, rect: Rectangle) {
+        let (Some(picker), Some(index)) = (self.picker, view.selected()) else {
+            return;
+        };
+        let cell = ui::cell_rect(rect, index);
+        let center = Vector2::new(cell.x + cell.width / 2.0, cell.y + cell.height / 2.0);
+        let radius = cell.width * 1.4;
+        let slot_radius = cell.width * 0.45;
+        d.draw_circle_v(center, radius + slot_radius * 1.2, Color::BLACK.alpha(0.6));
+
+        // Pencil marks are drawn smaller than digits on the board, and so are they here.
+        let font_size = ui::font_size() * if picker.pencil { 0.5 } else { 0.8 };
+        for entry in Entry::ALL {
+            let position = center + direction(entry) * radius;
+            let chosen = picker.choice == Some(entry);
+            let fill = if chosen { Color::GOLD } else { Color::RAYWHITE };
+            d.draw_circle_v(position, slot_radius, fill);
+            let slot = Rectangle::new(
+                position.x - slot_radius,
+                position.y - slot_radius,
+                slot_radius * 2.0,
+                slot_radius * 2.0,
+            );
+            ui::draw_text_centered(d, &entry.to_string(), slot, font_size, Color::BLACK);
+        }
+    }
+}
+
+/// Get the direction of a digit from the middle of the ring. The 1 is at the top, and the rest
+/// follow clockwise.
+fn direction(entry: Entry) -> Vector2 {
+    let angle = entry as usize as f32 * std::f32::consts::TAU / 9.0;
+    Vector2::new(angle.sin(), -angle.cos())
+}
+
+/// Get the digit that the stick is pointing at, or [`None`] if it isn't pushed far enough.
+fn pick(stick: Vector2) -> Option<Entry> {
+    if stick.x.hypot(stick.y) < STICK_DEAD_ZONE {
+        return None;
+    }
+    // The angle is measured clockwise from straight up, to match the ring.
+    let angle = stick.x.atan2(-stick.y).rem_euclid(std::f32::consts::TAU);
+    let slot = (angle / (std::f32::consts::TAU / 9.0)).round() as usize % 9;
+    Some(Entry::ALL[slot])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick() {
+        assert_eq!(pick(Vector2::new(0.0, 0.2)), None);
+        assert_eq!(pick(Vector2::new(0.0, -1.0)), Some(Entry::One));
+        for entry in Entry::ALL {
+            assert_eq!(pick(direction(entry)), Some(entry));
+        }
+
+        let mut view = BoardView::new();
+        let mut gamepad = Gamepad::new();
+        let mut input = InputState {
+            buttons_pressed: vec![DIGIT_BUTTON],
+            ..InputState::default()
+        };
+        gamepad.update(&mut input, &mut view);
+        assert_eq!(view.selected(), Some(MIDDLE_CELL));
+
+        let mut input = InputState {
+            buttons_released: vec![DIGIT_BUTTON],
+            left_stick: direction(Entry::Four),
+            ..InputState::default()
+        };
+        gamepad.update(&mut input, &mut view);
+        assert_eq!(input.keys_pressed, vec![KeyboardKey::KEY_FOUR]);
+        assert!(!input.shift);
+    }
+}
@@ -0,0 +1,610 @@
+//! Generation of new puzzles.
+//!
+//! A puzzle is made by filling in a random solved board and then digging holes in it. A clue is
+//! only removed if the puzzle still has exactly one solution without it, so every generated puzzle
+//! is proper.
+//!
+//! Generation is driven entirely by a [`Seed`], so the same seed and settings always produce the
+//! same puzzle. That makes it easy to share a puzzle by its seed alone.
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::board::Board;
+use crate::random::{Rng, Seed};
+use crate::rating::{self, Difficulty};
+use crate::solver;
+
+/// A pattern that the clues of a puzzle can follow.
+///
+/// With any symmetry other than [`Symmetry::None`], clues are removed in pairs, so the pattern of
+/// clues looks the same after the matching transformation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Symmetry {
+    /// No pattern at all.
+    #[default]
+    None,
+
+    /// The pattern looks the same after turning the board halfway around.
+    Rotational,
+
+    /// The pattern looks the same when reflected left to right.
+    Mirror,
+
+    /// The pattern looks the same when reflected across the diagonal from the top left to the
+    /// bottom right.
+    Diagonal,
+}
+
+impl Symmetry {
+    /// Get the cell which the cell at the supplied index is paired with. This might be the cell
+    /// itself, like the center cell under rotation.
+    pub const fn partner(&self, index: usize) -> usize {
+        let (row, column) = (index / 9, index % 9);
+        match self {
+            Symmetry::None => index,
+            Symmetry::Rotational => 80 - index,
+            Symmetry::Mirror => row * 9 + (8 - column),
+            Symmetry::Diagonal => column * 9 + row,
+        }
+    }
+}
+
+/// How hard a generated puzzle should be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DifficultyGoal {
+    /// The puzzle needs techniques up to this difficulty at most. A clue is only removed if the
+    /// puzzle can still be solved by logic using those techniques, so unless this is
+    /// [`Difficulty::Expert`], the puzzle never needs any guessing.
+    AtMost(Difficulty),
+
+    /// The puzzle is rated as exactly this difficulty by [`rating::rate`]. Clues are removed the
+    /// same way as for [`DifficultyGoal::AtMost`], and if the puzzle comes out too easy, the
+    /// generator starts over with a new solution.
+    Exactly(Difficulty),
+}
+
+impl DifficultyGoal {
+    /// Get the hardest techniques that a puzzle meeting the goal may need, or [`None`] if it may
+    /// need guessing.
+    const fn max_techniques(&self) -> Option<Difficulty> {
+        match *self {
+            DifficultyGoal::AtMost(Difficulty::Expert)
+            | DifficultyGoal::Exactly(Difficulty::Expert) => None,
+            DifficultyGoal::AtMost(max) | DifficultyGoal::Exactly(max) => Some(max),
+        }
+    }
+
+    /// Check whether a puzzle meets the goal.
+    pub fn is_met_by(&self, puzzle: &Board) -> bool {
+        let rated = rating::rate(puzzle);
+        match *self {
+            DifficultyGoal::AtMost(max) => rated.is_some_and(|rated| rated <= max),
+            DifficultyGoal::Exactly(target) => rated == Some(target),
+        }
+    }
+}
+
+/// Settings for [`generate_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Options {
+    /// The seed to generate from. If there is none, one is made up from the current time.
+    pub seed: Option<Seed>,
+
+    /// The pattern that the clues should follow.
+    pub symmetry: Symmetry,
+
+    /// Whether to keep removing clues until none of them can be removed, even if that breaks the
+    /// symmetry. Without a symmetry, every generated puzzle is minimal anyway.
+    pub minimal: bool,
+
+    /// How hard the puzzle should be. With [`DifficultyGoal::Exactly`], the generator gives up
+    /// after [`MAX_ATTEMPTS`] tries, like with [`Options::target_clues`], and returns the puzzle
+    /// that came closest, so check it with [`DifficultyGoal::is_met_by`] if the difficulty
+    /// matters.
+    pub difficulty: Option<DifficultyGoal>,
+
+    /// The exact number of clues that the puzzle should have. Digging stops once the puzzle gets
+    /// down to this many, and if it can't get that low, the generator starts over with a new
+    /// solution. After [`MAX_ATTEMPTS`] tries, the puzzle with the closest number of clues is
+    /// returned instead, so check [`Generated::clues`] if the target matters. Very low targets
+    /// (below 17) can never be reached.
+    pub target_clues: Option<usize>,
+}
+
+/// The most times that [`generate_with`] starts over while trying to hit
+/// [`Options::target_clues`] or [`Options::difficulty`].
+pub const MAX_ATTEMPTS: usize = 100;
+
+/// A freshly generated puzzle.
+#[derive(Debug, Clone)]
+pub struct Generated {
+    /// The puzzle itself.
+    pub puzzle: Board,
+
+    /// The only solution to the puzzle.
+    pub solution: Board,
+
+    /// The number of clues left in the puzzle.
+    pub clues: usize,
+
+    /// The seed that the puzzle was generated from. Generating again with this seed and the same
+    /// settings gives the same puzzle.
+    pub seed: Seed,
+}
+
+/// Generate a new puzzle with a unique solution.
+pub fn generate() -> Board {
+    generate_with(&Options::default()).puzzle
+}
+
+/// Generate a new puzzle with a unique solution, following the supplied settings.
+pub fn generate_with(options: &Options) -> Generated {
+    let seed = options.seed.unwrap_or_else(Seed::from_time);
+    let mut rng = Rng::new(seed);
+    let exact_difficulty = matches!(options.difficulty, Some(DifficultyGoal::Exactly(_)));
+    if options.target_clues.is_none() && !exact_difficulty {
+        return attempt(options, seed, &mut rng);
+    }
+
+    let mut best = attempt(options, seed, &mut rng);
+    let mut best_miss = miss(options, &best);
+    for _ in 1..MAX_ATTEMPTS {
+        if best_miss == (0, 0) {
+            break;
+        }
+        let next = attempt(options, seed, &mut rng);
+        let next_miss = miss(options, &next);
+        if next_miss < best_miss {
+            (best, best_miss) = (next, next_miss);
+        }
+    }
+    best
+}
+
+/// Work out how far a generated puzzle is from the difficulty and the number of clues that the
+/// options ask for. The difficulty comes first, and a perfect match is `(0, 0)`.
+fn miss(options: &Options, generated: &Generated) -> (usize, usize) {
+    let difficulty = match options.difficulty {
+        Some(DifficultyGoal::Exactly(target)) => {
+            let rated = rating::rate(&generated.puzzle).unwrap_or(Difficulty::Expert);
+            (target as usize).abs_diff(rated as usize)
+        }
+        _ => 0,
+    };
+    let clues = options
+        .target_clues
+        .map_or(0, |target| generated.clues.abs_diff(target));
+    (difficulty, clues)
+}
+
+/// Make a single attempt at generating a puzzle.
+fn attempt(options: &Options, seed: Seed, rng: &mut Rng) -> Generated {
+    let target = options.target_clues.unwrap_or(0);
+    let acceptable = |puzzle: &Board| {
+        clue_count(puzzle) >= target
+            && match options.difficulty.and_then(|goal| goal.max_techniques()) {
+                Some(max) => rating::solvable_within(puzzle, max),
+                None => solver::count_solutions(puzzle, 2) == 1,
+            }
+    };
+
+    let solution = random_solution(rng);
+    let mut puzzle = dig(&solution, options.symmetry, rng, acceptable);
+    if options.minimal && options.symmetry != Symmetry::None {
+        // Clues which are only there to keep their partners company can go now.
+        puzzle = dig(&puzzle, Symmetry::None, rng, acceptable);
+    }
+    puzzle.mark_givens();
+
+    Generated {
+        clues: clue_count(&puzzle),
+        puzzle,
+        solution,
+        seed,
+    }
+}
+
+/// Count the filled cells of a board.
+fn clue_count(board: &Board) -> usize {
+    (0..81)
+        .filter(|&index| board.get_cell_index(index).is_some())
+        .count()
+}
+
+/// A day on the calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    /// The year, like 2025.
+    pub year: i32,
+
+    /// The month, from 1 to 12.
+    pub month: u32,
+
+    /// The day of the month, from 1 to 31.
+    pub day: u32,
+}
+
+impl Date {
+    /// Get today's date in UTC.
+    ///
+    /// UTC is used rather than the local time zone so that everyone gets the same puzzle of the
+    /// day at the same moment.
+    pub fn today() -> Date {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Date::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    /// Convert a number of days since 1970-01-01 into a date.
+    fn from_days_since_epoch(days: i64) -> Date {
+        // This is Howard Hinnant's civil_from_days algorithm, which counts in 400 year eras
+        // starting from March so that leap days fall at the end of each year.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400) as i32 + i32::from(month <= 2);
+        Date { year, month, day }
+    }
+
+    /// Get the number of days in a month, counting February 29th in leap years.
+    fn days_in_month(year: i32, month: u32) -> u32 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+}
+
+impl std::fmt::Display for Date {
+    /// Write the date as `YYYY-MM-DD`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl std::str::FromStr for Date {
+    type Err = ();
+
+    /// Parse a date written as `YYYY-MM-DD`. Days that the month doesn't have, like February 30th,
+    /// are rejected.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or(());
+        let year = next()?.parse().map_err(|_| ())?;
+        let month = next()?.parse().map_err(|_| ())?;
+        let day = next()?.parse().map_err(|_| ())?;
+        if !(1..=12).contains(&month) || !(1..=Date::days_in_month(year, month)).contains(&day) {
+            return Err(());
+        }
+        Ok(Date { year, month, day })
+    }
+}
+
+/// Generate the puzzle of the day.
+///
+/// The seed comes from the date alone, so everyone who asks for the same date gets the same
+/// puzzle. Daily puzzles have rotational symmetry and never need anything harder than
+/// [`Difficulty::Medium`] techniques.
+pub fn daily(date: Date) -> Generated {
+    let seed = date.year as u64 * 10_000 + u64::from(date.month) * 100 + u64::from(date.day);
+    generate_with(&Options {
+        seed: Some(Seed(seed)),
+        symmetry: Symmetry::Rotational,
+        difficulty: Some(DifficultyGoal::AtMost(Difficulty::Medium)),
+        ..Options::default()
+    })
+}
+
+/// Generate puzzle number `i` (counting from zero) of a batch whose seeds start at `base`.
+///
+/// If the puzzle misses the difficulty that the options ask for, a warning is logged and [`None`]
+/// is returned, so that it can be left out of the batch.
+fn batch_puzzle(options: &Options, base: Seed, i: usize) -> Option<Generated> {
+    let generated = generate_with(&Options {
+        seed: Some(Seed(base.0.wrapping_add(i as u64))),
+        ..*options
+    });
+    if let Some(goal) = options.difficulty
+        && !goal.is_met_by(&generated.puzzle)
+    {
+        log::warn!(
+            "left out puzzle {} of seed {}, which missed the difficulty after {MAX_ATTEMPTS} tries",
+            i + 1,
+            generated.seed.0
+        );
+        return None;
+    }
+    Some(generated)
+}
+
+/// Generate a batch of puzzles, writing each one to `out` as soon as it is ready.
+///
+/// The puzzles are written one per line in the `.sdm` format (see [`Board::to_line`]). After each
+/// puzzle, `progress` is called with the number of puzzles written so far. If the options have a
+/// seed, puzzle number `i` (counting from zero) is generated from that seed plus `i`, so the whole
+/// batch can be reproduced. Otherwise a single seed is made up for the whole batch.
+///
+/// Every puzzle is rated before it is written, and puzzles which miss the difficulty that the
+/// options ask for are left out, with a warning. The number of puzzles written is returned, so it
+/// is less than `count` if any were left out.
+///
+/// # Errors
+///
+/// Any error from writing to `out` is returned, and no more puzzles are generated.
+pub fn generate_batch(
+    count: usize,
+    options: &Options,
+    mut out: impl Write,
+    mut progress: impl FnMut(usize),
+) -> std::io::Result<usize> {
+    let base = options.seed.unwrap_or_else(Seed::from_time);
+    let mut written = 0;
+    for i in 0..count {
+        if let Some(generated) = batch_puzzle(options, base, i) {
+            writeln!(out, "{}", generated.puzzle.to_line())?;
+            written += 1;
+        }
+        progress(i + 1);
+    }
+    out.flush()?;
+    Ok(written)
+}
+
+/// Generate a batch of puzzles like [`generate_batch`], but write each one to its own file in the
+/// directory at `dir`, which is made if it doesn't exist yet.
+///
+/// The files are named `puzzle-1.txt`, `puzzle-2.txt`, and so on, in the usual puzzle file format.
+/// If `solutions` is set, the solution to each puzzle is written alongside it, to
+/// `puzzle-1-solution.txt` and so on. Puzzles which miss the difficulty are left out the same way,
+/// and the files are numbered without gaps. The number of puzzles written is returned.
+///
+/// # Errors
+///
+/// Any error from making the directory or writing a file is returned, and no more puzzles are
+/// generated.
+pub fn generate_files(
+    count: usize,
+    options: &Options,
+    dir: &Path,
+    solutions: bool,
+    mut progress: impl FnMut(usize),
+) -> std::io::Result<usize> {
+    std::fs::create_dir_all(dir)?;
+    let base = options.seed.unwrap_or_else(Seed::from_time);
+    let mut written = 0;
+    for i in 0..count {
+        let Some(generated) = batch_puzzle(options, base, i) else {
+            progress(i + 1);
+            continue;
+        };
+        written += 1;
+        let name = format!("puzzle-{written}");
+        std::fs::write(
+            dir.join(format!("{name}.txt")),
+            generated.puzzle.to_string(),
+        )?;
+        if solutions {
+            let path = dir.join(format!("{name}-solution.txt"));
+            std::fs::write(path, generated.solution.to_string())?;
+        }
+        progress(i + 1);
+    }
+    Ok(written)
+}
+
+/// Fill in an empty board at random.
+fn random_solution(rng: &mut Rng) -> Board {
+    let mut board = Board::empty();
+    // An empty board always has a solution, so this can't fail.
+    solver::solve_seeded(&mut board, Seed(rng.next_u64()));
+    board
+}
+
+/// Remove clues from a solved board in a random order, keeping each one that the puzzle can't do
+/// without. Clues are removed together with their partners under the symmetry, and only if the
+/// puzzle is still acceptable afterwards.
+fn dig(
+    solution: &Board,
+    symmetry: Symmetry,
+    rng: &mut Rng,
+    acceptable: impl Fn(&Board) -> bool,
+) -> Board {
+    let mut order: Vec<usize> = (0..81).collect();
+    rng.shuffle(&mut order);
+
+    let mut puzzle = solution.clone();
+    for index in order {
+        let partner = symmetry.partner(index);
+        let Some(clue) = puzzle.get_cell_index(index) else {
+            // The cell was already removed as the partner of another one.
+            continue;
+        };
+        let partner_clue = puzzle.get_cell_index(partner);
+
+        puzzle.set_cell_index(index, None);
+        puzzle.set_cell_index(partner, None);
+        if !acceptable(&puzzle) {
+            puzzle.set_cell_index(index, Some(clue));
+            puzzle.set_cell_index(partner, partner_clue);
+        }
+    }
+    puzzle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let puzzle = generate();
+        assert!(puzzle.first_unfilled_index().is_some());
+        assert_eq!(solver::count_solutions(&puzzle, 2), 1);
+    }
+
+    #[test]
+    fn test_seeded() {
+        let options = Options {
+            seed: Some(Seed(42)),
+            ..Options::default()
+        };
+        let first = generate_with(&options);
+        let second = generate_with(&options);
+        assert_eq!(first.seed, Seed(42));
+        assert_eq!(first.puzzle.to_string(), second.puzzle.to_string());
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        let options = Options {
+            seed: Some(Seed(7)),
+            ..Options::default()
+        };
+        let mut out = Vec::new();
+        let mut reported = Vec::new();
+        generate_batch(3, &options, &mut out, |done| reported.push(done)).unwrap();
+        assert_eq!(reported, [1, 2, 3]);
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for line in lines {
+            assert_eq!(line.len(), 81);
+            let puzzle: Board = line.parse().unwrap();
+            assert_eq!(puzzle.to_line(), line);
+            assert_eq!(solver::count_solutions(&puzzle, 2), 1);
+        }
+    }
+
+    #[test]
+    fn test_max_difficulty() {
+        let generated = generate_with(&Options {
+            seed: Some(Seed(11)),
+            difficulty: Some(DifficultyGoal::AtMost(Difficulty::Easy)),
+            ..Options::default()
+        });
+        assert_eq!(rating::rate(&generated.puzzle), Some(Difficulty::Easy));
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let generated = generate_with(&Options {
+            seed: Some(Seed(11)),
+            difficulty: Some(DifficultyGoal::Exactly(Difficulty::Medium)),
+            ..Options::default()
+        });
+        assert_eq!(rating::rate(&generated.puzzle), Some(Difficulty::Medium));
+
+        // Nothing is that hard with so many clues, so the whole batch is left out.
+        let options = Options {
+            seed: Some(Seed(11)),
+            difficulty: Some(DifficultyGoal::Exactly(Difficulty::Expert)),
+            target_clues: Some(70),
+            ..Options::default()
+        };
+        let mut out = Vec::new();
+        assert_eq!(generate_batch(2, &options, &mut out, |_| {}).unwrap(), 0);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_generate_files() {
+        let dir = std::env::temp_dir().join(format!("generate-test-{}", std::process::id()));
+        let options = Options {
+            seed: Some(Seed(9)),
+            ..Options::default()
+        };
+        assert_eq!(generate_files(2, &options, &dir, true, |_| {}).unwrap(), 2);
+
+        for name in ["puzzle-1", "puzzle-2"] {
+            let puzzle = std::fs::read_to_string(dir.join(format!("{name}.txt"))).unwrap();
+            let solution = dir.join(format!("{name}-solution.txt"));
+            let solution: Board = std::fs::read_to_string(solution).unwrap().parse().unwrap();
+            let puzzle: Board = puzzle.parse().unwrap();
+            assert_eq!(
+                solver::solve_copy(&puzzle).unwrap().to_line(),
+                solution.to_line()
+            );
+        }
+        assert!(!dir.join("puzzle-3.txt").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_daily() {
+        let date: Date = "2024-02-29".parse().unwrap();
+        assert_eq!(date.to_string(), "2024-02-29");
+        assert_eq!(Date::from_days_since_epoch(19_782), date);
+        assert_eq!(Date::from_days_since_epoch(0).to_string(), "1970-01-01");
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2023-02-31".parse::<Date>().is_err());
+        assert!("2023-04-31".parse::<Date>().is_err());
+        assert!("2000-02-29".parse::<Date>().is_ok());
+        assert!("1900-02-29".parse::<Date>().is_err());
+
+        let first = daily(date);
+        let second = daily(date);
+        assert_eq!(first.puzzle.to_string(), second.puzzle.to_string());
+        assert!(rating::solvable_within(&first.puzzle, Difficulty::Medium));
+    }
+
+    #[test]
+    fn test_target_clues() {
+        let generated = generate_with(&Options {
+            seed: Some(Seed(5)),
+            target_clues: Some(30),
+            ..Options::default()
+        });
+        assert_eq!(generated.clues, 30);
+        assert_eq!(solver::count_solutions(&generated.puzzle, 2), 1);
+    }
+
+    #[test]
+    fn test_symmetry() {
+        for symmetry in [Symmetry::Rotational, Symmetry::Mirror, Symmetry::Diagonal] {
+            let puzzle = generate_with(&Options {
+                seed: Some(Seed(2)),
+                symmetry,
+                ..Options::default()
+            })
+            .puzzle;
+            assert_eq!(solver::count_solutions(&puzzle, 2), 1);
+            for index in 0..81 {
+                let partner = symmetry.partner(index);
+                assert_eq!(symmetry.partner(partner), index);
+                assert_eq!(
+                    puzzle.get_cell_index(index).is_some(),
+                    puzzle.get_cell_index(partner).is_some()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimal() {
+        let generated = generate_with(&Options {
+            seed: Some(Seed(3)),
+            symmetry: Symmetry::Rotational,
+            minimal: true,
+            ..Options::default()
+        });
+        assert_eq!(solver::count_solutions(&generated.puzzle, 2), 1);
+        assert_eq!(solver::count_solutions(&generated.solution, 2), 1);
+        assert!(crate::analysis::redundant_givens(&generated.puzzle).is_empty());
+        assert_eq!(generated.clues, clue_count(&generated.puzzle));
+    }
+}
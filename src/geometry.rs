@@ -8,6 +8,7 @@ use raylib::math::{Rectangle, Vector2};
 /// # Examples
 ///
 /// ```
+/// use raylib::math::{Rectangle, Vector2};
 /// use sudoku_solver::geometry::rect_contains_point;
 ///
 /// assert!(rect_contains_point(
@@ -17,7 +18,7 @@ use raylib::math::{Rectangle, Vector2};
 ///
 /// assert!(!rect_contains_point(
 ///     Rectangle { x: 10.0, y: 20.0, width: 50.0, height: 60.0 },
-///     Vector2 { x: 90.0, 80.0 },
+///     Vector2 { x: 90.0, y: 80.0 },
 /// ));
 /// ```
 pub fn rect_contains_point(rect: Rectangle, point: Vector2) -> bool {
@@ -1,46 +1,474 @@
+use std::f32::consts::PI;
+use std::time::{Duration, Instant};
+
 use raylib::prelude::*;
 
+use crate::board;
+use crate::keys;
+use crate::locale::Message;
+use crate::logic::Technique;
+use crate::solver::StepStats;
 use crate::ui::{self, Widget};
 
-fn center_text(d: &mut RaylibDrawHandle, text: &str, rect: Rectangle) -> Vector2 {
-    let font = d.get_font_default();
-    let size = font.measure_text(text, ui::FONT_SIZE, ui::FONT_SPACING);
-
-    Vector2 {
-        x: rect.x + (rect.width - size.x) / 2.0,
-        y: rect.y + (rect.height - size.y) / 2.0,
-    }
-}
-
+/// The state of the solving animation, shown in a strip underneath the board.
 pub enum SolvingStatus {
+    /// The solver is stepping every frame.
     Going,
+
+    /// The solver is waiting for the user to start it.
     Stopped,
+
+    /// The solver was started, and has been paused partway through.
+    Paused,
+
+    /// The board has been filled in, by the solver or by hand.
+    Solved,
+
+    /// The solver is stopped, and a hint is being shown on the board. This holds the technique
+    /// that the hint uses.
+    Hint(Technique),
+
+    /// The solver tried every possibility, and none of them led to a solution.
+    Unsolvable,
+
+    /// The puzzle broke the rules from the start, so the solver can't work on it.
+    Invalid,
 }
 
 impl SolvingStatus {
+    /// Switch between going and paused.
+    ///
+    /// A solved, unsolvable, or invalid puzzle stays that way, since there is nothing left for
+    /// the solver to do.
     pub const fn toggled(&self) -> Self {
         match self {
-            Self::Going => Self::Stopped,
-            Self::Stopped => Self::Going,
+            Self::Going => Self::Paused,
+            Self::Stopped | Self::Paused | Self::Hint(_) => Self::Going,
+            Self::Solved => Self::Solved,
+            Self::Unsolvable => Self::Unsolvable,
+            Self::Invalid => Self::Invalid,
+        }
+    }
+
+    /// Check whether the solver is finished with the puzzle, one way or another.
+    pub const fn is_finished(&self) -> bool {
+        matches!(self, Self::Solved | Self::Unsolvable | Self::Invalid)
+    }
+
+    /// Get the message which describes this status.
+    pub const fn message(&self) -> Message<'static> {
+        match self {
+            Self::Going => Message::Going,
+            Self::Stopped => Message::Stopped,
+            Self::Paused => Message::Paused,
+            Self::Solved => Message::Solved,
+            Self::Hint(technique) => Message::HintFor(*technique),
+            Self::Unsolvable => Message::Unsolvable,
+            Self::Invalid => Message::Invalid,
         }
     }
 }
 
 impl Widget for SolvingStatus {
     fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
-        let (text, color) = match self {
-            Self::Going => ("Going...", Color::GREEN),
-            Self::Stopped => ("Stopped", Color::RED),
+        let color = match self {
+            Self::Going => Color::GREEN,
+            Self::Stopped => Color::RED,
+            Self::Paused => Color::ORANGE,
+            Self::Solved | Self::Hint(_) => Color::GOLD,
+            Self::Unsolvable => Color::PINK,
+            Self::Invalid => Color::GRAY,
+        };
+        // Technique names can be long, so hints get a smaller font to fit.
+        let font_size = match self {
+            Self::Hint(_) => ui::font_size() * 0.6,
+            _ => ui::font_size(),
         };
-        let pos = center_text(d, text, rect);
-
         d.draw_rectangle_rec(rect, color);
-        d.draw_text(
-            text,
-            pos.x as i32,
-            pos.y as i32,
-            ui::FONT_SIZE as i32,
-            Color::BLACK,
-        );
+        let message = self.message().to_string();
+        ui::draw_text_centered(d, &message, rect, font_size, Color::BLACK);
+    }
+
+    fn preferred_size(&self) -> Vector2 {
+        Vector2::new(0.0, ui::font_size() * 1.6)
+    }
+
+    fn min_size(&self) -> Vector2 {
+        Vector2::new(0.0, ui::font_size())
+    }
+}
+
+impl Widget for StepStats {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let text = Message::StepStats {
+            steps: self.steps,
+            backtracks: self.backtracks,
+            seconds: self.elapsed.as_secs_f32(),
+        }
+        .to_string();
+        d.draw_rectangle_rec(rect, Color::LIGHTGRAY);
+        // The counts can get long, so this uses a smaller font than the status strip to fit.
+        ui::draw_text_centered(d, &text, rect, ui::font_size() * 0.6, Color::BLACK);
+    }
+
+    /// The other strips that can take the place of this one, like the one for play mode, use the
+    /// same font size, so they fit in the same room.
+    fn preferred_size(&self) -> Vector2 {
+        Vector2::new(0.0, ui::font_size() * 1.2)
+    }
+
+    fn min_size(&self) -> Vector2 {
+        Vector2::new(0.0, ui::font_size() * 0.6)
+    }
+}
+
+/// A panel beside the board listing the solver's running totals in more detail than the stats
+/// strip has room for: the number of steps and backtracks, how deep the search is, and how often
+/// each logical technique has been used.
+///
+/// The panel can be collapsed down to a narrow strip with the button at its top, to give the board
+/// more room. It has to be updated once per frame with [`Widget::update`] for the button to work.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatsPanel {
+    stats: StepStats,
+    collapsed: bool,
+    toggle: ui::Button,
+}
+
+impl StatsPanel {
+    /// Create an open panel, with nothing counted yet.
+    pub fn new() -> StatsPanel {
+        StatsPanel {
+            stats: StepStats::default(),
+            collapsed: false,
+            toggle: ui::Button::new("-"),
+        }
+    }
+
+    /// Show the supplied totals.
+    pub fn set_stats(&mut self, stats: StepStats) {
+        self.stats = stats;
+    }
+
+    /// Check whether the panel is collapsed.
+    pub const fn collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Get the width that the panel takes up when it is open.
+    pub fn open_width() -> f32 {
+        ui::font_size() * 8.0
+    }
+
+    /// Get the width that the panel takes up right now.
+    pub fn width(&self) -> f32 {
+        if self.collapsed {
+            ui::font_size()
+        } else {
+            Self::open_width()
+        }
+    }
+
+    /// Get the rectangle of the button which opens and collapses the panel.
+    fn toggle_rect(rect: Rectangle) -> Rectangle {
+        let size = f32::min(rect.width, ui::font_size());
+        Rectangle::new(rect.x + rect.width - size, rect.y, size, size)
+    }
+
+    /// Get the lines of text to show when the panel is open.
+    fn lines(&self) -> Vec<String> {
+        let stats = &self.stats;
+        let mut lines = vec![
+            Message::Nodes(stats.steps).to_string(),
+            Message::Backtracks(stats.backtracks).to_string(),
+            Message::Depth {
+                depth: stats.depth,
+                max: stats.max_depth,
+            }
+            .to_string(),
+        ];
+        let used: Vec<_> = (0..Technique::KINDS)
+            .filter(|&kind| stats.techniques[kind] > 0)
+            .map(|kind| {
+                format!(
+                    "{}: {}",
+                    Message::TechniqueKind(kind),
+                    stats.techniques[kind]
+                )
+            })
+            .collect();
+        if !used.is_empty() {
+            lines.push(Message::Techniques.to_string());
+            lines.extend(used);
+        }
+        lines
+    }
+}
+
+impl Default for StatsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for StatsPanel {
+    fn update(&mut self, input: &ui::InputState, rect: Rectangle) {
+        self.toggle.set_tooltip(&Message::TipStats.to_string());
+        self.toggle.update(input, Self::toggle_rect(rect));
+        if self.toggle.clicked() {
+            self.collapsed = !self.collapsed;
+            self.toggle
+                .set_label(if self.collapsed { "+" } else { "-" });
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        if rect.width <= 0.0 {
+            return;
+        }
+        d.draw_rectangle_rec(rect, Color::LIGHTGRAY);
+        self.toggle.draw(d, Self::toggle_rect(rect));
+        if self.collapsed {
+            return;
+        }
+
+        let font_size = ui::font_size() * 0.5;
+        let line_height = font_size * 1.2;
+        let title_rect = Rectangle {
+            width: rect.width - ui::font_size(),
+            height: ui::font_size(),
+            ..rect
+        };
+        let title = Message::SolverStats.to_string();
+        ui::draw_text_centered(d, &title, title_rect, font_size, Color::BLACK);
+        for (i, line) in self.lines().iter().enumerate() {
+            let line_rect = Rectangle {
+                y: title_rect.y + title_rect.height + i as f32 * line_height,
+                height: line_height,
+                ..rect
+            };
+            ui::draw_text_centered(d, line, line_rect, font_size, Color::BLACK);
+        }
+    }
+
+    fn preferred_size(&self) -> Vector2 {
+        Vector2::new(self.width(), 0.0)
+    }
+}
+
+/// How long the [`Celebration`] lasts.
+const CELEBRATION_LENGTH: Duration = Duration::from_millis(2000);
+
+/// A short animation played over the board once it is solved.
+///
+/// A wave of gold sweeps across the cells from the top left corner to the bottom right, while the
+/// whole board pulses gently. It is drawn on top of the board, into the same rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Celebration {
+    started: Instant,
+}
+
+impl Celebration {
+    /// Start the animation.
+    pub fn new() -> Celebration {
+        Celebration {
+            started: Instant::now(),
+        }
+    }
+
+    /// Check whether the animation has finished, and no longer needs drawing.
+    pub fn is_finished(&self) -> bool {
+        self.started.elapsed() >= CELEBRATION_LENGTH
+    }
+}
+
+impl Default for Celebration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for Celebration {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let progress = self.started.elapsed().as_secs_f32() / CELEBRATION_LENGTH.as_secs_f32();
+        if progress >= 1.0 {
+            return;
+        }
+
+        // Each of the 17 diagonals lights up in turn, and stays lit for a fifth of the animation.
+        for (index, cell) in board::cell_rects(rect).into_iter().enumerate() {
+            let diagonal = (index / 9 + index % 9) as f32 / 16.0;
+            let phase = (progress - diagonal * 0.8) / 0.2;
+            if (0.0..1.0).contains(&phase) {
+                d.draw_rectangle_rec(cell, Color::GOLD.alpha(0.7 * (phase * PI).sin()));
+            }
+        }
+
+        let pulse = (progress * 3.0 * PI).sin().abs() * (1.0 - progress);
+        d.draw_rectangle_rec(rect, Color::YELLOW.alpha(0.2 * pulse));
+    }
+}
+
+/// How often the figures in the [`PerfOverlay`] are updated. Updating them every frame would make
+/// them flicker too fast to read.
+const PERF_SAMPLE_LENGTH: Duration = Duration::from_millis(500);
+
+/// Figures for diagnosing performance problems, drawn over the top left corner of the board.
+///
+/// The figures are collected for half a second at a time with [`PerfOverlay::record_frame`], and
+/// the averages over the last half second are shown.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PerfOverlay {
+    started: Instant,
+    frames: u32,
+    steps: u64,
+    validating: Duration,
+    rendering: Duration,
+
+    /// The lines of text to show, worked out from the last complete sample.
+    lines: Vec<String>,
+}
+
+impl PerfOverlay {
+    /// Start collecting figures.
+    pub fn new() -> PerfOverlay {
+        PerfOverlay {
+            started: Instant::now(),
+            frames: 0,
+            steps: 0,
+            validating: Duration::ZERO,
+            rendering: Duration::ZERO,
+            lines: vec![Message::Measuring.to_string()],
+        }
+    }
+
+    /// Add the figures for a frame.
+    ///
+    /// That is the number of solver steps taken, the time the solver spent checking the board
+    /// against the rules, the time spent drawing, and the size of the solver's attempt stack
+    /// right now.
+    pub fn record_frame(
+        &mut self,
+        steps: u64,
+        validating: Duration,
+        rendering: Duration,
+        attempt_stack_bytes: usize,
+    ) {
+        self.frames += 1;
+        self.steps += steps;
+        self.validating += validating;
+        self.rendering += rendering;
+
+        let seconds = self.started.elapsed().as_secs_f64();
+        if seconds < PERF_SAMPLE_LENGTH.as_secs_f64() {
+            return;
+        }
+        let per_frame = |total: Duration| total.as_secs_f64() * 1000.0 / f64::from(self.frames);
+        self.lines = [
+            Message::FramesPerSecond(f64::from(self.frames) / seconds),
+            Message::StepsPerSecondMeasured(self.steps as f64 / seconds),
+            Message::ValidatingTime(per_frame(self.validating)),
+            Message::DrawingTime(per_frame(self.rendering)),
+            Message::AttemptStack(attempt_stack_bytes),
+        ]
+        .map(|message| message.to_string())
+        .to_vec();
+        *self = PerfOverlay {
+            lines: std::mem::take(&mut self.lines),
+            ..PerfOverlay::new()
+        };
+    }
+}
+
+impl Default for PerfOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for PerfOverlay {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let font_size = ui::font_size() * 0.5;
+        let line_height = font_size * 1.2;
+        let background = Rectangle {
+            x: rect.x,
+            y: rect.y,
+            width: f32::min(rect.width, font_size * 12.0),
+            height: line_height * self.lines.len() as f32,
+        };
+        d.draw_rectangle_rec(background, Color::BLACK.alpha(0.7));
+        for (i, line) in self.lines.iter().enumerate() {
+            let line_rect = Rectangle {
+                y: background.y + i as f32 * line_height,
+                height: line_height,
+                ..background
+            };
+            ui::draw_text_centered(d, line, line_rect, font_size, Color::WHITE);
+        }
+    }
+}
+
+/// A list of every keyboard shortcut, drawn over the board.
+///
+/// The list is built from the table in [`keys`] when the overlay is opened, so it is in the locale
+/// that was current then.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HelpOverlay {
+    rows: Vec<(String, String)>,
+}
+
+impl HelpOverlay {
+    /// List the shortcuts.
+    pub fn new() -> HelpOverlay {
+        HelpOverlay { rows: keys::help() }
+    }
+}
+
+impl Default for HelpOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for HelpOverlay {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        // The text shrinks to fit every row in, rather than running off the bottom.
+        let lines = self.rows.len() + 1;
+        let font_size = f32::min(ui::font_size() * 0.5, rect.height / lines as f32 / 1.2);
+        let line_height = font_size * 1.2;
+        d.draw_rectangle_rec(rect, Color::BLACK.alpha(0.8));
+
+        let title_rect = Rectangle {
+            height: line_height,
+            ..rect
+        };
+        let title = Message::KeyboardShortcuts.to_string();
+        ui::draw_text_centered(d, &title, title_rect, font_size, Color::WHITE);
+
+        let key_width = self
+            .rows
+            .iter()
+            .map(|(keys, _)| ui::measure_text(d, keys, font_size).x)
+            .fold(0.0, f32::max)
+            + font_size;
+        for (i, (keys, description)) in self.rows.iter().enumerate() {
+            let row = Rectangle {
+                y: title_rect.y + (i + 1) as f32 * line_height,
+                height: line_height,
+                ..rect
+            };
+            let [key_rect, description_rect] = ui::split(
+                row,
+                ui::Direction::Horizontal,
+                [ui::Size::Fixed(key_width), ui::Size::Flexible(1.0)],
+            );
+            ui::draw_text_centered(d, keys, key_rect, font_size, Color::GOLD);
+            // Descriptions line up on the left, which is easier to read down than centered text.
+            let description_rect = Rectangle {
+                width: ui::measure_text(d, description, font_size).x,
+                ..description_rect
+            };
+            ui::draw_text_centered(d, description, description_rect, font_size, Color::WHITE);
+        }
     }
 }
@@ -0,0 +1,131 @@
+//! Hints for players who are stuck.
+//!
+//! A hint tells the player the next digit that can be placed by pure logic, along with the name of
+//! the technique that finds it and the cells which the reasoning depends on.
+
+use crate::board::{Board, Entry};
+use crate::logic::{Action, Deduction, Grid, Technique};
+
+/// The next logical move on a board.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hint {
+    /// The technique which places the digit.
+    pub technique: Technique,
+
+    /// The index of the cell to fill in.
+    pub index: usize,
+
+    /// The digit to fill in.
+    pub entry: Entry,
+
+    /// The cells which the technique relies on.
+    pub support: Vec<usize>,
+
+    /// Any candidate eliminations which have to be made before the digit can be placed, in the
+    /// order they have to be made. This is empty unless the board needs something harder than
+    /// singles.
+    pub eliminations: Vec<Deduction>,
+}
+
+impl Hint {
+    /// Get every step of the hint as a deduction, in order, ending with the one which places the
+    /// digit.
+    pub fn deductions(&self) -> Vec<Deduction> {
+        let mut deductions = self.eliminations.clone();
+        deductions.push(Deduction {
+            technique: self.technique,
+            action: Action::Place {
+                index: self.index,
+                entry: self.entry,
+            },
+            support: self.support.clone(),
+        });
+        deductions
+    }
+
+    /// Get every cell which the reasoning behind the hint relies on, including the cells behind
+    /// any eliminations, in order and without repeats.
+    pub fn cells(&self) -> Vec<usize> {
+        let mut cells: Vec<usize> = self
+            .eliminations
+            .iter()
+            .flat_map(|deduction| deduction.support.iter().copied())
+            .chain(self.support.iter().copied())
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells
+    }
+}
+
+impl std::fmt::Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "place {} at row {}, column {} ({})",
+            self.entry,
+            self.index / 9 + 1,
+            self.index % 9 + 1,
+            self.technique
+        )?;
+        for deduction in &self.eliminations {
+            write!(f, ", after {}", deduction.technique)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the next digit that can be placed on the board by logic alone.
+///
+/// Placements which only need singles are always preferred. If there are none, eliminations are
+/// made until a single shows up, and those eliminations are reported in [`Hint::eliminations`]. If
+/// the board is invalid, already full, or too hard for the deduction engine, [`None`] is
+/// returned.
+pub fn next_hint(board: &Board) -> Option<Hint> {
+    if !board.is_valid() {
+        return None;
+    }
+
+    let mut grid = Grid::new(board);
+    let mut eliminations = Vec::new();
+    loop {
+        let deduction = grid.find_deduction()?;
+        match deduction.action {
+            Action::Place { index, entry } => {
+                return Some(Hint {
+                    technique: deduction.technique,
+                    index,
+                    entry,
+                    support: deduction.support,
+                    eliminations,
+                });
+            }
+            Action::Eliminate(_) => {
+                grid.apply(&deduction);
+                eliminations.push(deduction);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    #[test]
+    fn test_next_hint() {
+        let board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let solution = solver::solve_copy(&board).unwrap();
+
+        let hint = next_hint(&board).unwrap();
+        assert!(hint.technique.is_single());
+        assert_eq!(board.get_cell_index(hint.index), None);
+        assert_eq!(solution.get_cell_index(hint.index), Some(hint.entry));
+
+        let cells = hint.cells();
+        assert!(!cells.is_empty());
+        assert!(cells.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(hint.support.iter().all(|index| cells.contains(index)));
+    }
+}
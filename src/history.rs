@@ -0,0 +1,151 @@
+//! Undo and redo for changes made by hand.
+//!
+//! Only edits made by the user are kept here. The solver has its own way of stepping back (see
+//! [`Solver::step_back`](crate::solver::Solver::step_back)), and its steps would drown out the
+//! user's own edits if they went in the same history.
+
+use crate::board::{Candidates, Entry};
+
+/// What a [`Move`] did to its cell. Both the old and the new contents are kept, so that a move
+/// can be undone without looking at the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// The digit in the cell was written, replaced, or rubbed out.
+    Entry {
+        /// The digit before the move.
+        before: Option<Entry>,
+
+        /// The digit after the move.
+        after: Option<Entry>,
+    },
+
+    /// The pencil marks in the cell were changed.
+    PencilMarks {
+        /// The pencil marks before the move.
+        before: Candidates,
+
+        /// The pencil marks after the move.
+        after: Candidates,
+    },
+}
+
+/// A single change to a single cell, made by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    /// The index of the cell.
+    pub index: usize,
+
+    /// What happened to the cell.
+    pub change: Change,
+}
+
+impl Move {
+    /// Get the move which puts the cell back the way it was before this one.
+    pub const fn inverse(self) -> Move {
+        let change = match self.change {
+            Change::Entry { before, after } => Change::Entry {
+                before: after,
+                after: before,
+            },
+            Change::PencilMarks { before, after } => Change::PencilMarks {
+                before: after,
+                after: before,
+            },
+        };
+        Move {
+            index: self.index,
+            change,
+        }
+    }
+}
+
+/// The moves which can be undone, and the undone moves which can be redone.
+///
+/// Moves made together, like toggling a pencil mark in several cells at once, are kept in groups
+/// which are undone and redone as one. This only keeps track of the moves. Actually changing the
+/// board is up to the caller, usually through
+/// [`BoardView::make_move`](crate::board::BoardView::make_move) and friends.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MoveHistory {
+    undo: Vec<Vec<Move>>,
+    redo: Vec<Vec<Move>>,
+}
+
+impl MoveHistory {
+    /// Create an empty history.
+    pub const fn new() -> MoveHistory {
+        MoveHistory {
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Remember a move which has just been made. Anything which was undone can no longer be
+    /// redone, since the new move takes its place.
+    pub fn push(&mut self, mv: Move) {
+        self.push_group(vec![mv]);
+    }
+
+    /// Remember several moves which have just been made together, so that they are undone and
+    /// redone as one. An empty group is ignored.
+    pub fn push_group(&mut self, moves: Vec<Move>) {
+        if moves.is_empty() {
+            return;
+        }
+        self.undo.push(moves);
+        self.redo.clear();
+    }
+
+    /// Take back the last group of moves, returning the moves which reverse it, in the order they
+    /// should be made.
+    pub fn undo(&mut self) -> Option<Vec<Move>> {
+        let moves = self.undo.pop()?;
+        let inverse = moves.iter().rev().map(|mv| mv.inverse()).collect();
+        self.redo.push(moves);
+        Some(inverse)
+    }
+
+    /// Make the last undone group of moves again, returning it.
+    pub fn redo(&mut self) -> Option<Vec<Move>> {
+        let moves = self.redo.pop()?;
+        self.undo.push(moves.clone());
+        Some(moves)
+    }
+
+    /// Forget every move.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo() {
+        let mv = Move {
+            index: 4,
+            change: Change::Entry {
+                before: None,
+                after: Some(Entry::Two),
+            },
+        };
+        let mut history = MoveHistory::new();
+        history.push(mv);
+
+        assert_eq!(history.undo(), Some(vec![mv.inverse()]));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(vec![mv]));
+        assert_eq!(history.redo(), None);
+
+        history.undo();
+        history.push(mv.inverse());
+        assert_eq!(history.redo(), None);
+
+        let other = Move { index: 5, ..mv };
+        history.push_group(vec![mv, other]);
+        assert_eq!(history.undo(), Some(vec![other.inverse(), mv.inverse()]));
+    }
+}
@@ -0,0 +1,609 @@
+//! The keyboard shortcuts of the main window.
+//!
+//! Every shortcut is listed once, in the current [`Keymap`], along with the [`Action`] it triggers.
+//! The main loop asks whether an action's shortcut was pressed rather than checking for keys
+//! itself, and the help overlay, the menus, and the tooltips all name shortcuts from the same
+//! table, so they can't disagree about which key does what. The keymap starts out as the
+//! [`BINDINGS`], and any of them can be changed in the settings.
+
+use std::cell::Cell;
+use std::fmt::{self, Formatter};
+
+use raylib::prelude::*;
+
+use crate::locale::Message;
+use crate::ui::InputState;
+
+/// Something that can be done with a keyboard shortcut.
+///
+/// The variants are described by [`Message::Action`], which is what the help overlay shows.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    StartPause,
+    Faster,
+    Slower,
+    SwitchMode,
+    Race,
+    Anneal,
+    Heatmap,
+    SplitView,
+    SearchTree,
+    Announce,
+    Mute,
+    Settings,
+    Help,
+    Hint,
+    Play,
+    GiveUp,
+    Check,
+    Erase,
+    StepBack,
+    Step,
+    OpenDaily,
+    NewPuzzle,
+    Lock,
+    Reset,
+    Performance,
+    Screenshot,
+    NextTab,
+    CloseTab,
+    Snapshot,
+    Undo,
+    Redo,
+}
+
+impl Action {
+    /// Every action, in the order that the help overlay lists them.
+    pub const ALL: [Action; 31] = [
+        Action::StartPause,
+        Action::Faster,
+        Action::Slower,
+        Action::SwitchMode,
+        Action::Race,
+        Action::Anneal,
+        Action::Heatmap,
+        Action::SplitView,
+        Action::SearchTree,
+        Action::Announce,
+        Action::Mute,
+        Action::Settings,
+        Action::Help,
+        Action::Hint,
+        Action::Play,
+        Action::GiveUp,
+        Action::Check,
+        Action::Erase,
+        Action::StepBack,
+        Action::Step,
+        Action::OpenDaily,
+        Action::NewPuzzle,
+        Action::Lock,
+        Action::Reset,
+        Action::Performance,
+        Action::Screenshot,
+        Action::NextTab,
+        Action::CloseTab,
+        Action::Snapshot,
+        Action::Undo,
+        Action::Redo,
+    ];
+
+    /// Get the name of the action in the settings file, like `start_pause`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Action::StartPause => "start_pause",
+            Action::Faster => "faster",
+            Action::Slower => "slower",
+            Action::SwitchMode => "switch_mode",
+            Action::Race => "race",
+            Action::Anneal => "anneal",
+            Action::Heatmap => "heatmap",
+            Action::SplitView => "split_view",
+            Action::SearchTree => "search_tree",
+            Action::Announce => "announce",
+            Action::Mute => "mute",
+            Action::Settings => "settings",
+            Action::Help => "help",
+            Action::Hint => "hint",
+            Action::Play => "play",
+            Action::GiveUp => "give_up",
+            Action::Check => "check",
+            Action::Erase => "erase",
+            Action::StepBack => "step_back",
+            Action::Step => "step",
+            Action::OpenDaily => "open_daily",
+            Action::NewPuzzle => "new_puzzle",
+            Action::Lock => "lock",
+            Action::Reset => "reset",
+            Action::Performance => "performance",
+            Action::Screenshot => "screenshot",
+            Action::NextTab => "next_tab",
+            Action::CloseTab => "close_tab",
+            Action::Snapshot => "snapshot",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+        }
+    }
+
+    /// Get every shortcut for the action in the current keymap, in the order they are listed.
+    pub fn shortcuts(self) -> impl Iterator<Item = Shortcut> {
+        keymap().shortcuts[self as usize].into_iter().flatten()
+    }
+
+    /// Check whether any of the action's shortcuts, or its gamepad button, was pressed this frame.
+    pub fn pressed(self, input: &InputState) -> bool {
+        self.shortcuts().any(|shortcut| shortcut.pressed(input))
+            || GAMEPAD_BINDINGS
+                .iter()
+                .any(|&(action, button)| action == self && input.button_pressed(button))
+    }
+
+    /// Check whether any of the action's shortcuts was pressed this frame, or has been held down
+    /// long enough to repeat, for actions that can be done over and over by holding a key.
+    pub fn repeated(self, rl: &RaylibHandle, input: &InputState) -> bool {
+        self.pressed(input)
+            || self.shortcuts().any(|shortcut| {
+                rl.is_key_pressed_repeat(shortcut.key) && input.ctrl == shortcut.ctrl
+            })
+    }
+
+    /// Get the action's shortcuts written out for the user, like "Right, S", or [`None`] if it
+    /// has none.
+    pub fn label(self) -> Option<String> {
+        let shortcuts: Vec<_> = self
+            .shortcuts()
+            .map(|shortcut| shortcut.to_string())
+            .collect();
+        (!shortcuts.is_empty()).then(|| shortcuts.join(", "))
+    }
+}
+
+impl std::str::FromStr for Action {
+    type Err = ();
+
+    /// Find the action with the supplied name from the settings file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or(())
+    }
+}
+
+/// A key, along with the modifiers that have to be held down with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    /// The key to press.
+    pub key: KeyboardKey,
+
+    /// Whether Control has to be held down. Shortcuts without it don't work while it is held, so
+    /// that S and Ctrl+S can do different things.
+    pub ctrl: bool,
+
+    /// Whether Shift has to be held down. Shortcuts without it still work while it is held, since
+    /// some of them use Shift to do things slightly differently.
+    pub shift: bool,
+}
+
+impl Shortcut {
+    /// A key pressed on its own.
+    pub const fn key(key: KeyboardKey) -> Shortcut {
+        Shortcut {
+            key,
+            ctrl: false,
+            shift: false,
+        }
+    }
+
+    /// A key pressed with Control held down.
+    pub const fn ctrl(key: KeyboardKey) -> Shortcut {
+        Shortcut {
+            key,
+            ctrl: true,
+            shift: false,
+        }
+    }
+
+    /// A key pressed with Shift held down.
+    pub const fn shift(key: KeyboardKey) -> Shortcut {
+        Shortcut {
+            key,
+            ctrl: false,
+            shift: true,
+        }
+    }
+
+    /// Check whether the shortcut was pressed this frame.
+    pub fn pressed(&self, input: &InputState) -> bool {
+        input.key_pressed(self.key) && input.ctrl == self.ctrl && (input.shift || !self.shift)
+    }
+
+    /// Check whether pressing one shortcut could set off the other as well.
+    ///
+    /// Shortcuts without Shift still work while it is held, so a key with and without Shift
+    /// overlap, but a key with and without Control don't.
+    pub fn overlaps(&self, other: &Shortcut) -> bool {
+        self.key == other.key && self.ctrl == other.ctrl
+    }
+
+    /// Write the shortcut the way it is written in the settings file, like "Ctrl+S" or
+    /// "Shift+SLASH". Unlike [`Display`](fmt::Display), this is the same in every language, and
+    /// can be read back with [`str::parse`].
+    pub fn to_config(&self) -> String {
+        let ctrl = if self.ctrl { "Ctrl+" } else { "" };
+        let shift = if self.shift { "Shift+" } else { "" };
+        format!("{ctrl}{shift}{}", key_name(self.key))
+    }
+}
+
+impl std::str::FromStr for Shortcut {
+    type Err = ();
+
+    /// Read a shortcut from the settings file, like "Ctrl+S", "Shift+SLASH", or "Right".
+    ///
+    /// Any modifiers come first. Keys are named the way raylib names them, without the `KEY_` in
+    /// front, in any case, so the names in the help overlay work too. A question mark is short for
+    /// Shift and slash. Only the keys in [`BINDABLE_KEYS`] can be used.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "?" {
+            return Ok(Shortcut::shift(KeyboardKey::KEY_SLASH));
+        }
+
+        let mut parts: Vec<_> = s.split('+').map(str::trim).collect();
+        let name = parts.pop().ok_or(())?;
+        let key = BINDABLE_KEYS
+            .into_iter()
+            .find(|&key| key_name(key).eq_ignore_ascii_case(name))
+            .ok_or(())?;
+        let mut shortcut = Shortcut::key(key);
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => shortcut.ctrl = true,
+                "shift" => shortcut.shift = true,
+                _ => return Err(()),
+            }
+        }
+        Ok(shortcut)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    /// Write the shortcut the way it is printed on the keyboard, like "Ctrl+S".
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Shift and slash is how a question mark is typed on most keyboards.
+        if self.shift && self.key == KeyboardKey::KEY_SLASH {
+            return write!(f, "?");
+        }
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        let code = self.key as i32;
+        let letters = KeyboardKey::KEY_A as i32..=KeyboardKey::KEY_Z as i32;
+        let function_keys = KeyboardKey::KEY_F1 as i32..=KeyboardKey::KEY_F12 as i32;
+        if letters.contains(&code) {
+            write!(f, "{}", char::from(b'A' + (code - letters.start()) as u8))
+        } else if function_keys.contains(&code) {
+            write!(f, "F{}", code - KeyboardKey::KEY_F1 as i32 + 1)
+        } else {
+            write!(f, "{}", Message::Key(self.key))
+        }
+    }
+}
+
+/// Every keyboard shortcut, along with what it does. An action with more than one shortcut is
+/// listed once for each.
+pub const BINDINGS: &[(Action, Shortcut)] = &[
+    (Action::StartPause, Shortcut::key(KeyboardKey::KEY_SPACE)),
+    (Action::Faster, Shortcut::key(KeyboardKey::KEY_UP)),
+    (Action::Slower, Shortcut::key(KeyboardKey::KEY_DOWN)),
+    (Action::SwitchMode, Shortcut::key(KeyboardKey::KEY_L)),
+    (Action::Race, Shortcut::key(KeyboardKey::KEY_K)),
+    (Action::Anneal, Shortcut::key(KeyboardKey::KEY_J)),
+    (Action::Heatmap, Shortcut::key(KeyboardKey::KEY_M)),
+    (Action::SplitView, Shortcut::key(KeyboardKey::KEY_V)),
+    (Action::SearchTree, Shortcut::key(KeyboardKey::KEY_T)),
+    (Action::Announce, Shortcut::key(KeyboardKey::KEY_A)),
+    (Action::Mute, Shortcut::key(KeyboardKey::KEY_N)),
+    (Action::Settings, Shortcut::key(KeyboardKey::KEY_O)),
+    (Action::Help, Shortcut::shift(KeyboardKey::KEY_SLASH)),
+    (Action::Hint, Shortcut::key(KeyboardKey::KEY_H)),
+    (Action::Play, Shortcut::key(KeyboardKey::KEY_P)),
+    (Action::GiveUp, Shortcut::key(KeyboardKey::KEY_G)),
+    (Action::Check, Shortcut::key(KeyboardKey::KEY_C)),
+    (Action::Erase, Shortcut::key(KeyboardKey::KEY_BACKSPACE)),
+    (Action::Erase, Shortcut::key(KeyboardKey::KEY_DELETE)),
+    (Action::StepBack, Shortcut::key(KeyboardKey::KEY_LEFT)),
+    (Action::Step, Shortcut::key(KeyboardKey::KEY_RIGHT)),
+    (Action::Step, Shortcut::key(KeyboardKey::KEY_S)),
+    (Action::OpenDaily, Shortcut::key(KeyboardKey::KEY_D)),
+    (Action::NewPuzzle, Shortcut::key(KeyboardKey::KEY_E)),
+    (Action::Lock, Shortcut::key(KeyboardKey::KEY_ENTER)),
+    (Action::Reset, Shortcut::key(KeyboardKey::KEY_R)),
+    (Action::Performance, Shortcut::key(KeyboardKey::KEY_F3)),
+    (Action::Screenshot, Shortcut::key(KeyboardKey::KEY_F12)),
+    (Action::NextTab, Shortcut::ctrl(KeyboardKey::KEY_TAB)),
+    (Action::CloseTab, Shortcut::ctrl(KeyboardKey::KEY_W)),
+    (Action::Snapshot, Shortcut::ctrl(KeyboardKey::KEY_S)),
+    (Action::Undo, Shortcut::ctrl(KeyboardKey::KEY_Z)),
+    (Action::Redo, Shortcut::ctrl(KeyboardKey::KEY_Y)),
+];
+
+/// Every gamepad button that does an action, along with the action. The d-pad and the buttons for
+/// typing digits are handled by [`Gamepad`](crate::gamepad::Gamepad) instead.
+pub const GAMEPAD_BINDINGS: &[(Action, GamepadButton)] = &[
+    (
+        Action::StartPause,
+        GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT,
+    ),
+    (Action::Hint, GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+    (
+        Action::Erase,
+        GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+    ),
+    (
+        Action::StepBack,
+        GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+    ),
+    (Action::Step, GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+];
+
+/// The keys which shortcuts can be made from. The digits are left out, since they are always used
+/// for filling in cells.
+pub const BINDABLE_KEYS: [KeyboardKey; 64] = [
+    KeyboardKey::KEY_A,
+    KeyboardKey::KEY_B,
+    KeyboardKey::KEY_C,
+    KeyboardKey::KEY_D,
+    KeyboardKey::KEY_E,
+    KeyboardKey::KEY_F,
+    KeyboardKey::KEY_G,
+    KeyboardKey::KEY_H,
+    KeyboardKey::KEY_I,
+    KeyboardKey::KEY_J,
+    KeyboardKey::KEY_K,
+    KeyboardKey::KEY_L,
+    KeyboardKey::KEY_M,
+    KeyboardKey::KEY_N,
+    KeyboardKey::KEY_O,
+    KeyboardKey::KEY_P,
+    KeyboardKey::KEY_Q,
+    KeyboardKey::KEY_R,
+    KeyboardKey::KEY_S,
+    KeyboardKey::KEY_T,
+    KeyboardKey::KEY_U,
+    KeyboardKey::KEY_V,
+    KeyboardKey::KEY_W,
+    KeyboardKey::KEY_X,
+    KeyboardKey::KEY_Y,
+    KeyboardKey::KEY_Z,
+    KeyboardKey::KEY_F1,
+    KeyboardKey::KEY_F2,
+    KeyboardKey::KEY_F3,
+    KeyboardKey::KEY_F4,
+    KeyboardKey::KEY_F5,
+    KeyboardKey::KEY_F6,
+    KeyboardKey::KEY_F7,
+    KeyboardKey::KEY_F8,
+    KeyboardKey::KEY_F9,
+    KeyboardKey::KEY_F10,
+    KeyboardKey::KEY_F11,
+    KeyboardKey::KEY_F12,
+    KeyboardKey::KEY_SPACE,
+    KeyboardKey::KEY_ESCAPE,
+    KeyboardKey::KEY_ENTER,
+    KeyboardKey::KEY_TAB,
+    KeyboardKey::KEY_BACKSPACE,
+    KeyboardKey::KEY_INSERT,
+    KeyboardKey::KEY_DELETE,
+    KeyboardKey::KEY_RIGHT,
+    KeyboardKey::KEY_LEFT,
+    KeyboardKey::KEY_DOWN,
+    KeyboardKey::KEY_UP,
+    KeyboardKey::KEY_PAGE_UP,
+    KeyboardKey::KEY_PAGE_DOWN,
+    KeyboardKey::KEY_HOME,
+    KeyboardKey::KEY_END,
+    KeyboardKey::KEY_APOSTROPHE,
+    KeyboardKey::KEY_COMMA,
+    KeyboardKey::KEY_MINUS,
+    KeyboardKey::KEY_PERIOD,
+    KeyboardKey::KEY_SLASH,
+    KeyboardKey::KEY_SEMICOLON,
+    KeyboardKey::KEY_EQUAL,
+    KeyboardKey::KEY_GRAVE,
+    KeyboardKey::KEY_LEFT_BRACKET,
+    KeyboardKey::KEY_RIGHT_BRACKET,
+    KeyboardKey::KEY_BACKSLASH,
+];
+
+/// Get the name that raylib gives a key, without the `KEY_` in front, like `PAGE_UP`.
+pub fn key_name(key: KeyboardKey) -> String {
+    let name = format!("{key:?}");
+    name.strip_prefix("KEY_").unwrap_or(&name).to_string()
+}
+
+/// The most shortcuts that an action can have.
+pub const MAX_SHORTCUTS: usize = 3;
+
+/// The shortcuts for every action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Keymap {
+    /// The shortcuts for each action, indexed by the action's place in [`Action::ALL`].
+    shortcuts: [[Option<Shortcut>; MAX_SHORTCUTS]; Action::ALL.len()],
+}
+
+impl Keymap {
+    /// The shortcuts listed in [`BINDINGS`].
+    pub const DEFAULT: Keymap = {
+        let mut shortcuts = [[None; MAX_SHORTCUTS]; Action::ALL.len()];
+        let mut binding = 0;
+        while binding < BINDINGS.len() {
+            let (action, shortcut) = BINDINGS[binding];
+            let mut slot = 0;
+            while shortcuts[action as usize][slot].is_some() {
+                slot += 1;
+            }
+            shortcuts[action as usize][slot] = Some(shortcut);
+            binding += 1;
+        }
+        Keymap { shortcuts }
+    };
+
+    /// Replace the shortcuts for an action. An action with no shortcuts can only be done with the
+    /// mouse, if at all.
+    ///
+    /// Returns whether the shortcuts fit. If there are more than [`MAX_SHORTCUTS`] of them,
+    /// `false` is returned and the keymap is left as it was.
+    pub fn bind(&mut self, action: Action, shortcuts: &[Shortcut]) -> bool {
+        if shortcuts.len() > MAX_SHORTCUTS {
+            return false;
+        }
+        let slots = &mut self.shortcuts[action as usize];
+        *slots = [None; MAX_SHORTCUTS];
+        for (slot, &shortcut) in slots.iter_mut().zip(shortcuts) {
+            *slot = Some(shortcut);
+        }
+        true
+    }
+
+    /// Write the shortcuts for an action the way they are written in the settings file, like
+    /// "RIGHT, S".
+    pub fn to_config(&self, action: Action) -> String {
+        let shortcuts: Vec<_> = self.shortcuts[action as usize]
+            .into_iter()
+            .flatten()
+            .map(|shortcut| shortcut.to_config())
+            .collect();
+        shortcuts.join(", ")
+    }
+
+    /// Find a shortcut which would set off two different actions at once, if there is one. The
+    /// shortcut is returned along with both actions.
+    pub fn conflict(&self) -> Option<(Shortcut, Action, Action)> {
+        let bindings: Vec<_> = Action::ALL
+            .into_iter()
+            .flat_map(|action| {
+                self.shortcuts[action as usize]
+                    .into_iter()
+                    .flatten()
+                    .map(move |shortcut| (action, shortcut))
+            })
+            .collect();
+        bindings
+            .iter()
+            .enumerate()
+            .find_map(|(i, &(first, shortcut))| {
+                bindings[i + 1..]
+                    .iter()
+                    .find(|&&(second, other)| second != first && shortcut.overlaps(&other))
+                    .map(|&(second, _)| (shortcut, first, second))
+            })
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::DEFAULT
+    }
+}
+
+thread_local! {
+    /// The shortcuts that are in use. Input is only handled on the main thread, so there is no
+    /// point sharing this between threads.
+    static KEYMAP: Cell<Keymap> = const { Cell::new(Keymap::DEFAULT) };
+}
+
+/// Use the supplied shortcuts from now on.
+pub fn set_keymap(keymap: Keymap) {
+    KEYMAP.set(keymap);
+}
+
+/// Get the shortcuts that are in use.
+pub fn keymap() -> Keymap {
+    KEYMAP.get()
+}
+
+/// Add the shortcuts for some actions to the end of a tooltip, like "Start the puzzle over (R)".
+pub fn tooltip(text: Message, actions: &[Action]) -> String {
+    let labels: Vec<_> = actions.iter().filter_map(|action| action.label()).collect();
+    if labels.is_empty() {
+        text.to_string()
+    } else {
+        format!("{text} ({})", labels.join("/"))
+    }
+}
+
+/// Get every shortcut written out for the user, next to what it does, in the order that the help
+/// overlay lists them.
+///
+/// The digit keys aren't actions, since what they do depends on the mode, so they are listed at
+/// the end.
+pub fn help() -> Vec<(String, String)> {
+    let mut rows: Vec<_> = Action::ALL
+        .into_iter()
+        .filter_map(|action| Some((action.label()?, Message::Action(action).to_string())))
+        .collect();
+    rows.push(("1-9".to_string(), Message::TypeDigit.to_string()));
+    rows.push((
+        Message::ShiftDigit.to_string(),
+        Message::PencilMarkDigit.to_string(),
+    ));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortcuts() {
+        assert_eq!(Action::Step.label().unwrap(), "Right, S");
+        assert_eq!(Action::Snapshot.label().unwrap(), "Ctrl+S");
+        assert_eq!(Action::Help.label().unwrap(), "?");
+        assert_eq!(Action::Performance.label().unwrap(), "F3");
+
+        let input = InputState {
+            ctrl: true,
+            keys_pressed: vec![KeyboardKey::KEY_S],
+            ..InputState::default()
+        };
+        assert!(Action::Snapshot.pressed(&input));
+        assert!(!Action::Step.pressed(&input));
+
+        // Every action should have a shortcut, or it couldn't be done from the keyboard.
+        assert_eq!(help().len(), Action::ALL.len() + 2);
+    }
+
+    #[test]
+    fn test_keymap() {
+        // The keymap finds each action's shortcuts by where it is in the list of every action.
+        for (index, action) in Action::ALL.into_iter().enumerate() {
+            assert_eq!(action as usize, index);
+            assert_eq!(action.name().parse(), Ok(action));
+        }
+        assert_eq!(Keymap::DEFAULT.conflict(), None);
+        assert_eq!(Keymap::DEFAULT.to_config(Action::Step), "RIGHT, S");
+
+        assert_eq!("ctrl+s".parse(), Ok(Shortcut::ctrl(KeyboardKey::KEY_S)));
+        assert_eq!(
+            "Page_Up".parse(),
+            Ok(Shortcut::key(KeyboardKey::KEY_PAGE_UP))
+        );
+        assert_eq!("?".parse(), Ok(Shortcut::shift(KeyboardKey::KEY_SLASH)));
+        let help = Shortcut::shift(KeyboardKey::KEY_SLASH);
+        assert_eq!(help.to_config().parse(), Ok(help));
+        assert_eq!("Alt+S".parse::<Shortcut>(), Err(()));
+        assert_eq!("1".parse::<Shortcut>(), Err(()));
+
+        let mut keymap = Keymap::DEFAULT;
+        assert!(keymap.bind(Action::Hint, &[Shortcut::key(KeyboardKey::KEY_S)]));
+        let (shortcut, first, second) = keymap.conflict().unwrap();
+        assert_eq!(shortcut, Shortcut::key(KeyboardKey::KEY_S));
+        assert_eq!((first, second), (Action::Hint, Action::Step));
+    }
+}
@@ -1,7 +1,34 @@
 #![warn(missing_docs)]
 
+pub mod analysis;
+pub mod annealing;
+pub mod annotation;
+pub mod announce;
+pub mod bench;
 pub mod board;
+pub mod cli;
+pub mod constraint;
+pub mod editor;
+pub mod exact_cover;
+pub mod gamepad;
+pub mod generator;
 pub mod geometry;
 pub mod graphics;
+pub mod hints;
+pub mod history;
+pub mod keys;
+pub mod locale;
+pub mod logic;
+pub mod play;
+pub mod race;
+pub mod random;
+pub mod rating;
+pub mod search_tree;
+pub mod settings;
+pub mod sized;
+pub mod solve_log;
 pub mod solver;
+pub mod sound;
+pub mod tui;
 pub mod ui;
+pub mod watch;
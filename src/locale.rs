@@ -0,0 +1,1039 @@
+//! Translations of the text that the program shows to the user.
+//!
+//! Every piece of text in the window, along with the errors reported while it is open, is a
+//! [`Message`]. Messages are only turned into words when they are displayed, in the language picked
+//! with [`set_locale`]. Adding a language means adding a [`Locale`], and a function alongside the
+//! English one which says every message in the new language.
+//!
+//! Technique names in the window's hints, and the sentences written by
+//! [`announce`](crate::announce), are always in English.
+
+use std::cell::Cell;
+use std::fmt::{self, Formatter};
+use std::path::Path;
+use std::time::Duration;
+
+use raylib::consts::KeyboardKey;
+
+use crate::hints::Hint;
+use crate::keys::{self, Action, Shortcut};
+use crate::logic::Technique;
+use crate::rating::Difficulty;
+use crate::solver::StepMode;
+
+/// A language that the program can be shown in.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+}
+
+impl Locale {
+    /// Every locale, in the order that the settings screen cycles through them.
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::French];
+
+    /// Pick the locale asked for by the environment, the same way as other programs do.
+    ///
+    /// The first of `LC_ALL`, `LC_MESSAGES`, and `LANG` which is set is used, so `fr_FR.UTF-8`
+    /// picks French. Languages without a translation fall back to English.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .find(|value| !value.is_empty())
+            .and_then(|value| {
+                let value = value.to_string_lossy();
+                let language = value.split(['_', '.', '@']).next()?;
+                language.parse().ok()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the name of the language, in that language.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Locale::English => "English",
+            Locale::French => "Français",
+        }
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = ();
+
+    /// Parse a two letter language code, like `en`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "en" => Ok(Self::English),
+            "fr" => Ok(Self::French),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    /// Write the two letter language code of the locale.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::English => write!(f, "en"),
+            Self::French => write!(f, "fr"),
+        }
+    }
+}
+
+thread_local! {
+    /// The locale that messages are displayed in.
+    static LOCALE: Cell<Locale> = const { Cell::new(Locale::English) };
+}
+
+/// Display messages in the supplied locale from now on.
+pub fn set_locale(locale: Locale) {
+    LOCALE.set(locale);
+}
+
+/// Get the locale that messages are displayed in.
+pub fn locale() -> Locale {
+    LOCALE.get()
+}
+
+/// A piece of text shown to the user. Display it to get the words in the current locale.
+///
+/// The variants are named after the English text, so they don't each need their own
+/// documentation.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message<'a> {
+    // The status strip.
+    Going,
+    Stopped,
+    Paused,
+    Solved,
+    HintFor(Technique),
+    Unsolvable,
+    Invalid,
+
+    // The stats strip and panel.
+    StepStats {
+        steps: u64,
+        backtracks: u64,
+        seconds: f32,
+    },
+    SolverStats,
+    Nodes(u64),
+    Backtracks(u64),
+    Depth {
+        depth: usize,
+        max: usize,
+    },
+    Techniques,
+    /// The name of a kind of technique, as numbered by [`Technique::kind`].
+    TechniqueKind(usize),
+    SearchTree {
+        guesses: usize,
+        depth: usize,
+    },
+
+    // The performance overlay.
+    Measuring,
+    FramesPerSecond(f64),
+    StepsPerSecondMeasured(f64),
+    /// The time spent checking the board against the rules each frame, in milliseconds.
+    ValidatingTime(f64),
+    /// The time spent drawing each frame, in milliseconds.
+    DrawingTime(f64),
+    /// The size of the solver's attempt stack, in bytes.
+    AttemptStack(usize),
+
+    // Buttons and menus.
+    Start,
+    Pause,
+    Step,
+    StepBack,
+    StartPause,
+    Hint,
+    Reset,
+    Clear,
+    Undo,
+    Redo,
+    File,
+    Edit,
+    Solve,
+    OpenDaily,
+    NewPuzzle,
+    SaveProgress,
+    ExportSnapshot,
+    ExportScreenshot,
+    CloseTab,
+    PuzzleOfTheDay,
+
+    // Edit mode and play mode.
+    EditingInvalid,
+    EditingUnsolvable,
+    EditingUnique,
+    EditingMultiple,
+    Playing {
+        seconds: u64,
+        mistakes: u32,
+    },
+    SolvedIn {
+        seconds: u64,
+        mistakes: u32,
+    },
+    GaveUp {
+        seconds: u64,
+    },
+    /// How far along one of the solvers in a race is.
+    RaceLane {
+        mode: StepMode,
+        steps: u64,
+        won: bool,
+    },
+    AnnealingProgress {
+        clashes: u32,
+        steps: u64,
+    },
+    Checked {
+        wrong: usize,
+    },
+
+    // The settings screen.
+    Theme,
+    Speed,
+    Sound,
+    FontSize,
+    Snapshots,
+    Announcements,
+    Language,
+    Light,
+    Dark,
+    StepsPerSecond(usize),
+    On,
+    Off,
+    Grid,
+    Line,
+
+    // Tooltips.
+    TipStart,
+    TipStep,
+    TipHint,
+    TipReset,
+    TipStats,
+    TipTheme,
+    TipSpeed,
+    TipSound,
+    TipFontSize,
+    TipSnapshots,
+    TipAnnouncements,
+    TipLanguage,
+
+    // The help overlay.
+    KeyboardShortcuts,
+    /// What a keyboard shortcut does.
+    Action(Action),
+    /// The name of a key which isn't a letter or a function key.
+    Key(KeyboardKey),
+    TypeDigit,
+    ShiftDigit,
+    PencilMarkDigit,
+
+    // Reports and errors.
+    SavedSnapshot(&'a Path),
+    SavedPuzzle(&'a Path),
+    SavedScreenshot(&'a Path),
+    CorruptSave(&'a Path),
+    CorruptSettings(&'a Path),
+    /// Two actions in the settings share a shortcut.
+    KeyConflict {
+        path: &'a Path,
+        shortcut: Shortcut,
+        first: Action,
+        second: Action,
+    },
+    NotABoard(&'a Path),
+    /// A command which only works on 9x9 boards was given a board of the supplied width.
+    OnlyNineByNine(usize),
+    FailedToRead {
+        path: &'a Path,
+        error: String,
+    },
+    /// The error is [`None`] if raylib doesn't say what went wrong.
+    FailedToWrite {
+        path: &'a Path,
+        error: Option<String>,
+    },
+    NoFont(String),
+    NoAudio,
+    NoSounds(String),
+
+    // The command line.
+    OneSolution,
+    SeveralSolutions,
+    /// Logic alone can't fill in any more digits.
+    NoHint,
+    SolvedCount {
+        solved: usize,
+        total: usize,
+        time: Duration,
+    },
+    /// Solving took longer than the time allowed.
+    TimedOut(Duration),
+    /// The keys for the terminal frontend.
+    TerminalHelp,
+    Watching(&'a Path),
+    /// The digit that a hint places, and the techniques that lead to it.
+    HintPlacement(&'a Hint),
+    /// The cells that a hint relies on, already written out as a list.
+    CellsInvolved(String),
+    CantWatchStdin,
+    /// A line of a puzzle collection doesn't hold a board.
+    NotAPuzzle,
+    /// Some of the generated puzzles weren't the difficulty asked for, so they were left out.
+    MissedDifficulty {
+        written: usize,
+        total: usize,
+        difficulty: Difficulty,
+    },
+}
+
+impl fmt::Display for Message<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match locale() {
+            Locale::English => english(self, f),
+            Locale::French => french(self, f),
+        }
+    }
+}
+
+/// Write a number of seconds as minutes and seconds, like `3:07`.
+fn minutes(seconds: u64) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Say a message in English.
+fn english(message: &Message, f: &mut Formatter<'_>) -> fmt::Result {
+    let mistakes = |count: u32| match count {
+        1 => "1 mistake".to_string(),
+        n => format!("{n} mistakes"),
+    };
+    match message {
+        Message::Going => write!(f, "Going..."),
+        Message::Stopped => write!(f, "Stopped"),
+        Message::Paused => write!(f, "Paused"),
+        Message::Solved => write!(f, "Solved!"),
+        Message::HintFor(technique) => write!(f, "Hint: {technique}"),
+        Message::Unsolvable => write!(f, "No solution"),
+        Message::Invalid => write!(f, "Invalid puzzle"),
+
+        Message::StepStats {
+            steps,
+            backtracks,
+            seconds,
+        } => write!(f, "{steps} steps, {backtracks} backtracks, {seconds:.2}s"),
+        Message::SolverStats => write!(f, "Solver stats"),
+        Message::Nodes(nodes) => write!(f, "Nodes: {nodes}"),
+        Message::SearchTree { guesses, depth } => {
+            write!(f, "Search tree: {guesses} guesses, {depth} deep")
+        }
+        Message::Backtracks(backtracks) => write!(f, "Backtracks: {backtracks}"),
+        Message::Depth { depth, max } => write!(f, "Depth: {depth} (max {max})"),
+        Message::Techniques => write!(f, "Techniques:"),
+        Message::TechniqueKind(kind) => write!(f, "{}", Technique::kind_name(*kind)),
+
+        Message::Measuring => write!(f, "Measuring..."),
+        Message::FramesPerSecond(frames) => write!(f, "{frames:.0} FPS"),
+        Message::StepsPerSecondMeasured(steps) => write!(f, "{steps:.0} steps/s"),
+        Message::ValidatingTime(ms) => write!(f, "is_valid: {ms:.2} ms/frame"),
+        Message::DrawingTime(ms) => write!(f, "Drawing: {ms:.2} ms/frame"),
+        Message::AttemptStack(bytes) => write!(f, "Attempt stack: {bytes} B"),
+
+        Message::Start => write!(f, "Start"),
+        Message::Pause => write!(f, "Pause"),
+        Message::Step => write!(f, "Step"),
+        Message::StepBack => write!(f, "Step back"),
+        Message::StartPause => write!(f, "Start/Pause"),
+        Message::Hint => write!(f, "Hint"),
+        Message::Reset => write!(f, "Reset"),
+        Message::Clear => write!(f, "Clear"),
+        Message::Undo => write!(f, "Undo"),
+        Message::Redo => write!(f, "Redo"),
+        Message::File => write!(f, "File"),
+        Message::Edit => write!(f, "Edit"),
+        Message::Solve => write!(f, "Solve"),
+        Message::OpenDaily => write!(f, "Open today's puzzle"),
+        Message::NewPuzzle => write!(f, "New puzzle"),
+        Message::SaveProgress => write!(f, "Save progress"),
+        Message::ExportSnapshot => write!(f, "Export snapshot"),
+        Message::ExportScreenshot => write!(f, "Export screenshot"),
+        Message::CloseTab => write!(f, "Close tab"),
+        Message::PuzzleOfTheDay => write!(f, "Puzzle of the day"),
+
+        Message::EditingInvalid => write!(f, "Editing: breaks the rules"),
+        Message::EditingUnsolvable => write!(f, "Editing: no solution"),
+        Message::EditingUnique => write!(f, "Editing: one solution"),
+        Message::EditingMultiple => write!(f, "Editing: several solutions"),
+        Message::Playing {
+            seconds,
+            mistakes: count,
+        } => {
+            write!(f, "{}, {}", minutes(*seconds), mistakes(*count))
+        }
+        Message::SolvedIn {
+            seconds,
+            mistakes: count,
+        } => {
+            write!(
+                f,
+                "Solved in {} with {}",
+                minutes(*seconds),
+                mistakes(*count)
+            )
+        }
+        Message::GaveUp { seconds } => write!(f, "Gave up after {}", minutes(*seconds)),
+        Message::RaceLane { mode, steps, won } => {
+            let name = match mode {
+                StepMode::Search => "Guessing",
+                StepMode::Logic => "Logic",
+            };
+            write!(f, "{name}: {steps} steps")?;
+            if *won {
+                write!(f, ", finished first")?;
+            }
+            Ok(())
+        }
+        Message::AnnealingProgress { clashes, steps } => {
+            write!(f, "Annealing: {clashes} clashes left after {steps} steps")
+        }
+        Message::Checked { wrong: 0 } => write!(f, "Everything so far is right"),
+        Message::Checked { wrong: 1 } => write!(f, "1 wrong digit"),
+        Message::Checked { wrong } => write!(f, "{wrong} wrong digits"),
+
+        Message::Theme => write!(f, "Theme"),
+        Message::Speed => write!(f, "Speed"),
+        Message::Sound => write!(f, "Sound"),
+        Message::FontSize => write!(f, "Font size"),
+        Message::Snapshots => write!(f, "Snapshots"),
+        Message::Announcements => write!(f, "Announcements"),
+        Message::Language => write!(f, "Language"),
+        Message::Light => write!(f, "light"),
+        Message::Dark => write!(f, "dark"),
+        Message::StepsPerSecond(steps) => write!(f, "{steps} steps per second"),
+        Message::On => write!(f, "on"),
+        Message::Off => write!(f, "off"),
+        Message::Grid => write!(f, "grid"),
+        Message::Line => write!(f, "line"),
+
+        Message::TipStart => write!(f, "Start or pause the solver"),
+        Message::TipStep => write!(f, "Take one solver step"),
+        Message::TipHint => write!(f, "Show a hint for the next digit"),
+        Message::TipReset => write!(f, "Start the puzzle over"),
+        Message::TipStats => write!(f, "Show or hide the solver stats"),
+        Message::TipTheme => write!(f, "Switch between light and dark colors"),
+        Message::TipSpeed => write!(f, "Change how many steps the solver takes each second"),
+        Message::TipSound => write!(f, "Turn the sound effects on or off"),
+        Message::TipFontSize => write!(f, "Change the size of the text"),
+        Message::TipSnapshots => write!(f, "Change how snapshots of the board are written"),
+        Message::TipAnnouncements => write!(f, "Describe each step in words on standard output"),
+        Message::TipLanguage => write!(f, "Change the language of the window"),
+
+        Message::KeyboardShortcuts => write!(f, "Keyboard shortcuts"),
+        Message::Action(action) => match action {
+            Action::StartPause => write!(f, "Start/stop solving"),
+            Action::Faster => write!(f, "Double solver speed"),
+            Action::Slower => write!(f, "Halve solver speed"),
+            Action::SwitchMode => write!(f, "Switch between guessing and logic"),
+            Action::Race => write!(f, "Race guessing against logic"),
+            Action::Anneal => write!(f, "Solve by simulated annealing"),
+            Action::Heatmap => write!(f, "Show how many candidates are left"),
+            Action::SplitView => write!(f, "Show the original puzzle as well"),
+            Action::SearchTree => write!(f, "Show the tree of guesses"),
+            Action::Announce => write!(f, "Describe each step in words"),
+            Action::Mute => write!(f, "Mute or unmute the sound effects"),
+            Action::Settings => write!(f, "Open or close the settings"),
+            Action::Help => write!(f, "Show or hide this list"),
+            Action::Hint => write!(f, "Show a hint for the next digit"),
+            Action::Play => write!(f, "Start or stop playing by hand"),
+            Action::GiveUp => write!(f, "Give up and let the solver finish"),
+            Action::Check => write!(f, "Flag wrong digits while playing"),
+            Action::Erase => write!(f, "Rub out the selected square"),
+            Action::StepBack => write!(f, "Undo the last solver step"),
+            Action::Step => write!(f, "Take one solver step while paused"),
+            Action::OpenDaily => write!(f, "Open today's puzzle in a new tab"),
+            Action::NewPuzzle => write!(f, "Make a new puzzle in a new tab"),
+            Action::Lock => write!(f, "Finish and save the new puzzle"),
+            Action::Reset => write!(f, "Start the puzzle over"),
+            Action::Performance => write!(f, "Show performance figures"),
+            Action::Screenshot => write!(f, "Save a screenshot of the board"),
+            Action::NextTab => write!(f, "Switch to the next tab"),
+            Action::CloseTab => write!(f, "Close the current tab"),
+            Action::Snapshot => write!(f, "Save a snapshot of the board"),
+            Action::Undo => write!(f, "Undo the last pencil mark"),
+            Action::Redo => write!(f, "Redo the last undone pencil mark"),
+        },
+        Message::Key(key) => match key {
+            KeyboardKey::KEY_SPACE => write!(f, "Space"),
+            KeyboardKey::KEY_UP => write!(f, "Up"),
+            KeyboardKey::KEY_DOWN => write!(f, "Down"),
+            KeyboardKey::KEY_LEFT => write!(f, "Left"),
+            KeyboardKey::KEY_RIGHT => write!(f, "Right"),
+            KeyboardKey::KEY_ENTER => write!(f, "Enter"),
+            KeyboardKey::KEY_TAB => write!(f, "Tab"),
+            KeyboardKey::KEY_BACKSPACE => write!(f, "Backspace"),
+            KeyboardKey::KEY_DELETE => write!(f, "Delete"),
+            KeyboardKey::KEY_ESCAPE => write!(f, "Escape"),
+            key => write!(f, "{}", keys::key_name(*key)),
+        },
+        Message::TypeDigit => write!(f, "Highlight a digit, or fill it in"),
+        Message::ShiftDigit => write!(f, "Shift+1-9"),
+        Message::PencilMarkDigit => write!(f, "Pencil mark the selected squares"),
+
+        Message::SavedSnapshot(path) => write!(f, "saved a snapshot of the board to {path:?}"),
+        Message::SavedPuzzle(path) => write!(f, "saved the new puzzle to {path:?}"),
+        Message::SavedScreenshot(path) => write!(f, "saved a screenshot of the board to {path:?}"),
+        Message::CorruptSave(path) => write!(f, "ignoring corrupt save file for {path:?}"),
+        Message::CorruptSettings(path) => write!(f, "ignoring corrupt settings in {path:?}"),
+        Message::KeyConflict {
+            path,
+            shortcut,
+            first,
+            second,
+        } => write!(
+            f,
+            "{shortcut} is used for both {:?} and {:?} in {path:?}, so the usual keys are used \
+             instead",
+            first.name(),
+            second.name()
+        ),
+        Message::NotABoard(path) => write!(f, "{path:?} does not contain a valid board"),
+        Message::OnlyNineByNine(size) => write!(
+            f,
+            "{size}x{size} boards can only be solved, checked, and counted; this needs a 9x9 board"
+        ),
+        Message::FailedToRead { path, error } => write!(f, "failed to read {path:?}: {error}"),
+        Message::FailedToWrite { path, error } => {
+            write!(f, "failed to write {path:?}")?;
+            match error {
+                Some(error) => write!(f, ": {error}"),
+                None => Ok(()),
+            }
+        }
+        Message::NoFont(error) => write!(
+            f,
+            "failed to load the bundled font, so the default one is used: {error}"
+        ),
+        Message::NoAudio => write!(
+            f,
+            "failed to open the audio device, so there will be no sound"
+        ),
+        Message::NoSounds(error) => write!(f, "failed to load the sound effects: {error}"),
+
+        Message::OneSolution => write!(f, "One solution"),
+        Message::SeveralSolutions => write!(f, "Several solutions"),
+        Message::NoHint => write!(f, "no digit can be filled in by logic alone"),
+        Message::SolvedCount {
+            solved,
+            total,
+            time,
+        } => write!(f, "solved {solved} of {total} puzzles in {time:.2?}"),
+        Message::TimedOut(time) => write!(f, "gave up after {time:.2?}"),
+        Message::TerminalHelp => {
+            write!(f, "Space: start/pause  S: step  Up/Down: speed  Q: quit")
+        }
+        Message::Watching(path) => write!(f, "watching {path:?} for changes"),
+        Message::HintPlacement(hint) => write!(f, "{hint}"),
+        Message::CellsInvolved(cells) => write!(f, "Cells involved: {cells}"),
+        Message::CantWatchStdin => write!(f, "standard input can't be watched for changes"),
+        Message::NotAPuzzle => write!(f, "Not a puzzle"),
+        Message::MissedDifficulty {
+            written,
+            total,
+            difficulty,
+        } => write!(
+            f,
+            "only {written} of {total} puzzles came out {difficulty}, so the rest were left out"
+        ),
+    }
+}
+
+/// Say a message in French.
+fn french(message: &Message, f: &mut Formatter<'_>) -> fmt::Result {
+    let mistakes = |count: u32| match count {
+        0 | 1 => format!("{count} erreur"),
+        n => format!("{n} erreurs"),
+    };
+    match message {
+        Message::Going => write!(f, "En cours..."),
+        Message::Stopped => write!(f, "Arrêté"),
+        Message::Paused => write!(f, "En pause"),
+        Message::Solved => write!(f, "Résolu !"),
+        Message::HintFor(technique) => write!(f, "Indice : {technique}"),
+        Message::Unsolvable => write!(f, "Aucune solution"),
+        Message::Invalid => write!(f, "Grille invalide"),
+
+        Message::StepStats {
+            steps,
+            backtracks,
+            seconds,
+        } => write!(f, "{steps} étapes, {backtracks} retours, {seconds:.2} s"),
+        Message::SolverStats => write!(f, "Statistiques"),
+        Message::Nodes(nodes) => write!(f, "Nœuds : {nodes}"),
+        Message::SearchTree { guesses, depth } => {
+            write!(
+                f,
+                "Arbre de recherche : {guesses} essais, profondeur {depth}"
+            )
+        }
+        Message::Backtracks(backtracks) => write!(f, "Retours : {backtracks}"),
+        Message::Depth { depth, max } => write!(f, "Profondeur : {depth} (max {max})"),
+        Message::Techniques => write!(f, "Techniques :"),
+        Message::TechniqueKind(kind) => match kind {
+            0 => write!(f, "singleton nu"),
+            1 => write!(f, "singleton caché"),
+            2 => write!(f, "pointage"),
+            3 => write!(f, "réclamation"),
+            4 => write!(f, "sous-ensemble nu"),
+            5 => write!(f, "sous-ensemble caché"),
+            _ => write!(f, "X-wing"),
+        },
+
+        Message::Measuring => write!(f, "Mesure en cours..."),
+        Message::FramesPerSecond(frames) => write!(f, "{frames:.0} images/s"),
+        Message::StepsPerSecondMeasured(steps) => write!(f, "{steps:.0} étapes/s"),
+        Message::ValidatingTime(ms) => write!(f, "is_valid : {ms:.2} ms/image"),
+        Message::DrawingTime(ms) => write!(f, "Dessin : {ms:.2} ms/image"),
+        Message::AttemptStack(bytes) => write!(f, "Pile d'essais : {bytes} o"),
+
+        Message::Start => write!(f, "Démarrer"),
+        Message::Pause => write!(f, "Pause"),
+        Message::Step => write!(f, "Étape"),
+        Message::StepBack => write!(f, "Étape précédente"),
+        Message::StartPause => write!(f, "Démarrer/Pause"),
+        Message::Hint => write!(f, "Indice"),
+        Message::Reset => write!(f, "Recommencer"),
+        Message::Clear => write!(f, "Effacer"),
+        Message::Undo => write!(f, "Annuler"),
+        Message::Redo => write!(f, "Rétablir"),
+        Message::File => write!(f, "Fichier"),
+        Message::Edit => write!(f, "Édition"),
+        Message::Solve => write!(f, "Résoudre"),
+        Message::OpenDaily => write!(f, "Grille du jour"),
+        Message::NewPuzzle => write!(f, "Nouvelle grille"),
+        Message::SaveProgress => write!(f, "Enregistrer"),
+        Message::ExportSnapshot => write!(f, "Exporter la grille"),
+        Message::ExportScreenshot => write!(f, "Exporter une image"),
+        Message::CloseTab => write!(f, "Fermer l'onglet"),
+        Message::PuzzleOfTheDay => write!(f, "Grille du jour"),
+
+        Message::EditingInvalid => write!(f, "Édition : règles enfreintes"),
+        Message::EditingUnsolvable => write!(f, "Édition : aucune solution"),
+        Message::EditingUnique => write!(f, "Édition : une solution"),
+        Message::EditingMultiple => write!(f, "Édition : plusieurs solutions"),
+        Message::Playing {
+            seconds,
+            mistakes: count,
+        } => {
+            write!(f, "{}, {}", minutes(*seconds), mistakes(*count))
+        }
+        Message::SolvedIn {
+            seconds,
+            mistakes: count,
+        } => {
+            write!(
+                f,
+                "Résolu en {} avec {}",
+                minutes(*seconds),
+                mistakes(*count)
+            )
+        }
+        Message::GaveUp { seconds } => write!(f, "Abandon après {}", minutes(*seconds)),
+        Message::RaceLane { mode, steps, won } => {
+            let name = match mode {
+                StepMode::Search => "Essais",
+                StepMode::Logic => "Logique",
+            };
+            write!(f, "{name} : {steps} étapes")?;
+            if *won {
+                write!(f, ", premier arrivé")?;
+            }
+            Ok(())
+        }
+        Message::AnnealingProgress { clashes, steps } => {
+            write!(
+                f,
+                "Recuit : {clashes} conflits restants après {steps} étapes"
+            )
+        }
+        Message::Checked { wrong: 0 } => write!(f, "Tout est juste pour l'instant"),
+        Message::Checked { wrong: 1 } => write!(f, "1 chiffre faux"),
+        Message::Checked { wrong } => write!(f, "{wrong} chiffres faux"),
+
+        Message::Theme => write!(f, "Thème"),
+        Message::Speed => write!(f, "Vitesse"),
+        Message::Sound => write!(f, "Son"),
+        Message::FontSize => write!(f, "Taille du texte"),
+        Message::Snapshots => write!(f, "Exports"),
+        Message::Announcements => write!(f, "Annonces"),
+        Message::Language => write!(f, "Langue"),
+        Message::Light => write!(f, "clair"),
+        Message::Dark => write!(f, "sombre"),
+        Message::StepsPerSecond(steps) => write!(f, "{steps} étapes par seconde"),
+        Message::On => write!(f, "activé"),
+        Message::Off => write!(f, "désactivé"),
+        Message::Grid => write!(f, "grille"),
+        Message::Line => write!(f, "ligne"),
+
+        Message::TipStart => write!(f, "Démarrer ou mettre en pause le solveur"),
+        Message::TipStep => write!(f, "Avancer le solveur d'une étape"),
+        Message::TipHint => write!(f, "Afficher un indice pour le prochain chiffre"),
+        Message::TipReset => write!(f, "Recommencer la grille"),
+        Message::TipStats => write!(f, "Afficher ou masquer les statistiques"),
+        Message::TipTheme => write!(f, "Passer des couleurs claires aux couleurs sombres"),
+        Message::TipSpeed => write!(f, "Changer le nombre d'étapes par seconde"),
+        Message::TipSound => write!(f, "Activer ou couper les effets sonores"),
+        Message::TipFontSize => write!(f, "Changer la taille du texte"),
+        Message::TipSnapshots => write!(f, "Changer le format des exports de la grille"),
+        Message::TipAnnouncements => write!(f, "Décrire chaque étape sur la sortie standard"),
+        Message::TipLanguage => write!(f, "Changer la langue de la fenêtre"),
+
+        Message::KeyboardShortcuts => write!(f, "Raccourcis clavier"),
+        Message::Action(action) => match action {
+            Action::StartPause => write!(f, "Démarrer ou arrêter le solveur"),
+            Action::Faster => write!(f, "Doubler la vitesse du solveur"),
+            Action::Slower => write!(f, "Diviser la vitesse du solveur par deux"),
+            Action::SwitchMode => write!(f, "Alterner entre essais et logique"),
+            Action::Race => write!(f, "Faire la course entre essais et logique"),
+            Action::Anneal => write!(f, "Résoudre par recuit simulé"),
+            Action::Heatmap => write!(f, "Afficher le nombre de candidats restants"),
+            Action::SplitView => write!(f, "Afficher aussi la grille de départ"),
+            Action::SearchTree => write!(f, "Afficher l'arbre des essais"),
+            Action::Announce => write!(f, "Décrire chaque étape en mots"),
+            Action::Mute => write!(f, "Couper ou rétablir les effets sonores"),
+            Action::Settings => write!(f, "Ouvrir ou fermer les réglages"),
+            Action::Help => write!(f, "Afficher ou masquer cette liste"),
+            Action::Hint => write!(f, "Afficher un indice pour le prochain chiffre"),
+            Action::Play => write!(f, "Jouer soi-même, ou arrêter"),
+            Action::GiveUp => write!(f, "Abandonner et laisser finir le solveur"),
+            Action::Check => write!(f, "Signaler les chiffres faux en jouant"),
+            Action::Erase => write!(f, "Effacer la case sélectionnée"),
+            Action::StepBack => write!(f, "Annuler la dernière étape du solveur"),
+            Action::Step => write!(f, "Avancer d'une étape pendant la pause"),
+            Action::OpenDaily => write!(f, "Ouvrir la grille du jour dans un onglet"),
+            Action::NewPuzzle => write!(f, "Créer une grille dans un onglet"),
+            Action::Lock => write!(f, "Terminer et enregistrer la nouvelle grille"),
+            Action::Reset => write!(f, "Recommencer la grille"),
+            Action::Performance => write!(f, "Afficher les performances"),
+            Action::Screenshot => write!(f, "Enregistrer une image de la grille"),
+            Action::NextTab => write!(f, "Passer à l'onglet suivant"),
+            Action::CloseTab => write!(f, "Fermer l'onglet"),
+            Action::Snapshot => write!(f, "Exporter la grille"),
+            Action::Undo => write!(f, "Annuler la dernière note"),
+            Action::Redo => write!(f, "Rétablir la dernière note annulée"),
+        },
+        Message::Key(key) => match key {
+            KeyboardKey::KEY_SPACE => write!(f, "Espace"),
+            KeyboardKey::KEY_UP => write!(f, "Haut"),
+            KeyboardKey::KEY_DOWN => write!(f, "Bas"),
+            KeyboardKey::KEY_LEFT => write!(f, "Gauche"),
+            KeyboardKey::KEY_RIGHT => write!(f, "Droite"),
+            KeyboardKey::KEY_ENTER => write!(f, "Entrée"),
+            KeyboardKey::KEY_TAB => write!(f, "Tab"),
+            KeyboardKey::KEY_BACKSPACE => write!(f, "Retour arrière"),
+            KeyboardKey::KEY_DELETE => write!(f, "Suppr"),
+            KeyboardKey::KEY_ESCAPE => write!(f, "Échap"),
+            key => write!(f, "{}", keys::key_name(*key)),
+        },
+        Message::TypeDigit => write!(f, "Surligner un chiffre, ou le remplir"),
+        Message::ShiftDigit => write!(f, "Maj+1-9"),
+        Message::PencilMarkDigit => write!(f, "Noter un candidat dans les cases sélectionnées"),
+
+        Message::SavedSnapshot(path) => write!(f, "grille exportée dans {path:?}"),
+        Message::SavedPuzzle(path) => write!(f, "nouvelle grille enregistrée dans {path:?}"),
+        Message::SavedScreenshot(path) => {
+            write!(f, "image de la grille enregistrée dans {path:?}")
+        }
+        Message::CorruptSave(path) => {
+            write!(f, "sauvegarde corrompue pour {path:?}, elle est ignorée")
+        }
+        Message::CorruptSettings(path) => {
+            write!(f, "réglages corrompus dans {path:?}, ils sont ignorés")
+        }
+        Message::KeyConflict {
+            path,
+            shortcut,
+            first,
+            second,
+        } => write!(
+            f,
+            "{shortcut} sert à la fois à {:?} et à {:?} dans {path:?}, les touches habituelles \
+             sont utilisées à la place",
+            first.name(),
+            second.name()
+        ),
+        Message::NotABoard(path) => write!(f, "{path:?} ne contient pas de grille valide"),
+        Message::OnlyNineByNine(size) => write!(
+            f,
+            "les grilles {size}x{size} peuvent seulement être résolues, vérifiées et \
+             comptées ; il faut une grille 9x9 ici"
+        ),
+        Message::FailedToRead { path, error } => {
+            write!(f, "impossible de lire {path:?} : {error}")
+        }
+        Message::FailedToWrite { path, error } => {
+            write!(f, "impossible d'écrire {path:?}")?;
+            match error {
+                Some(error) => write!(f, " : {error}"),
+                None => Ok(()),
+            }
+        }
+        Message::NoFont(error) => write!(
+            f,
+            "impossible de charger la police fournie, celle par défaut est utilisée : {error}"
+        ),
+        Message::NoAudio => {
+            write!(
+                f,
+                "impossible d'ouvrir la sortie audio, il n'y aura pas de son"
+            )
+        }
+        Message::NoSounds(error) => write!(f, "impossible de charger les effets sonores : {error}"),
+
+        Message::OneSolution => write!(f, "Une seule solution"),
+        Message::SeveralSolutions => write!(f, "Plusieurs solutions"),
+        Message::NoHint => write!(f, "aucun chiffre ne peut être placé par la logique seule"),
+        Message::SolvedCount {
+            solved,
+            total,
+            time,
+        } => write!(f, "{solved} grilles résolues sur {total} en {time:.2?}"),
+        Message::TimedOut(time) => write!(f, "abandon après {time:.2?}"),
+        Message::TerminalHelp => write!(
+            f,
+            "Espace : lancer/pause  S : étape  Haut/Bas : vitesse  Q : quitter"
+        ),
+        Message::Watching(path) => write!(f, "surveillance des modifications de {path:?}"),
+        Message::HintPlacement(hint) => {
+            write!(
+                f,
+                "placer {} en ligne {}, colonne {} ({})",
+                hint.entry,
+                hint.index / 9 + 1,
+                hint.index % 9 + 1,
+                Message::TechniqueKind(hint.technique.kind())
+            )?;
+            for deduction in &hint.eliminations {
+                let kind = Message::TechniqueKind(deduction.technique.kind());
+                write!(f, ", après {kind}")?;
+            }
+            Ok(())
+        }
+        Message::CellsInvolved(cells) => write!(f, "Cases concernées : {cells}"),
+        Message::CantWatchStdin => {
+            write!(
+                f,
+                "impossible de surveiller les modifications de l'entrée standard"
+            )
+        }
+        Message::NotAPuzzle => write!(f, "Pas une grille"),
+        Message::MissedDifficulty {
+            written,
+            total,
+            difficulty,
+        } => write!(
+            f,
+            "seulement {written} grilles sur {total} sont de difficulté {difficulty}, les autres \
+             ont été écartées"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale() {
+        assert_eq!("fr".parse(), Ok(Locale::French));
+        assert_eq!(Locale::French.to_string(), "fr");
+        assert_eq!("de".parse::<Locale>(), Err(()));
+
+        let message = Message::Playing {
+            seconds: 187,
+            mistakes: 1,
+        };
+        assert_eq!(message.to_string(), "3:07, 1 mistake");
+        set_locale(Locale::French);
+        assert_eq!(message.to_string(), "3:07, 1 erreur");
+        set_locale(Locale::English);
+    }
+
+    #[test]
+    fn test_every_message() {
+        let path = Path::new("boards/easy.txt");
+        let hint = Hint {
+            technique: Technique::NakedSingle,
+            index: 0,
+            entry: crate::board::Entry::One,
+            support: vec![],
+            eliminations: vec![],
+        };
+        let mut messages = vec![
+            Message::Going,
+            Message::Stopped,
+            Message::Paused,
+            Message::Solved,
+            Message::HintFor(Technique::NakedSingle),
+            Message::Unsolvable,
+            Message::Invalid,
+            Message::StepStats {
+                steps: 10,
+                backtracks: 2,
+                seconds: 1.5,
+            },
+            Message::SolverStats,
+            Message::Nodes(10),
+            Message::Backtracks(2),
+            Message::Depth { depth: 3, max: 5 },
+            Message::Techniques,
+            Message::SearchTree {
+                guesses: 4,
+                depth: 2,
+            },
+            Message::Measuring,
+            Message::FramesPerSecond(60.0),
+            Message::StepsPerSecondMeasured(1000.0),
+            Message::ValidatingTime(0.25),
+            Message::DrawingTime(1.5),
+            Message::AttemptStack(4096),
+            Message::Start,
+            Message::Pause,
+            Message::Step,
+            Message::StepBack,
+            Message::StartPause,
+            Message::Hint,
+            Message::Reset,
+            Message::Clear,
+            Message::Undo,
+            Message::Redo,
+            Message::File,
+            Message::Edit,
+            Message::Solve,
+            Message::OpenDaily,
+            Message::NewPuzzle,
+            Message::SaveProgress,
+            Message::ExportSnapshot,
+            Message::ExportScreenshot,
+            Message::CloseTab,
+            Message::PuzzleOfTheDay,
+            Message::EditingInvalid,
+            Message::EditingUnsolvable,
+            Message::EditingUnique,
+            Message::EditingMultiple,
+            Message::Playing {
+                seconds: 187,
+                mistakes: 1,
+            },
+            Message::SolvedIn {
+                seconds: 187,
+                mistakes: 0,
+            },
+            Message::GaveUp { seconds: 187 },
+            Message::RaceLane {
+                mode: StepMode::Logic,
+                steps: 10,
+                won: true,
+            },
+            Message::AnnealingProgress {
+                clashes: 3,
+                steps: 100,
+            },
+            Message::Checked { wrong: 2 },
+            Message::Theme,
+            Message::Speed,
+            Message::Sound,
+            Message::FontSize,
+            Message::Snapshots,
+            Message::Announcements,
+            Message::Language,
+            Message::Light,
+            Message::Dark,
+            Message::StepsPerSecond(60),
+            Message::On,
+            Message::Off,
+            Message::Grid,
+            Message::Line,
+            Message::TipStart,
+            Message::TipStep,
+            Message::TipHint,
+            Message::TipReset,
+            Message::TipStats,
+            Message::TipTheme,
+            Message::TipSpeed,
+            Message::TipSound,
+            Message::TipFontSize,
+            Message::TipSnapshots,
+            Message::TipAnnouncements,
+            Message::TipLanguage,
+            Message::KeyboardShortcuts,
+            Message::Key(KeyboardKey::KEY_SPACE),
+            Message::Key(KeyboardKey::KEY_A),
+            Message::TypeDigit,
+            Message::ShiftDigit,
+            Message::PencilMarkDigit,
+            Message::SavedSnapshot(path),
+            Message::SavedPuzzle(path),
+            Message::SavedScreenshot(path),
+            Message::CorruptSave(path),
+            Message::CorruptSettings(path),
+            Message::KeyConflict {
+                path,
+                shortcut: Shortcut::ctrl(KeyboardKey::KEY_S),
+                first: Action::ALL[0],
+                second: Action::ALL[1],
+            },
+            Message::NotABoard(path),
+            Message::OnlyNineByNine(16),
+            Message::FailedToRead {
+                path,
+                error: "not found".to_string(),
+            },
+            Message::FailedToWrite { path, error: None },
+            Message::NoFont("font.ttf".to_string()),
+            Message::NoAudio,
+            Message::NoSounds("assets".to_string()),
+            Message::OneSolution,
+            Message::SeveralSolutions,
+            Message::NoHint,
+            Message::SolvedCount {
+                solved: 3,
+                total: 4,
+                time: Duration::from_millis(1500),
+            },
+            Message::TimedOut(Duration::from_secs(1)),
+            Message::TerminalHelp,
+            Message::Watching(path),
+            Message::HintPlacement(&hint),
+            Message::CellsInvolved("r1c1".to_string()),
+            Message::CantWatchStdin,
+            Message::NotAPuzzle,
+            Message::MissedDifficulty {
+                written: 3,
+                total: 4,
+                difficulty: Difficulty::Hard,
+            },
+        ];
+        messages.extend((0..Technique::KINDS).map(Message::TechniqueKind));
+        messages.extend(Action::ALL.into_iter().map(Message::Action));
+
+        for locale in Locale::ALL {
+            set_locale(locale);
+            for message in &messages {
+                assert!(
+                    !message.to_string().is_empty(),
+                    "{message:?} is empty in {locale}"
+                );
+            }
+        }
+        set_locale(Locale::English);
+    }
+}
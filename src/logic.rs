@@ -0,0 +1,650 @@
+//! A deduction engine which solves Sudoku boards the way that people do.
+//!
+//! Instead of guessing and backtracking, the engine keeps track of the candidates for every empty
+//! cell and applies named techniques to narrow them down. Each technique either places a digit or
+//! eliminates some candidates, and every deduction records which cells it relied on. This makes it
+//! possible to explain a solve step by step, which is what the hint and rating features are built
+//! on.
+
+use itertools::Itertools;
+
+use crate::board::{Board, Candidates, Entry, Unit, Variant};
+
+/// A named solving technique.
+///
+/// The variants are listed roughly in order of difficulty, and the engine always tries the easier
+/// techniques first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Technique {
+    /// A cell has only one candidate left.
+    NakedSingle,
+
+    /// A digit has only one possible cell left in the unit.
+    HiddenSingle(Unit),
+
+    /// Within a box, a digit can only go in one row or column, so it can be eliminated from the
+    /// rest of that row or column.
+    Pointing {
+        /// The box which the candidates are confined to.
+        from: Unit,
+
+        /// The row or column which the candidates are eliminated from.
+        into: Unit,
+    },
+
+    /// Within a row or column, a digit can only go in one box, so it can be eliminated from the
+    /// rest of that box.
+    Claiming {
+        /// The row or column which the candidates are confined to.
+        from: Unit,
+
+        /// The box which the candidates are eliminated from.
+        into: Unit,
+    },
+
+    /// Some number of cells in a unit have exactly that many candidates between them, so those
+    /// candidates can be eliminated from the rest of the unit.
+    NakedSubset {
+        /// The unit containing the cells.
+        unit: Unit,
+
+        /// The number of cells, from 2 to 4.
+        size: usize,
+    },
+
+    /// Some number of digits can only go in exactly that many cells of a unit, so every other
+    /// candidate can be eliminated from those cells.
+    HiddenSubset {
+        /// The unit containing the cells.
+        unit: Unit,
+
+        /// The number of digits, from 2 to 4.
+        size: usize,
+    },
+
+    /// A digit is confined to the same two columns in two different rows (or the same two rows in
+    /// two different columns), so it can be eliminated from the rest of those columns (or rows).
+    XWing {
+        /// The digit which the technique applies to.
+        entry: Entry,
+
+        /// The two rows or columns which the digit is confined in.
+        lines: [Unit; 2],
+    },
+}
+
+impl Technique {
+    /// The number of kinds of technique, as numbered by [`Technique::kind`].
+    pub const KINDS: usize = 7;
+
+    /// Get which kind of technique this is, ignoring where it was applied, as a number below
+    /// [`Technique::KINDS`]. The kinds are numbered from easiest to hardest.
+    pub const fn kind(&self) -> usize {
+        match self {
+            Technique::NakedSingle => 0,
+            Technique::HiddenSingle(_) => 1,
+            Technique::Pointing { .. } => 2,
+            Technique::Claiming { .. } => 3,
+            Technique::NakedSubset { .. } => 4,
+            Technique::HiddenSubset { .. } => 5,
+            Technique::XWing { .. } => 6,
+        }
+    }
+
+    /// Get the name of a kind of technique, as numbered by [`Technique::kind`].
+    pub const fn kind_name(kind: usize) -> &'static str {
+        match kind {
+            0 => "naked single",
+            1 => "hidden single",
+            2 => "pointing",
+            3 => "claiming",
+            4 => "naked subset",
+            5 => "hidden subset",
+            _ => "X-wing",
+        }
+    }
+
+    /// Check whether this technique places a digit, as opposed to eliminating candidates.
+    pub const fn is_single(&self) -> bool {
+        matches!(self, Technique::NakedSingle | Technique::HiddenSingle(_))
+    }
+}
+
+/// Get the name of a subset of the given size, like "pair" or "triple".
+fn subset_name(size: usize) -> &'static str {
+    match size {
+        2 => "pair",
+        3 => "triple",
+        4 => "quad",
+        _ => "subset",
+    }
+}
+
+impl std::fmt::Display for Technique {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Technique::NakedSingle => write!(f, "naked single"),
+            Technique::HiddenSingle(unit) => write!(f, "hidden single in {unit}"),
+            Technique::Pointing { from, into } => {
+                write!(f, "pointing candidates from {from} into {into}")
+            }
+            Technique::Claiming { from, into } => {
+                write!(f, "claiming candidates from {from} into {into}")
+            }
+            Technique::NakedSubset { unit, size } => {
+                write!(f, "naked {} in {unit}", subset_name(*size))
+            }
+            Technique::HiddenSubset { unit, size } => {
+                write!(f, "hidden {} in {unit}", subset_name(*size))
+            }
+            Technique::XWing { entry, lines } => {
+                write!(f, "X-wing on {entry}s in {} and {}", lines[0], lines[1])
+            }
+        }
+    }
+}
+
+/// The effect that a deduction has on the grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Write a digit into a cell.
+    Place {
+        /// The index of the cell.
+        index: usize,
+
+        /// The digit to write.
+        entry: Entry,
+    },
+
+    /// Remove some candidates. Each pair holds the index of a cell and the candidate to remove
+    /// from it.
+    Eliminate(Vec<(usize, Entry)>),
+}
+
+/// A single logical step, along with the reasoning behind it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Deduction {
+    /// The technique which justifies the step.
+    pub technique: Technique,
+
+    /// What the step does to the grid.
+    pub action: Action,
+
+    /// The cells which the technique relied on. For example, the two cells of a naked pair, or the
+    /// filled cells which rule out every other candidate of a naked single.
+    pub support: Vec<usize>,
+}
+
+/// Get every cell which shares a unit with the cell at the supplied index on an ordinary board, not
+/// including the cell itself. Use [`Variant::peers`] for boards with extra units.
+pub fn peers(index: usize) -> impl Iterator<Item = usize> {
+    Unit::containing(index)
+        .into_iter()
+        .flat_map(|unit| unit.cells())
+        .filter(move |&peer| peer != index)
+        .unique()
+}
+
+/// A board with candidates tracked for every empty cell.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid {
+    cells: [Option<Entry>; 81],
+    candidates: [Candidates; 81],
+    variant: Variant,
+}
+
+impl Grid {
+    /// Set up a grid for a board.
+    ///
+    /// The candidates of every empty cell start out as the digits which do not yet appear in any
+    /// of the cell's units.
+    pub fn new(board: &Board) -> Grid {
+        Grid {
+            cells: std::array::from_fn(|index| board.get_cell_index(index)),
+            candidates: std::array::from_fn(|index| board.candidates(index)),
+            variant: board.variant(),
+        }
+    }
+
+    /// Get the extra rules of the board that the grid was set up for.
+    pub const fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Retrieve the entry in the cell at the supplied index.
+    pub const fn entry(&self, index: usize) -> Option<Entry> {
+        self.cells[index]
+    }
+
+    /// Retrieve the remaining candidates of the cell at the supplied index. Filled cells have no
+    /// candidates.
+    pub const fn candidates(&self, index: usize) -> Candidates {
+        self.candidates[index]
+    }
+
+    /// Check whether every cell has been filled.
+    pub fn is_solved(&self) -> bool {
+        self.cells.iter().all(Option::is_some)
+    }
+
+    /// Check whether some empty cell has run out of candidates, which means that the grid can no
+    /// longer be completed.
+    pub fn is_stuck(&self) -> bool {
+        (0..81).any(|index| self.cells[index].is_none() && self.candidates[index].is_empty())
+    }
+
+    /// Write the grid's entries into a board.
+    pub fn write_to(&self, board: &mut Board) {
+        for (index, entry) in self.cells.into_iter().enumerate() {
+            board.set_cell_index(index, entry);
+        }
+    }
+
+    /// Write a digit into a cell, removing it from the candidates of the cell's peers.
+    pub fn place(&mut self, index: usize, entry: Entry) {
+        self.cells[index] = Some(entry);
+        self.candidates[index] = Candidates::NONE;
+        for peer in self.variant.peers(index) {
+            self.candidates[peer].remove(entry);
+        }
+    }
+
+    /// Remove a candidate from a cell.
+    pub fn eliminate(&mut self, index: usize, entry: Entry) {
+        self.candidates[index].remove(entry);
+    }
+
+    /// Apply the effect of a deduction.
+    pub fn apply(&mut self, deduction: &Deduction) {
+        match &deduction.action {
+            &Action::Place { index, entry } => {
+                log::debug!(
+                    "{}: placed {entry} at row {}, column {}",
+                    deduction.technique,
+                    index / 9 + 1,
+                    index % 9 + 1
+                );
+                self.place(index, entry);
+            }
+            Action::Eliminate(eliminations) => {
+                log::debug!(
+                    "{}: eliminated {} candidates",
+                    deduction.technique,
+                    eliminations.len()
+                );
+                for &(index, entry) in eliminations {
+                    self.eliminate(index, entry);
+                }
+            }
+        }
+    }
+
+    /// Find the easiest deduction which makes progress on the grid.
+    ///
+    /// The techniques are tried in order of difficulty, so the returned deduction always uses the
+    /// easiest technique available. If no technique applies, or the grid is stuck, [`None`] is
+    /// returned.
+    pub fn find_deduction(&self) -> Option<Deduction> {
+        self.find_deduction_where(|_| true)
+    }
+
+    /// Find the easiest deduction which only uses the techniques that `allowed` accepts.
+    ///
+    /// This works like [`Grid::find_deduction`], except that techniques which are not allowed are
+    /// skipped over as if they didn't exist.
+    pub fn find_deduction_where(&self, allowed: impl Fn(&Technique) -> bool) -> Option<Deduction> {
+        if self.is_stuck() {
+            return None;
+        }
+
+        let allowed = |deduction: &Deduction| allowed(&deduction.technique);
+        self.naked_single()
+            .filter(allowed)
+            .or_else(|| self.hidden_single().filter(allowed))
+            .or_else(|| self.locked_candidates().filter(allowed))
+            .or_else(|| (2..=4).find_map(|size| self.naked_subset(size).filter(allowed)))
+            .or_else(|| (2..=4).find_map(|size| self.hidden_subset(size).filter(allowed)))
+            .or_else(|| self.x_wing().filter(allowed))
+    }
+
+    /// Get the empty cells of a unit which still have the supplied candidate.
+    fn cells_with_candidate(&self, unit: Unit, entry: Entry) -> Vec<usize> {
+        self.variant
+            .cells(unit)
+            .into_iter()
+            .filter(|&index| self.candidates[index].contains(entry))
+            .collect()
+    }
+
+    /// Get the empty cells of a unit.
+    fn empty_cells(&self, unit: Unit) -> Vec<usize> {
+        self.variant
+            .cells(unit)
+            .into_iter()
+            .filter(|&index| self.cells[index].is_none())
+            .collect()
+    }
+
+    /// Find a filled peer of the cell at `index` holding the supplied entry.
+    fn blocker(&self, index: usize, entry: Entry) -> Option<usize> {
+        self.variant
+            .peers(index)
+            .into_iter()
+            .find(|&peer| self.cells[peer] == Some(entry))
+    }
+
+    fn naked_single(&self) -> Option<Deduction> {
+        (0..81).find_map(|index| {
+            let entry = self.candidates[index].single()?;
+            let support = self
+                .variant
+                .peers(index)
+                .into_iter()
+                .filter(|&peer| self.cells[peer].is_some())
+                .collect();
+            Some(Deduction {
+                technique: Technique::NakedSingle,
+                action: Action::Place { index, entry },
+                support,
+            })
+        })
+    }
+
+    fn hidden_single(&self) -> Option<Deduction> {
+        // Boxes first, since those are usually the easiest for people to spot.
+        let units = (0..9)
+            .map(Unit::Box)
+            .chain((0..9).map(Unit::Row))
+            .chain((0..9).map(Unit::Column))
+            .chain(self.variant.extra_units());
+
+        units.into_iter().find_map(|unit| {
+            Entry::ALL.into_iter().find_map(|entry| {
+                let [index] = self.cells_with_candidate(unit, entry)[..] else {
+                    return None;
+                };
+
+                // The support is whatever rules out the digit in the rest of the unit.
+                let support = self
+                    .empty_cells(unit)
+                    .into_iter()
+                    .filter(|&other| other != index)
+                    .filter_map(|other| self.blocker(other, entry))
+                    .unique()
+                    .collect();
+                Some(Deduction {
+                    technique: Technique::HiddenSingle(unit),
+                    action: Action::Place { index, entry },
+                    support,
+                })
+            })
+        })
+    }
+
+    fn locked_candidates(&self) -> Option<Deduction> {
+        let boxes = (0..9).map(Unit::Box);
+        let lines = (0..9).map(Unit::Row).chain((0..9).map(Unit::Column));
+
+        // Pointing: the candidates in a box all lie along one line.
+        for r#box in boxes {
+            for entry in Entry::ALL {
+                let cells = self.cells_with_candidate(r#box, entry);
+                if cells.len() < 2 {
+                    continue;
+                }
+
+                for line in shared_lines(&cells) {
+                    let eliminations = self.eliminations_outside(line, &cells, entry);
+                    if !eliminations.is_empty() {
+                        return Some(Deduction {
+                            technique: Technique::Pointing {
+                                from: r#box,
+                                into: line,
+                            },
+                            action: Action::Eliminate(eliminations),
+                            support: cells,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Claiming: the candidates in a line all lie in one box.
+        for line in lines {
+            for entry in Entry::ALL {
+                let cells = self.cells_with_candidate(line, entry);
+                if cells.len() < 2 {
+                    continue;
+                }
+
+                let first_box = self.variant.box_of(cells[0]);
+                let same_box = cells
+                    .iter()
+                    .all(|&index| self.variant.box_of(index) == first_box);
+                if !same_box {
+                    continue;
+                }
+
+                let r#box = Unit::Box(first_box);
+                let eliminations = self.eliminations_outside(r#box, &cells, entry);
+                if !eliminations.is_empty() {
+                    return Some(Deduction {
+                        technique: Technique::Claiming {
+                            from: line,
+                            into: r#box,
+                        },
+                        action: Action::Eliminate(eliminations),
+                        support: cells,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Collect the eliminations of `entry` from every cell of `unit` other than `keep`.
+    fn eliminations_outside(
+        &self,
+        unit: Unit,
+        keep: &[usize],
+        entry: Entry,
+    ) -> Vec<(usize, Entry)> {
+        self.cells_with_candidate(unit, entry)
+            .into_iter()
+            .filter(|index| !keep.contains(index))
+            .map(|index| (index, entry))
+            .collect()
+    }
+
+    fn naked_subset(&self, size: usize) -> Option<Deduction> {
+        self.variant.units().into_iter().find_map(|unit| {
+            let empty = self.empty_cells(unit);
+            if empty.len() <= size {
+                return None;
+            }
+
+            empty.iter().copied().combinations(size).find_map(|subset| {
+                let union = subset.iter().fold(Candidates::NONE, |union, &index| {
+                    union.union(self.candidates[index])
+                });
+                if union.len() != size {
+                    return None;
+                }
+
+                let eliminations: Vec<_> = empty
+                    .iter()
+                    .filter(|index| !subset.contains(index))
+                    .flat_map(|&index| {
+                        self.candidates[index]
+                            .intersection(union)
+                            .iter()
+                            .map(move |entry| (index, entry))
+                    })
+                    .collect();
+                if eliminations.is_empty() {
+                    return None;
+                }
+
+                Some(Deduction {
+                    technique: Technique::NakedSubset { unit, size },
+                    action: Action::Eliminate(eliminations),
+                    support: subset,
+                })
+            })
+        })
+    }
+
+    fn hidden_subset(&self, size: usize) -> Option<Deduction> {
+        self.variant.units().into_iter().find_map(|unit| {
+            // Only digits which are still missing from the unit can be part of a hidden subset.
+            let missing: Vec<Entry> = Entry::ALL
+                .into_iter()
+                .filter(|&entry| !self.cells_with_candidate(unit, entry).is_empty())
+                .collect();
+            if missing.len() <= size {
+                return None;
+            }
+
+            missing
+                .iter()
+                .copied()
+                .combinations(size)
+                .find_map(|digits| {
+                    let cells: Vec<usize> = digits
+                        .iter()
+                        .flat_map(|&entry| self.cells_with_candidate(unit, entry))
+                        .unique()
+                        .sorted()
+                        .collect();
+                    if cells.len() != size {
+                        return None;
+                    }
+
+                    let digits: Candidates = digits.into_iter().collect();
+                    let eliminations: Vec<_> = cells
+                        .iter()
+                        .flat_map(|&index| {
+                            self.candidates[index]
+                                .difference(digits)
+                                .iter()
+                                .map(move |entry| (index, entry))
+                        })
+                        .collect();
+                    if eliminations.is_empty() {
+                        return None;
+                    }
+
+                    Some(Deduction {
+                        technique: Technique::HiddenSubset { unit, size },
+                        action: Action::Eliminate(eliminations),
+                        support: cells,
+                    })
+                })
+        })
+    }
+
+    fn x_wing(&self) -> Option<Deduction> {
+        // Rows as the base lines and columns as the cover lines, then the other way around.
+        let orientations: [Orientation; 2] = [
+            (Unit::Row, Unit::Column, |index| index % 9),
+            (Unit::Column, Unit::Row, |index| index / 9),
+        ];
+
+        for entry in Entry::ALL {
+            for (base, cover, cover_of) in orientations {
+                // Every base line where the digit has exactly two spots left.
+                let spots: Vec<(usize, [usize; 2])> = (0..9)
+                    .filter_map(|line| {
+                        let [first, second] = self.cells_with_candidate(base(line), entry)[..]
+                        else {
+                            return None;
+                        };
+                        Some((line, [first, second]))
+                    })
+                    .collect();
+
+                for (a, b) in spots.iter().tuple_combinations() {
+                    let covers = a.1.map(cover_of);
+                    if covers != b.1.map(cover_of) {
+                        continue;
+                    }
+
+                    let corners = [a.1[0], a.1[1], b.1[0], b.1[1]];
+                    let eliminations: Vec<_> = covers
+                        .into_iter()
+                        .flat_map(|line| self.eliminations_outside(cover(line), &corners, entry))
+                        .collect();
+                    if !eliminations.is_empty() {
+                        return Some(Deduction {
+                            technique: Technique::XWing {
+                                entry,
+                                lines: [base(a.0), base(b.0)],
+                            },
+                            action: Action::Eliminate(eliminations),
+                            support: corners.to_vec(),
+                        });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// The kind of base lines and cover lines for an X-wing, along with a function which maps a cell
+/// to the number of the cover line it lies in.
+type Orientation = (fn(usize) -> Unit, fn(usize) -> Unit, fn(usize) -> usize);
+
+/// Get the rows and columns which every one of the supplied cells lies in.
+fn shared_lines(cells: &[usize]) -> impl Iterator<Item = Unit> + use<> {
+    let row = cells[0] / 9;
+    let column = cells[0] % 9;
+    let same_row = cells.iter().all(|&index| index / 9 == row);
+    let same_column = cells.iter().all(|&index| index % 9 == column);
+
+    same_row
+        .then_some(Unit::Row(row))
+        .into_iter()
+        .chain(same_column.then_some(Unit::Column(column)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEDIUM: &str = include_str!("../boards/medium-1.txt");
+
+    #[test]
+    fn test_solves_medium_board_by_logic() {
+        let board: Board = MEDIUM.parse().unwrap();
+        let mut grid = Grid::new(&board);
+        while let Some(deduction) = grid.find_deduction() {
+            grid.apply(&deduction);
+        }
+        assert!(grid.is_solved());
+
+        let mut solved = board.clone();
+        grid.write_to(&mut solved);
+        assert!(solved.is_valid());
+    }
+
+    #[test]
+    fn test_naked_pair() {
+        let mut grid = Grid::new(&Board::empty());
+        let pair: Candidates = [Entry::One, Entry::Two].into_iter().collect();
+        for index in [0, 1] {
+            grid.candidates[index] = pair;
+        }
+
+        let deduction = grid.naked_subset(2).unwrap();
+        assert_eq!(deduction.support, vec![0, 1]);
+        let Action::Eliminate(eliminations) = deduction.action else {
+            panic!("a naked pair should eliminate candidates");
+        };
+        assert!(eliminations.contains(&(2, Entry::One)));
+        assert!(eliminations.contains(&(8, Entry::Two)));
+        assert!(!eliminations.contains(&(0, Entry::One)));
+    }
+}
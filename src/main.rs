@@ -2,83 +2,1796 @@
 
 #![warn(missing_docs)]
 
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 use raylib::prelude::*;
 
-use sudoku_solver::board::Board;
-use sudoku_solver::graphics::SolvingStatus;
-use sudoku_solver::solver::Solver;
-use sudoku_solver::ui::Widget;
-
-fn load_board() -> Board {
-    let mut args = std::env::args();
-    let program = args.next().unwrap();
-    let Some(path) = args.next() else {
-        eprintln!("Usage: {program} <board>");
-        std::process::exit(1);
+use rayon::prelude::*;
+
+use clap::Parser;
+
+use sudoku_solver::annealing::Annealing;
+use sudoku_solver::announce::{self, Announcer};
+use sudoku_solver::bench::{self, Strategy};
+use sudoku_solver::board::{self, Board, BoardCache, BoardView, Entry};
+use sudoku_solver::cli::{self, Cli, OutputFormat, Status, StrategyName};
+use sudoku_solver::editor::Editor;
+use sudoku_solver::gamepad::Gamepad;
+use sudoku_solver::generator::{self, Date, DifficultyGoal, Options};
+use sudoku_solver::graphics::{Celebration, HelpOverlay, PerfOverlay, SolvingStatus, StatsPanel};
+use sudoku_solver::hints;
+use sudoku_solver::history::MoveHistory;
+use sudoku_solver::keys::{self, Action};
+use sudoku_solver::locale::{self, Locale, Message};
+use sudoku_solver::play::Game;
+use sudoku_solver::race::Race;
+use sudoku_solver::random::Seed;
+use sudoku_solver::rating;
+use sudoku_solver::search_tree::SearchTree;
+use sudoku_solver::settings::{self, Format, Settings, SettingsScreen};
+use sudoku_solver::sized::{Shape, SizedBoard};
+use sudoku_solver::solve_log;
+use sudoku_solver::solver::{
+    self, SolveState, Solver, StepError, StepMode, StepOutcome, StepPacer,
+};
+use sudoku_solver::sound::{Effect, Sounds};
+use sudoku_solver::tui;
+use sudoku_solver::ui::{self, Button, Direction, InputState, Menu, MenuBar, Size, TabBar, Widget};
+use sudoku_solver::watch::FileWatcher;
+
+/// Work out where everything goes in a window of the supplied size. In order, that is the tab bar,
+/// the board, the original puzzle, the status strip, the stats strip, the start, step, hint, and
+/// reset buttons, the menu bar, and finally the stats panel.
+///
+/// The menu bar and then the tab bar run along the top of the window, and the strips and the row of
+/// buttons are stacked along the bottom. The board is kept square and as big as will fit in the
+/// space between them. With `split_view`, that space is shared with the original puzzle, which goes
+/// on the left. The original puzzle gets no space at all otherwise.
+///
+/// The bars and strips get as much height as `strips` ask for, in the same order as they go down
+/// the window: the menu bar, the tab bar, the status strip, the stats strip, and one of the
+/// buttons.
+///
+/// If the window is wide enough that an open stats panel would fit beside the board without
+/// shrinking it, the panel goes on the right, as wide as it asks to be. Otherwise it gets no space.
+fn layout(
+    width: f32,
+    height: f32,
+    split_view: bool,
+    strips: [&dyn Widget; 5],
+    stats_panel: &StatsPanel,
+) -> [Rectangle; 11] {
+    let window = Rectangle::new(0.0, 0.0, width, height);
+    let [menu_bar, tab_bar, status, stats, button] =
+        strips.map(|strip| Size::of(strip, Direction::Vertical));
+    let [menus, tabs, board, status, stats, buttons] = ui::split(
+        window,
+        Direction::Vertical,
+        [
+            menu_bar,
+            tab_bar,
+            Size::Flexible(1.0),
+            status,
+            stats,
+            button,
+        ],
+    );
+    let [start, step, hint, reset] =
+        ui::split(buttons, Direction::Horizontal, [Size::Flexible(1.0); 4]);
+    let boards = if split_view { 2.0 } else { 1.0 };
+    let spare = board.width - board.height * boards;
+    let stats_width = if spare >= StatsPanel::open_width() {
+        Size::of(stats_panel, Direction::Horizontal)
+    } else {
+        Size::Fixed(0.0)
+    };
+    let [board, stats_panel] = ui::split(
+        board,
+        Direction::Horizontal,
+        [Size::Flexible(1.0), stats_width],
+    );
+    let [original, board] = if split_view {
+        ui::split(board, Direction::Horizontal, [Size::Flexible(1.0); 2])
+    } else {
+        ui::split(
+            board,
+            Direction::Horizontal,
+            [Size::Fixed(0.0), Size::Flexible(1.0)],
+        )
+    };
+    [
+        tabs,
+        ui::square(board),
+        ui::square(original),
+        status,
+        stats,
+        start,
+        step,
+        hint,
+        reset,
+        menus,
+        stats_panel,
+    ]
+}
+
+/// The things that can be picked from the menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Command {
+    OpenDaily,
+    NewPuzzle,
+    Save,
+    Snapshot,
+    Screenshot,
+    CloseTab,
+    Undo,
+    Redo,
+    Reset,
+    StartPause,
+    Step,
+    StepBack,
+    Hint,
+}
+
+/// Build the File, Edit, and Solve menus. Each item names its keyboard shortcut, if it has one, so
+/// that the shortcuts can be picked up from the menus.
+///
+/// The menus are in the current locale, so they have to be built again when it changes.
+fn menus() -> Vec<Menu<Command>> {
+    let menu = |title: Message, items: &[(Message, Option<Action>, Command)]| Menu {
+        title: title.to_string(),
+        items: items
+            .iter()
+            .map(|(label, action, command)| {
+                let shortcut = action.and_then(|action| action.shortcuts().next());
+                match shortcut {
+                    Some(shortcut) => (format!("{label} ({shortcut})"), *command),
+                    None => (label.to_string(), *command),
+                }
+            })
+            .collect(),
+    };
+    vec![
+        menu(
+            Message::File,
+            &[
+                (
+                    Message::OpenDaily,
+                    Some(Action::OpenDaily),
+                    Command::OpenDaily,
+                ),
+                (
+                    Message::NewPuzzle,
+                    Some(Action::NewPuzzle),
+                    Command::NewPuzzle,
+                ),
+                (Message::SaveProgress, None, Command::Save),
+                (
+                    Message::ExportSnapshot,
+                    Some(Action::Snapshot),
+                    Command::Snapshot,
+                ),
+                (
+                    Message::ExportScreenshot,
+                    Some(Action::Screenshot),
+                    Command::Screenshot,
+                ),
+                (Message::CloseTab, Some(Action::CloseTab), Command::CloseTab),
+            ],
+        ),
+        menu(
+            Message::Edit,
+            &[
+                (Message::Undo, Some(Action::Undo), Command::Undo),
+                (Message::Redo, Some(Action::Redo), Command::Redo),
+                (Message::Clear, Some(Action::Reset), Command::Reset),
+            ],
+        ),
+        menu(
+            Message::Solve,
+            &[
+                (
+                    Message::StartPause,
+                    Some(Action::StartPause),
+                    Command::StartPause,
+                ),
+                (Message::Step, Some(Action::Step), Command::Step),
+                (Message::StepBack, Some(Action::StepBack), Command::StepBack),
+                (Message::Hint, Some(Action::Hint), Command::Hint),
+            ],
+        ),
+    ]
+}
+
+/// The text shown on a tab.
+enum TabName {
+    /// The name of the file that the board came from.
+    File(String),
+
+    /// Text in the current locale, for boards which didn't come from a file.
+    Untitled(Message<'static>),
+}
+
+/// A board open in its own tab, along with everything needed to carry on solving it.
+struct Tab {
+    name: TabName,
+
+    /// The file that progress is saved to. Progress is only saved for boards that came from a
+    /// file.
+    save_to: Option<String>,
+    solver: Solver,
+    board: Board,
+
+    /// The selection, pencil marks, and highlights on the board.
+    board_view: BoardView,
+
+    /// The edits made to the board by hand, so that they can be undone.
+    history: MoveHistory,
+    status: SolvingStatus,
+    solved: bool,
+
+    /// This is only set in play mode.
+    game: Option<Game>,
+    celebration: Option<Celebration>,
+
+    /// This is only set in edit mode.
+    editor: Option<Editor>,
+
+    /// This is only set in race mode.
+    race: Option<Race>,
+
+    /// This is only set while the puzzle is being solved by simulated annealing.
+    annealing: Option<Annealing>,
+
+    /// The guesses made by the solver, which are only kept track of once the search tree has
+    /// been shown.
+    tree: Option<SearchTree>,
+
+    /// This is only set if the board is reloaded whenever its file changes.
+    watcher: Option<FileWatcher>,
+}
+
+impl Tab {
+    /// Open a tab to carry on with the supplied solve. The tab is named after the file the board
+    /// came from.
+    fn new(save_to: Option<String>, state: SolveState) -> Tab {
+        Tab {
+            name: match &save_to {
+                Some(path) => TabName::File(file_name(path)),
+                None => TabName::Untitled(Message::PuzzleOfTheDay),
+            },
+            save_to,
+            solver: state.solver,
+            board: state.board,
+            board_view: BoardView::new(),
+            history: MoveHistory::new(),
+            status: SolvingStatus::Stopped,
+            solved: false,
+            game: None,
+            celebration: None,
+            editor: None,
+            race: None,
+            annealing: None,
+            tree: None,
+            watcher: None,
+        }
+    }
+
+    /// Open a tab with an empty board, to make a new puzzle on.
+    fn new_puzzle() -> Tab {
+        let state = SolveState {
+            solver: Solver::new(),
+            board: Board::empty(),
+        };
+        Tab {
+            name: TabName::Untitled(Message::NewPuzzle),
+            editor: Some(Editor::new(&state.board)),
+            ..Tab::new(None, state)
+        }
+    }
+
+    /// Get the text shown on the tab.
+    fn title(&self) -> String {
+        match &self.name {
+            TabName::File(name) => name.clone(),
+            TabName::Untitled(message) => message.to_string(),
+        }
+    }
+
+    /// Save the progress on the board, if it came from a file.
+    fn save(&mut self) {
+        if let Some(path) = &self.save_to {
+            let solved = self.solved;
+            with_state(&mut self.solver, &mut self.board, |state| {
+                save_state(path, state, solved);
+            });
+        }
+    }
+
+    /// Start the board over from the file it came from, if it has changed since the last time.
+    fn reload_if_changed(&mut self, program: &str) {
+        let (Some(path), Some(watcher)) = (&self.save_to, &mut self.watcher) else {
+            return;
+        };
+        if !watcher.changed() {
+            return;
+        }
+
+        match read_board(program, path) {
+            Ok(board) => {
+                let state = SolveState {
+                    solver: Solver::new(),
+                    board,
+                };
+                *self = Tab {
+                    watcher: self.watcher.take(),
+                    ..Tab::new(self.save_to.take(), state)
+                };
+            }
+            Err((_, message)) => eprintln!("{message}"),
+        }
+    }
+
+    /// Close the tab, saving the progress on its board first.
+    fn close(mut self) {
+        self.save();
+    }
+}
+
+/// Get the name of the file at the end of a path.
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Get the path of the file that an in-progress solve of the given board is saved to.
+fn save_path(board_path: &str) -> String {
+    format!("{board_path}.save")
+}
+
+/// Load the board in the file at the supplied path.
+///
+/// If a previous run was closed in the middle of solving this board, the solve is resumed from
+/// where it left off instead of starting over. If the board can't be loaded, the error message is
+/// returned, along with the status to exit with.
+fn open_state(program: &str, path: &str) -> Result<SolveState, (Status, String)> {
+    if let Ok(contents) = std::fs::read_to_string(save_path(path)) {
+        match contents.parse() {
+            Ok(state) => return Ok(state),
+            Err(()) => eprintln!("{program}: {}", Message::CorruptSave(Path::new(path))),
+        }
+    }
+    // Snapshots are solves in progress themselves, so they carry on from where they were taken.
+    if path != "-"
+        && let Ok(contents) = std::fs::read_to_string(path)
+        && let Ok(state) = contents.parse()
+    {
+        return Ok(state);
+    }
+
+    let board = read_board(program, path)?;
+    Ok(SolveState {
+        solver: Solver::new(),
+        board,
+    })
+}
+
+/// Read the whole of the file at the supplied path, or standard input if the path is `-`.
+fn read_input(path: &str) -> std::io::Result<String> {
+    if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+/// Read the whole of the file at the supplied path like [`read_input`]. If it can't be read, the
+/// error message is returned, along with the status to exit with.
+fn read_contents(program: &str, path: &str) -> Result<String, (Status, String)> {
+    read_input(path).map_err(|err| {
+        let error = err.to_string();
+        let message = Message::FailedToRead {
+            path: Path::new(path),
+            error,
+        };
+        (Status::Failure, format!("{program}: {message}"))
+    })
+}
+
+/// Read the board in the file at the supplied path. If the board can't be read, the error message
+/// is returned, along with the status to exit with.
+fn read_board(program: &str, path: &str) -> Result<Board, (Status, String)> {
+    let contents = read_contents(program, path)?;
+    contents.parse().map_err(|()| {
+        let message = match sized_board(&contents) {
+            Some(board) => Message::OnlyNineByNine(board.shape().size()),
+            None => Message::NotABoard(Path::new(path)),
+        };
+        (Status::ParseError, format!("{program}: {message}"))
+    })
+}
+
+/// A board for one of the commands which work on every size of board.
+enum AnyBoard {
+    Nine(Box<Board>),
+    Sized(SizedBoard),
+}
+
+impl AnyBoard {
+    /// Check that the board doesn't break any of its rules.
+    fn is_valid(&self) -> bool {
+        match self {
+            AnyBoard::Nine(board) => board.is_valid(),
+            AnyBoard::Sized(board) => board.is_valid(),
+        }
+    }
+
+    /// Count the solutions of the board, stopping once `limit` have been found.
+    fn count_solutions(&self, limit: usize) -> usize {
+        match self {
+            AnyBoard::Nine(board) => solver::count_solutions(board, limit),
+            AnyBoard::Sized(board) => board.count_solutions(limit),
+        }
+    }
+}
+
+/// Read the board in the file at the supplied path like [`read_valid_board`], except that boards
+/// of other sizes are read too.
+fn read_any_board(program: &str, path: &str) -> AnyBoard {
+    try_read_any_board(program, path).unwrap_or_else(|(status, message)| fail(status, message))
+}
+
+/// Read the board in the file at the supplied path like [`read_any_board`], except that the error
+/// message is returned along with the status to exit with, rather than exiting.
+fn try_read_any_board(program: &str, path: &str) -> Result<AnyBoard, (Status, String)> {
+    let contents = read_contents(program, path)?;
+    let board = match contents.parse() {
+        Ok(board) => AnyBoard::Nine(Box::new(board)),
+        Err(()) => match sized_board(&contents) {
+            Some(board) => AnyBoard::Sized(board),
+            None => {
+                let message = Message::NotABoard(Path::new(path));
+                return Err((Status::ParseError, format!("{program}: {message}")));
+            }
+        },
     };
+    if !board.is_valid() {
+        return Err((Status::Invalid, format!("{program}: {}", Message::Invalid)));
+    }
+    Ok(board)
+}
+
+/// Load the boards named on the command line, each in its own tab.
+fn load_tabs(program: &str, paths: Vec<String>) -> Vec<Tab> {
+    paths
+        .into_iter()
+        .map(|path| match open_state(program, &path) {
+            Ok(state) => Tab::new(Some(path), state),
+            Err((status, message)) => fail(status, message),
+        })
+        .collect()
+}
+
+/// Load the board named on the command line if it is a size other than 9x9.
+fn load_sized(path: &str) -> Option<SizedBoard> {
+    sized_board(&std::fs::read_to_string(path).ok()?)
+}
 
-    match std::fs::read_to_string(&path) {
-        Ok(contents) => contents.parse().unwrap(),
+/// Read a board out of the contents of a puzzle file if it is a size other than 9x9.
+fn sized_board(contents: &str) -> Option<SizedBoard> {
+    let board: SizedBoard = contents.parse().ok()?;
+    (board.shape() != Shape::NINE).then_some(board)
+}
+
+/// Open the window at the supplied size, scaled up to suit the monitor it opens on.
+///
+/// Sizes are in points on macOS and in pixels everywhere else, so only the other platforms need
+/// the size scaling up. Raylib is asked to render at full resolution on macOS instead, or the
+/// board would be blurry on a Retina display.
+fn open_window(width: i32, height: i32, resizable: bool) -> (RaylibHandle, RaylibThread) {
+    let mut builder = raylib::init();
+    builder.size(width, height).title("Sudoku Solver");
+    if resizable {
+        builder.resizable();
+    }
+    if cfg!(target_os = "macos") {
+        builder.highdpi();
+    }
+    let (mut rl, thread) = builder.build();
+    let scale = ui::monitor_scale(&rl);
+    ui::set_scale(scale);
+    if scale != 1.0 {
+        rl.set_window_size(
+            (width as f32 * scale) as i32,
+            (height as f32 * scale) as i32,
+        );
+    }
+    (rl, thread)
+}
+
+/// Stop the main window from being made so small that the digits no longer fit in their cells.
+fn set_min_size(rl: &mut RaylibHandle) {
+    let scale = ui::scale();
+    rl.set_window_min_size((400.0 * scale) as i32, (590.0 * scale) as i32);
+}
+
+/// Show a board of a size other than 9x9.
+///
+/// The step-by-step solver only works on 9x9 boards, so pressing Space solves the whole board in
+/// one go instead.
+fn show_sized(mut board: SizedBoard) {
+    let (mut rl, thread) = open_window(512, 563, false);
+    rl.set_target_fps(60);
+    if let Err(err) = ui::load_font(&mut rl, &thread, ui::FONT_SIZE) {
+        eprintln!("{}", Message::NoFont(err.to_string()));
+    }
+
+    let scale = ui::scale();
+    let mut status = SolvingStatus::Stopped;
+    if !board.is_valid() {
+        status = SolvingStatus::Invalid;
+    }
+    let board_rect = Rectangle::new(0.0, 0.0, 512.0 * scale, 512.0 * scale);
+    let status_height = status.preferred_size().y;
+    let status_rect = Rectangle::new(0.0, 512.0 * scale, 512.0 * scale, status_height);
+
+    while !rl.window_should_close() {
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) && !status.is_finished() {
+            match board.solve() {
+                Some(solution) => {
+                    board = solution;
+                    status = SolvingStatus::Solved;
+                }
+                None => status = SolvingStatus::Unsolvable,
+            }
+        }
+
+        let mut d = rl.begin_drawing(&thread);
+        d.clear_background(Color::WHITE);
+        board.draw(&mut d, board_rect);
+        status.draw(&mut d, status_rect);
+    }
+}
+
+/// Write to a new file in the working directory, numbered so that earlier files with the same
+/// name are never overwritten. The path of the file is returned if it was written.
+///
+/// Failures are reported here, but it is up to the caller to say what was saved.
+fn save_numbered(name: &str, extension: &str, contents: &str) -> Option<String> {
+    let path = (1..)
+        .map(|n| format!("{name}-{n}.{extension}"))
+        .find(|path| !Path::new(path).exists())?;
+
+    match std::fs::write(&path, contents) {
+        Ok(()) => Some(path),
         Err(err) => {
-            eprintln!("{program}: failed to read {path:?} to string: {err}");
-            std::process::exit(1);
+            let error = Some(err.to_string());
+            eprintln!(
+                "{}",
+                Message::FailedToWrite {
+                    path: Path::new(&path),
+                    error
+                }
+            );
+            None
+        }
+    }
+}
+
+/// Write the solve as it is right now to a new file in the working directory, so that interesting
+/// moments in a solve can be kept.
+///
+/// The board is written in the supplied format, along with where the solver is up to and which
+/// digits were given, so opening the snapshot carries on the solve from this moment rather than
+/// treating the solver's digits as clues. Files are numbered so that earlier snapshots are never
+/// overwritten.
+fn save_snapshot(solver: &mut Solver, board: &mut Board, format: Format) {
+    let contents = with_state(solver, board, |state| format.write_state(state));
+    if let Some(path) = save_numbered("snapshot", "txt", &contents) {
+        eprintln!("{}", Message::SavedSnapshot(Path::new(&path)));
+    }
+}
+
+/// Lend the solver and the board to a [`SolveState`] while `f` runs, since the state has to own
+/// them.
+fn with_state<R>(solver: &mut Solver, board: &mut Board, f: impl FnOnce(&SolveState) -> R) -> R {
+    let state = SolveState {
+        solver: std::mem::take(solver),
+        board: std::mem::replace(board, Board::empty()),
+    };
+    let result = f(&state);
+    SolveState {
+        solver: *solver,
+        board: *board,
+    } = state;
+    result
+}
+
+/// Step the solver up to `n` times, like [`Solver::step_n`], animating every digit that it writes
+/// or rubs out along the way. Each step is also described by the announcer and added to the search
+/// tree, if there are any.
+fn step_animated(
+    solver: &mut Solver,
+    board: &mut Board,
+    view: &mut BoardView,
+    n: usize,
+    announcer: Option<&Announcer>,
+    mut tree: Option<&mut SearchTree>,
+) -> Result<Option<StepOutcome>, StepError> {
+    let mut last = None;
+    for _ in 0..n {
+        let outcome = solver.step(board)?;
+        if let Some((index, before)) = outcome.changed_cell() {
+            view.animate_change(board, index, before);
+        }
+        if let Some(deduction) = solver.deduction() {
+            view.annotate(vec![deduction.clone()]);
+        }
+        if let Some(announcer) = announcer {
+            announcer.step(&outcome, board);
+        }
+        if let Some(tree) = tree.as_deref_mut() {
+            tree.record(outcome);
+        }
+        last = Some(outcome);
+        if outcome.is_finished() {
+            break;
+        }
+    }
+    Ok(last)
+}
+
+/// Play a sound effect, if there is any sound.
+fn play_sound(sounds: Option<&Sounds>, effect: Effect) {
+    if let Some(sounds) = sounds {
+        sounds.play(effect);
+    }
+}
+
+/// Get the current time in UTC, written like `2024-05-01-134502`, for naming files.
+fn timestamp() -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let time = seconds % 86_400;
+    let (hours, minutes, seconds) = (time / 3600, time / 60 % 60, time % 60);
+    format!("{}-{hours:02}{minutes:02}{seconds:02}", Date::today())
+}
+
+/// Save the part of the window inside `rect` to a PNG in the working directory, named after the
+/// current time.
+///
+/// This reads back whatever has been drawn so far, so it has to be called at the end of drawing a
+/// frame.
+fn save_screenshot(rl: &RaylibHandle, thread: &RaylibThread, rect: Rectangle) {
+    let mut image = rl.load_image_from_screen(thread);
+    image.crop(rect);
+
+    let path = format!("screenshot-{}.png", timestamp());
+    let path = Path::new(&path);
+    if image.export_image(&path.to_string_lossy()) {
+        eprintln!("{}", Message::SavedScreenshot(path));
+    } else {
+        eprintln!("{}", Message::FailedToWrite { path, error: None });
+    }
+}
+
+/// Write a batch of `count` new puzzles to the file at `path`.
+fn generate_batch(program: &str, count: usize, path: &str, options: &Options) {
+    let file = std::fs::File::create(path).unwrap_or_else(|err| write_failed(program, path, err));
+    let file = std::io::BufWriter::new(file);
+    let result = generator::generate_batch(count, options, file, generate_progress(count));
+    finish_generating(program, count, path, options, result);
+}
+
+/// Write `count` new puzzles to their own files in the directory at `dir`, along with their
+/// solutions if `solutions` is set.
+fn generate_files(program: &str, count: usize, dir: &str, options: &Options, solutions: bool) {
+    let progress = generate_progress(count);
+    let result = generator::generate_files(count, options, Path::new(dir), solutions, progress);
+    finish_generating(program, count, dir, options, result);
+}
+
+/// Report how generating `count` puzzles to the supplied path went, and exit with
+/// [`Status::Failure`] if any of them couldn't be written or missed the difficulty.
+fn finish_generating(
+    program: &str,
+    count: usize,
+    path: &str,
+    options: &Options,
+    result: std::io::Result<usize>,
+) {
+    if log::log_enabled!(log::Level::Info) {
+        eprintln!();
+    }
+    let written = result.unwrap_or_else(|err| write_failed(program, path, err));
+    if written < count
+        && let Some(DifficultyGoal::AtMost(difficulty) | DifficultyGoal::Exactly(difficulty)) =
+            options.difficulty
+    {
+        let message = Message::MissedDifficulty {
+            written,
+            total: count,
+            difficulty,
+        };
+        fail(Status::Failure, format!("{program}: {message}"));
+    }
+}
+
+/// Exit with [`Status::Failure`] because the file or directory at the supplied path couldn't be
+/// written.
+fn write_failed(program: &str, path: &str, err: std::io::Error) -> ! {
+    let path = Path::new(path);
+    let error = Some(err.to_string());
+    fail(
+        Status::Failure,
+        format!("{program}: {}", Message::FailedToWrite { path, error }),
+    );
+}
+
+/// Show how many of the `count` puzzles have been generated so far, unless the user asked for
+/// quiet.
+fn generate_progress(count: usize) -> impl FnMut(usize) {
+    let progress = log::log_enabled!(log::Level::Info);
+    move |done| {
+        if progress {
+            eprint!("\rgenerated {done} of {count} puzzles");
         }
     }
 }
 
+/// Writes log messages to standard error. Ordinary information is written as it is, and every
+/// other message has its level in front of it.
+struct Logger;
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        match record.level() {
+            _ if !self.enabled(record.metadata()) => {}
+            log::Level::Info => eprintln!("{}", record.args()),
+            level => eprintln!("[{level}] {}", record.args()),
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Report an error on standard error, and exit with the supplied status.
+fn fail(status: Status, message: String) -> ! {
+    eprintln!("{message}");
+    std::process::exit(status.code());
+}
+
+/// Exit with [`Status::Timeout`] if the process is still running once `timeout` has passed, so
+/// that a hard puzzle can't hold up a script forever.
+fn start_timeout(program: &str, timeout: Duration) {
+    let program = program.to_string();
+    std::thread::spawn(move || {
+        std::thread::sleep(timeout);
+        fail(
+            Status::Timeout,
+            format!("{program}: {}", Message::TimedOut(timeout)),
+        );
+    });
+}
+
+/// Read the board in the file at the supplied path for one of the commands which work without a
+/// window. If it can't be read, or it breaks the rules, the process exits with an error.
+fn read_valid_board(program: &str, path: &str) -> Board {
+    try_read_valid_board(program, path).unwrap_or_else(|(status, message)| fail(status, message))
+}
+
+/// Read the board in the file at the supplied path like [`read_valid_board`], except that the
+/// error message is returned along with the status to exit with, rather than exiting.
+fn try_read_valid_board(program: &str, path: &str) -> Result<Board, (Status, String)> {
+    let board = read_board(program, path)?;
+    if !board.is_valid() {
+        return Err((Status::Invalid, format!("{program}: {}", Message::Invalid)));
+    }
+    Ok(board)
+}
+
+/// Solve the board in the file at the supplied path with the exact cover solver, and print the
+/// solution in the supplied format.
+///
+/// This never opens a window, so it works on servers and in scripts without a screen. Anything
+/// that goes wrong is reported on standard error, and the process exits with a failure status.
+fn solve_headless(program: &str, path: &str, format: OutputFormat) {
+    if let Err((status, message)) = try_solve(program, path, format) {
+        fail(status, message);
+    }
+}
+
+/// Solve the board in the file at the supplied path like [`solve_headless`], except that the
+/// error message is returned along with the status to exit with, rather than exiting.
+fn try_solve(program: &str, path: &str, format: OutputFormat) -> Result<(), (Status, String)> {
+    let solution = match try_read_any_board(program, path)? {
+        AnyBoard::Nine(board) if log::log_enabled!(log::Level::Debug) => {
+            solve_verbosely(&board).map(|solution| format.write(&solution))
+        }
+        AnyBoard::Nine(board) => solver::solve_fast(&board).map(|solution| format.write(&solution)),
+        AnyBoard::Sized(board) => board.solve().map(|solution| format.write_sized(&solution)),
+    };
+    match solution {
+        Some(solution) => {
+            print!("{solution}");
+            Ok(())
+        }
+        None => Err((
+            Status::Unsolvable,
+            format!("{program}: {}", Message::Unsolvable),
+        )),
+    }
+}
+
+/// Solve a board one step at a time, logging the techniques applied along the way and, at the
+/// trace level, every guess too.
+///
+/// The exact cover solver that [`solver::solve_fast`] uses is much faster, but it finds the
+/// solution in one go with nothing to show for how it got there, so it is only used when nobody
+/// asked to see the steps.
+fn solve_verbosely(board: &Board) -> Option<Board> {
+    let mut board = board.clone();
+    let mut solver = Solver::new();
+    solver.set_mode(StepMode::Logic);
+    let solved = loop {
+        match solver.step(&mut board) {
+            Ok(StepOutcome::Solved) => break true,
+            Ok(StepOutcome::Unsolvable) | Err(StepError::InvalidBoard) => break false,
+            // Deductions are logged by the logic engine as they are applied.
+            Ok(StepOutcome::Deduced { .. }) => {}
+            Ok(outcome) => {
+                if let Some(sentence) = announce::describe_step(&outcome, &board) {
+                    log::trace!("{sentence}");
+                }
+            }
+        }
+    };
+
+    let stats = solver.stats();
+    log::debug!(
+        "step-by-step solve finished after {} steps and {} backtracks in {:.2?}",
+        stats.steps,
+        stats.backtracks,
+        stats.elapsed
+    );
+    solved.then_some(board)
+}
+
+/// Solve the board in the file at the supplied path, and then again every time the file changes,
+/// until the process is interrupted. Errors are reported without giving up, since the next change
+/// might fix them.
+fn watch_solve(program: &str, path: &str, format: OutputFormat) -> ! {
+    if path == "-" {
+        fail(
+            Status::Usage,
+            format!("{program}: {}", Message::CantWatchStdin),
+        );
+    }
+
+    let mut watcher = FileWatcher::new(path);
+    loop {
+        if let Err((_, message)) = try_solve(program, path, format) {
+            eprintln!("{message}");
+        }
+        log::info!("{}", Message::Watching(Path::new(path)));
+        watcher.wait();
+    }
+}
+
+/// Solve a single line of a puzzle collection, or say why it couldn't be solved.
+fn solve_line(line: &str) -> Result<Board, (Status, Message<'static>)> {
+    let board: Board = line
+        .parse()
+        .map_err(|()| (Status::ParseError, Message::NotAPuzzle))?;
+    if !board.is_valid() {
+        return Err((Status::Invalid, Message::Invalid));
+    }
+    solver::solve_fast(&board).ok_or((Status::Unsolvable, Message::Unsolvable))
+}
+
+/// Solve every puzzle in the collection at the supplied path, which holds one puzzle per line.
+///
+/// Each puzzle is printed on its own line, numbered from one, as either its solution or the reason
+/// it couldn't be solved, followed by how long it took. Solutions in a format that takes more than
+/// one line are printed underneath instead. The puzzles are solved on every core at once if
+/// `parallel` is true, but they are still printed in order. If any puzzle couldn't be solved, the
+/// process exits with the status for the first one.
+fn solve_all(program: &str, path: &str, parallel: bool, format: OutputFormat) {
+    let contents = read_input(path).unwrap_or_else(|err| {
+        let error = err.to_string();
+        let message = Message::FailedToRead {
+            path: Path::new(path),
+            error,
+        };
+        fail(Status::Failure, format!("{program}: {message}"))
+    });
+    let puzzles: Vec<&str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let timed = |line: &&str| {
+        let start = Instant::now();
+        (solve_line(line), start.elapsed())
+    };
+    let start = Instant::now();
+    let results: Vec<_> = if parallel {
+        puzzles.par_iter().map(timed).collect()
+    } else {
+        puzzles.iter().map(timed).collect()
+    };
+    let time = start.elapsed();
+
+    for (i, (result, time)) in results.iter().enumerate() {
+        let number = i + 1;
+        match result.as_ref().map(|solution| format.write(solution)) {
+            Ok(written) if written.trim_end().contains('\n') => {
+                print!("{number}: ({time:.2?})\n{written}");
+            }
+            Ok(written) => println!("{number}: {} ({time:.2?})", written.trim_end()),
+            Err((_, message)) => println!("{number}: {message} ({time:.2?})"),
+        }
+    }
+    let solved = results.iter().filter(|(result, _)| result.is_ok()).count();
+    let total = results.len();
+    log::info!(
+        "{}",
+        Message::SolvedCount {
+            solved,
+            total,
+            time
+        }
+    );
+
+    let failure = results.iter().find_map(|(result, _)| result.as_ref().err());
+    if let Some((status, _)) = failure {
+        std::process::exit(status.code());
+    }
+}
+
+/// Print how hard the board in the file at the supplied path is, followed by the techniques which
+/// were needed to solve it, all on one line.
+fn rate_board(program: &str, path: &str) {
+    match rating::rate_in_detail(&read_valid_board(program, path)) {
+        Some(rating) => {
+            let kinds: Vec<String> = rating
+                .kinds()
+                .map(|kind| Message::TechniqueKind(kind).to_string())
+                .collect();
+            println!("{}: {}", rating.difficulty, kinds.join(", "));
+        }
+        None => fail(
+            Status::Unsolvable,
+            format!("{program}: {}", Message::Unsolvable),
+        ),
+    }
+}
+
+/// Check that the board in the file at the supplied path has exactly one solution. Anything else
+/// is reported as an error.
+fn check_board(program: &str, path: &str) {
+    match read_any_board(program, path).count_solutions(2) {
+        0 => fail(
+            Status::Unsolvable,
+            format!("{program}: {}", Message::Unsolvable),
+        ),
+        1 => println!("{}", Message::OneSolution),
+        _ => fail(
+            Status::Failure,
+            format!("{program}: {}", Message::SeveralSolutions),
+        ),
+    }
+}
+
+/// Print the number of solutions to the board in the file at the supplied path. Counting stops
+/// at `limit`, which is printed with a `+` after it, since there could be more.
+fn count_board(program: &str, path: &str, limit: usize) {
+    match read_any_board(program, path).count_solutions(limit) {
+        count if count >= limit => println!("{count}+"),
+        count => println!("{count}"),
+    }
+}
+
+/// Print the next digit that can be filled in on the board in the file at the supplied path, and
+/// then the cells which the reasoning relies on.
+fn hint_board(program: &str, path: &str) {
+    match hints::next_hint(&read_valid_board(program, path)) {
+        Some(hint) => {
+            println!("{}", Message::HintPlacement(&hint));
+            let cells: Vec<String> = hint.cells().into_iter().map(solve_log::cell_name).collect();
+            if !cells.is_empty() {
+                println!("{}", Message::CellsInvolved(cells.join(", ")));
+            }
+        }
+        None => fail(Status::Failure, format!("{program}: {}", Message::NoHint)),
+    }
+}
+
+/// Run the benchmark suite in the file at the supplied path, which holds one puzzle per line, and
+/// print the report as a table or as JSON.
+fn run_bench(program: &str, path: &str, strategy: Strategy, json: bool) {
+    let contents = read_input(path).unwrap_or_else(|err| {
+        let error = err.to_string();
+        let message = Message::FailedToRead {
+            path: Path::new(path),
+            error,
+        };
+        fail(Status::Failure, format!("{program}: {message}"))
+    });
+    let puzzles: Vec<Board> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.parse())
+        .collect::<Result<_, ()>>()
+        .unwrap_or_else(|()| {
+            let message = Message::NotABoard(Path::new(path));
+            fail(Status::ParseError, format!("{program}: {message}"))
+        });
+
+    let report = bench::run_suite(&puzzles, strategy);
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        println!("{report}");
+    }
+}
+
+/// Show the board in the file at the supplied path being solved in the terminal, at the speed and
+/// in the language from the settings.
+fn solve_in_terminal(program: &str, path: &str) {
+    let board = read_board(program, path).unwrap_or_else(|(status, message)| fail(status, message));
+    let settings = Settings::load();
+    locale::set_locale(settings.locale());
+    if let Err(err) = tui::run(board, settings.steps_per_second) {
+        fail(Status::Failure, format!("{program}: {err}"));
+    }
+}
+
+/// Save an in-progress solve so that it can be resumed on the next launch.
+///
+/// If the board has already been solved, there is nothing worth resuming, so any old save file is
+/// removed instead.
+fn save_state(path: &str, state: &SolveState, solved: bool) {
+    let save_path = save_path(path);
+    let result = if solved {
+        match std::fs::remove_file(&save_path) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    } else {
+        std::fs::write(&save_path, state.to_string())
+    };
+
+    if let Err(err) = result {
+        let error = Some(err.to_string());
+        eprintln!(
+            "{}",
+            Message::FailedToWrite {
+                path: Path::new(&save_path),
+                error
+            }
+        );
+    }
+}
+
 fn main() {
+    // The settings aren't loaded yet, so until then the environment says which language to use.
+    locale::set_locale(Locale::from_env());
+    let program = std::env::args().next().unwrap_or_default();
+    let cli = Cli::parse();
+    if log::set_logger(&Logger).is_ok() {
+        log::set_max_level(cli.log_level());
+    }
+    let (paths, watch) = match cli.command {
+        None => (cli.boards, cli.watch),
+        Some(cli::Command::Gui { boards, watch }) => (boards, watch),
+        Some(cli::Command::Solve {
+            board,
+            all,
+            parallel,
+            format,
+            timeout,
+            watch,
+        }) => {
+            if watch {
+                watch_solve(&program, &board, format.unwrap_or(OutputFormat::Grid));
+            }
+            if let Some(timeout) = timeout {
+                start_timeout(&program, timeout);
+            }
+            // A puzzle piped in is usually on its way to another tool, which wants a single line.
+            let single = if board == "-" {
+                OutputFormat::Line
+            } else {
+                OutputFormat::Grid
+            };
+            return if all {
+                solve_all(
+                    &program,
+                    &board,
+                    parallel,
+                    format.unwrap_or(OutputFormat::Line),
+                )
+            } else {
+                solve_headless(&program, &board, format.unwrap_or(single))
+            };
+        }
+        Some(cli::Command::Generate {
+            number,
+            file,
+            count,
+            out,
+            difficulty,
+            solutions,
+            seed,
+        }) => {
+            let count = number.or(count).unwrap_or(1);
+            let options = Options {
+                seed: seed.map(Seed),
+                difficulty: difficulty.map(DifficultyGoal::Exactly),
+                ..Options::default()
+            };
+            return match (file, out) {
+                (_, Some(dir)) => generate_files(&program, count, &dir, &options, solutions),
+                (Some(file), None) => generate_batch(&program, count, &file, &options),
+                (None, None) => unreachable!("clap requires a file or a directory"),
+            };
+        }
+        Some(cli::Command::Rate { board }) => return rate_board(&program, &board),
+        Some(cli::Command::Check { board }) => return check_board(&program, &board),
+        Some(cli::Command::Count { board, limit }) => {
+            return count_board(&program, &board, limit as usize);
+        }
+        Some(cli::Command::Hint { board }) => return hint_board(&program, &board),
+        Some(cli::Command::Tui { board }) => return solve_in_terminal(&program, &board),
+        Some(cli::Command::Bench {
+            suite,
+            strategy,
+            seed,
+            json,
+        }) => {
+            let strategy = match strategy {
+                StrategyName::Backtracking => Strategy::Backtracking,
+                StrategyName::Seeded => Strategy::Seeded(Seed(seed.unwrap_or_default())),
+                StrategyName::Parallel => Strategy::Parallel,
+                StrategyName::Logic => Strategy::Logic,
+            };
+            return run_bench(&program, &suite, strategy, json);
+        }
+    };
+    if let Some(board) = load_sized(&paths[0]) {
+        show_sized(board);
+        return;
+    }
+
     // I'm putting this before the call to raylib::init since if there is an error on the CLI
     // level, I do not want raylib to be initialized at all.
-    let mut board = load_board();
+    let mut tabs = load_tabs(&program, paths);
+    if watch {
+        for tab in &mut tabs {
+            tab.watcher = tab.save_to.as_ref().map(FileWatcher::new);
+        }
+    }
+    let mut current = 0;
 
-    let mut board_rect = Rectangle::new(0.0, 0.0, 512.0, 563.2);
-    let (mut rl, thread) = raylib::init()
-        .size(board_rect.width as i32, board_rect.height as i32)
-        .title("Sudoku Solver")
-        // .resizable()
-        .build();
+    let (mut rl, thread) = open_window(512, 702, true);
+    set_min_size(&mut rl);
 
-    let mut status = SolvingStatus::Stopped;
-    let widget_rects = [
-        Rectangle {
-            x: 0.0,
-            y: 0.0,
-            width: 512.0,
-            height: 512.0,
-        },
-        Rectangle {
-            x: 0.0,
-            y: 512.0,
-            width: 512.0,
-            height: 51.2,
-        },
-    ];
+    let mut settings_screen = SettingsScreen::new(Settings::load());
+    // The shortcuts can't be changed on the settings screen, so they only need setting once.
+    keys::set_keymap(settings_screen.settings().keymap);
+    let mut show_settings = false;
+    // The font is loaded again whenever its size is changed in the settings, and the menus are
+    // built again whenever the language is.
+    let mut font_size = None;
+    let mut language = None;
 
-    let mut solver = Solver::new();
+    // Everything but the sound works without an audio device, so there's no need to give up.
+    let audio = match RaylibAudio::init_audio_device() {
+        Ok(audio) => Some(audio),
+        Err(_) => {
+            eprintln!("{}", Message::NoAudio);
+            None
+        }
+    };
+    let mut sounds = audio.as_ref().and_then(|audio| match Sounds::load(audio) {
+        Ok(sounds) => Some(sounds),
+        Err(err) => {
+            eprintln!("{}", Message::NoSounds(err.to_string()));
+            None
+        }
+    });
 
-    // Set up a board widget and solvingstate widget
+    let mut menu_bar = MenuBar::new(menus());
+    let mut tab_bar = TabBar::new();
+    let mut start_button = Button::new("");
+    let mut step_button = Button::new("");
+    let mut hint_button = Button::new("");
+    let mut reset_button = Button::new("");
+
+    // The view is shared between the tabs, like the settings, so that switching tabs doesn't
+    // change it.
+    let mut split_view = false;
+    let mut show_tree = false;
+    // This is only set while the performance overlay is shown.
+    let mut perf_overlay: Option<PerfOverlay> = None;
+    // This is only set while the list of shortcuts is shown.
+    let mut help_overlay: Option<HelpOverlay> = None;
+    let mut stats_panel = StatsPanel::new();
+    let mut announcer = Announcer::new();
+    // The speed is in steps per second, so the number of steps taken each frame depends on how
+    // long the frames are taking.
+    let mut pacer = StepPacer::new();
+    let mut gamepad = Gamepad::new();
+    let mut board_cache = BoardCache::new();
 
     rl.set_target_fps(120);
 
     while !rl.window_should_close() {
-        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-            status = status.toggled();
+        // The window can be dragged on to a monitor with a different scale factor, and then
+        // everything needs to be drawn at the new size.
+        let scale = ui::monitor_scale(&rl);
+        if scale != ui::scale() {
+            ui::set_scale(scale);
+            set_min_size(&mut rl);
+            font_size = None;
         }
+        // A race needs room for both boards, so it always splits the view.
+        let racing = tabs[current].race.is_some();
+        // The strip for play mode and the others that can take the place of the stats strip are
+        // all the same height, so the stats strip stands in for them.
+        let strips: [&dyn Widget; 5] = [
+            &menu_bar,
+            &tab_bar,
+            &tabs[current].status,
+            &tabs[current].solver.stats(),
+            &start_button,
+        ];
+        let widget_rects = layout(
+            rl.get_screen_width() as f32,
+            rl.get_screen_height() as f32,
+            split_view || racing || show_tree,
+            strips,
+            &stats_panel,
+        );
+        // The original puzzle is at the left of the view, and takes up no space unless the view is
+        // split.
+        let view = Rectangle {
+            x: widget_rects[2].x,
+            width: widget_rects[1].x + widget_rects[1].width - widget_rects[2].x,
+            ..widget_rects[1]
+        };
+        let input = InputState::capture(&mut rl);
+
+        let menu_was_open = menu_bar.is_open();
+        menu_bar.update(&input, widget_rects[9]);
+        let command = menu_bar.clicked();
+        // Clicks on an open menu belong to it, and shouldn't reach the widgets underneath it.
+        let mut input = if menu_was_open {
+            InputState {
+                mouse_pressed: false,
+                mouse_released: false,
+                mouse_down: false,
+                ..input
+            }
+        } else {
+            input
+        };
 
-        if let SolvingStatus::Going = status {
-            solver.step(&mut board);
+        if Action::Settings.pressed(&input) {
+            show_settings = !show_settings;
+            if !show_settings {
+                settings_screen.settings().save();
+            }
+        }
+        if show_settings {
+            settings_screen.update(&input, view);
+        }
+        let settings = *settings_screen.settings();
+        if font_size != Some(settings.font_size) {
+            font_size = Some(settings.font_size);
+            if let Err(err) = ui::load_font(&mut rl, &thread, settings.font_size) {
+                eprintln!("{}", Message::NoFont(err.to_string()));
+            }
+        }
+        let wanted = settings.locale();
+        if language != Some(wanted) {
+            language = Some(wanted);
+            locale::set_locale(wanted);
+            menu_bar = MenuBar::new(menus());
+        }
+        if let Some(sounds) = &mut sounds {
+            sounds.set_muted(!settings.sound);
+        }
+        tab_bar.update(&input, widget_rects[0]);
+        if let Some(clicked) = tab_bar.clicked() {
+            current = clicked;
         }
 
-        let screen_width = rl.get_screen_width();
-        let screen_height = rl.get_screen_height();
-        let smaller = std::cmp::min(screen_width, screen_height);
-        board_rect.width = smaller as f32;
-        board_rect.height = smaller as f32;
+        // Moving on from the last tab wraps around to the first.
+        if Action::NextTab.pressed(&input) {
+            current = (current + 1) % tabs.len();
+        }
+
+        // The last tab can't be closed.
+        let close_tab = Action::CloseTab.pressed(&input) || command == Some(Command::CloseTab);
+        if close_tab && tabs.len() > 1 {
+            tabs.remove(current).close();
+            current = current.min(tabs.len() - 1);
+        }
+
+        if Action::OpenDaily.pressed(&input) || command == Some(Command::OpenDaily) {
+            let state = SolveState {
+                solver: Solver::new(),
+                board: generator::daily(Date::today()).puzzle,
+            };
+            tabs.push(Tab::new(None, state));
+            current = tabs.len() - 1;
+        }
+
+        for tab in &mut tabs {
+            tab.reload_if_changed(&program);
+        }
+
+        // Puzzle files dropped onto the window open in new tabs.
+        if rl.is_file_dropped() {
+            for path in rl.load_dropped_files().paths() {
+                match open_state(&program, path) {
+                    Ok(state) => {
+                        tabs.push(Tab::new(Some(path.to_string()), state));
+                        current = tabs.len() - 1;
+                    }
+                    Err((_, message)) => eprintln!("{message}"),
+                }
+            }
+        }
+
+        if command == Some(Command::Save) {
+            tabs[current].save();
+        }
+
+        if Action::NewPuzzle.pressed(&input) || command == Some(Command::NewPuzzle) {
+            tabs.push(Tab::new_puzzle());
+            current = tabs.len() - 1;
+        }
+
+        tab_bar.set_tabs(tabs.iter().map(Tab::title).collect(), current);
+
+        let Tab {
+            name,
+            save_to,
+            solver,
+            board,
+            board_view,
+            history,
+            status,
+            solved,
+            game,
+            celebration,
+            editor,
+            race,
+            annealing,
+            tree,
+            watcher: _,
+        } = &mut tabs[current];
+
+        // The board in the tab is hidden during a race or annealing, so it shouldn't be edited
+        // either.
+        if !show_settings && race.is_none() && annealing.is_none() {
+            gamepad.update(&mut input, board_view);
+            board_view.update(&input, widget_rects[1]);
+            board_view.edit(board, &input, history);
+        }
+        board_view.set_filled_color(settings.theme.filled_color());
+        if let Some(race) = race.as_mut() {
+            race.set_filled_color(settings.theme.filled_color());
+        }
+        if let Some(annealing) = annealing.as_mut() {
+            annealing.set_filled_color(settings.theme.filled_color());
+        }
+        start_button.set_tooltip(&keys::tooltip(Message::TipStart, &[Action::StartPause]));
+        step_button.set_tooltip(&keys::tooltip(Message::TipStep, &[Action::Step]));
+        hint_button.set_tooltip(&keys::tooltip(Message::TipHint, &[Action::Hint]));
+        reset_button.set_tooltip(&keys::tooltip(Message::TipReset, &[Action::Reset]));
+        start_button.update(&input, widget_rects[5]);
+        step_button.update(&input, widget_rects[6]);
+        hint_button.update(&input, widget_rects[7]);
+        reset_button.update(&input, widget_rects[8]);
+        stats_panel.update(&input, widget_rects[10]);
+
+        match command {
+            Some(Command::Undo) => _ = board_view.undo(board, history),
+            Some(Command::Redo) => _ = board_view.redo(board, history),
+            _ => {}
+        }
+
+        if Action::StartPause.pressed(&input)
+            || start_button.clicked()
+            || command == Some(Command::StartPause)
+        {
+            *status = status.toggled();
+        }
+
+        if Action::Reset.pressed(&input)
+            || reset_button.clicked()
+            || command == Some(Command::Reset)
+        {
+            board.reset();
+            board_view.reset();
+            history.clear();
+            *solver = Solver::new();
+            *solved = false;
+            *status = SolvingStatus::Stopped;
+            if game.is_some() {
+                *game = Some(Game::new(board));
+            }
+            if race.is_some() {
+                *race = Some(Race::new(board));
+            }
+            if annealing.is_some() {
+                *annealing = Annealing::new(board, Seed::from_time());
+            }
+            if tree.is_some() {
+                *tree = Some(SearchTree::new());
+            }
+        }
+
+        if Action::Race.pressed(&input) && editor.is_none() && game.is_none() && annealing.is_none()
+        {
+            *race = match race {
+                Some(_) => None,
+                None => Some(Race::new(board)),
+            };
+            // The race doesn't touch the board in the tab, so leaving it picks up where it was.
+            *status = if race.is_none() && *solved {
+                SolvingStatus::Solved
+            } else {
+                SolvingStatus::Stopped
+            };
+        }
+
+        if Action::Anneal.pressed(&input) && editor.is_none() && game.is_none() && race.is_none() {
+            // Boards with extra rules can't be annealed, so nothing happens for those.
+            *annealing = match annealing {
+                Some(_) => None,
+                None => Annealing::new(board, Seed::from_time()),
+            };
+            *status = if annealing.is_none() && *solved {
+                SolvingStatus::Solved
+            } else {
+                SolvingStatus::Stopped
+            };
+        }
+
+        if Action::Play.pressed(&input) && editor.is_none() && race.is_none() && annealing.is_none()
+        {
+            *game = match game {
+                Some(_) => None,
+                None => Some(Game::new(board)),
+            };
+            *status = SolvingStatus::Stopped;
+        }
+
+        if let Some(game) = game.as_mut() {
+            // In play mode, plain digits go into the selected cell. Shift and a digit still make
+            // pencil marks, which the board deals with itself.
+            if let Some(index) = board_view.selected()
+                && !input.shift
+                && !input.ctrl
+            {
+                for (key, entry) in board::DIGIT_KEYS.into_iter().zip(Entry::ALL) {
+                    if input.key_pressed(key) {
+                        game.enter(board, board_view, history, index, Some(entry));
+                        if game.is_over() && !*solved {
+                            *solved = true;
+                            *status = SolvingStatus::Solved;
+                            *celebration = Some(Celebration::new());
+                            play_sound(sounds.as_ref(), Effect::Victory);
+                        }
+                    }
+                }
+                if Action::Erase.pressed(&input) {
+                    game.enter(board, board_view, history, index, None);
+                }
+            }
+
+            // Giving up lets the solver take it from here.
+            if Action::GiveUp.pressed(&input) && !game.is_over() {
+                game.give_up(board, board_view, history);
+                *solver = Solver::new();
+                *solved = false;
+                *status = SolvingStatus::Going;
+            }
+
+            if Action::Check.pressed(&input) && !game.is_over() {
+                let wrong = game.check(board);
+                board_view.flag(wrong);
+            }
+        }
+
+        if let Some(puzzle) = editor.as_mut() {
+            // In edit mode, plain digits go into the selected cell as clues.
+            if let Some(index) = board_view.selected()
+                && !input.shift
+                && !input.ctrl
+            {
+                for (key, entry) in board::DIGIT_KEYS.into_iter().zip(Entry::ALL) {
+                    if input.key_pressed(key) {
+                        puzzle.enter(board, board_view, history, index, Some(entry));
+                    }
+                }
+                if Action::Erase.pressed(&input) {
+                    puzzle.enter(board, board_view, history, index, None);
+                }
+            }
+            puzzle.check(board);
+        }
+
+        // Locking in the clues saves the new puzzle, and hands it over to be solved.
+        if Action::Lock.pressed(&input)
+            && let Some(puzzle) = editor.take()
+        {
+            if puzzle.clone().lock(board, board_view, history) {
+                *solver = Solver::new();
+                *status = SolvingStatus::Stopped;
+                *save_to = save_numbered("puzzle", "txt", &board.to_string());
+                if let Some(path) = save_to {
+                    eprintln!("{}", Message::SavedPuzzle(Path::new(path)));
+                    *name = TabName::File(file_name(path));
+                }
+            } else {
+                *editor = Some(puzzle);
+            }
+        }
+
+        if Action::Announce.pressed(&input) {
+            let announce = &mut settings_screen.settings_mut().announce;
+            *announce = !*announce;
+            let state = if *announce { Message::On } else { Message::Off };
+            println!("{}: {state}", Message::Announcements);
+        }
 
+        if Action::Mute.pressed(&input) {
+            settings_screen.settings_mut().sound = !settings.sound;
+        }
+
+        if Action::SplitView.pressed(&input) {
+            split_view = !split_view;
+        }
+
+        // The tree only grows from here on, since earlier steps weren't kept track of.
+        if Action::SearchTree.pressed(&input) {
+            show_tree = !show_tree;
+        }
+        if show_tree && tree.is_none() {
+            *tree = Some(SearchTree::new());
+        }
+
+        if Action::Heatmap.pressed(&input) {
+            board_view.set_heatmap(!board_view.heatmap());
+        }
+
+        if (Action::Hint.pressed(&input) || hint_button.clicked() || command == Some(Command::Hint))
+            && editor.is_none()
+            && race.is_none()
+            && annealing.is_none()
+        {
+            // There's no hint to give while the solver is busy changing the board.
+            if let SolvingStatus::Stopped | SolvingStatus::Paused | SolvingStatus::Hint(_) = status
+                && let Some(hint) = hints::next_hint(board)
+            {
+                *status = SolvingStatus::Hint(hint.technique);
+                board_view.annotate(hint.deductions());
+                board_view.set_hint(Some(hint));
+            }
+        }
+
+        if Action::SwitchMode.pressed(&input) {
+            solver.set_mode(match solver.mode() {
+                StepMode::Search => StepMode::Logic,
+                StepMode::Logic => StepMode::Search,
+            });
+        }
+
+        // Holding the key down scrubs backwards through the search.
+        let step_back =
+            Action::StepBack.repeated(&rl, &input) || command == Some(Command::StepBack);
+        // Racers can't step back, and the tab's own solver is set aside during a race.
+        if step_back && race.is_none() && annealing.is_none() && solver.step_back(board) {
+            if let Some(tree) = tree {
+                tree.step_back();
+            }
+            *solved = false;
+            *status = SolvingStatus::Paused;
+            if settings.announce {
+                announcer.say("Took back the last step.");
+            }
+        }
+
+        if Action::Snapshot.pressed(&input) || command == Some(Command::Snapshot) {
+            // Shift picks a single line, or the grid if lines are the usual format.
+            let format = match (input.shift, settings.format) {
+                (false, format) => format,
+                (true, Format::Line) => Format::Grid,
+                (true, Format::Grid) => Format::Line,
+            };
+            save_snapshot(solver, board, format);
+        }
+
+        let steps_per_second = &mut settings_screen.settings_mut().steps_per_second;
+        if Action::Faster.pressed(&input) {
+            *steps_per_second =
+                std::cmp::min(*steps_per_second * 2, settings::MAX_STEPS_PER_SECOND);
+        }
+        if Action::Slower.pressed(&input) {
+            *steps_per_second =
+                std::cmp::max(*steps_per_second / 2, settings::MIN_STEPS_PER_SECOND);
+        }
+        // Time spent stopped doesn't count towards the next steps.
+        if !matches!(status, SolvingStatus::Going) {
+            pacer.reset();
+        }
+
+        // While paused, the solver can be walked forwards one step at a time.
+        let step_forward = Action::Step.repeated(&rl, &input)
+            || step_button.clicked()
+            || command == Some(Command::Step);
+        let steps = match status {
+            // The solver stays out of the way while a puzzle is being made.
+            _ if editor.is_some() => 0,
+            SolvingStatus::Going => pacer.steps(
+                Duration::from_secs_f32(rl.get_frame_time()),
+                *steps_per_second,
+                settings::MAX_STEPS_PER_FRAME,
+            ),
+            SolvingStatus::Stopped | SolvingStatus::Paused | SolvingStatus::Hint(_)
+                if step_forward && (!*solved || race.is_some() || annealing.is_some()) =>
+            {
+                1
+            }
+            _ => 0,
+        };
+
+        let before = solver.stats();
+        if steps > 0 {
+            board_view.set_hint(None);
+            // Taking a step by hand starts the solve, but leaves it paused.
+            if let SolvingStatus::Stopped | SolvingStatus::Hint(_) = status {
+                *status = SolvingStatus::Paused;
+            }
+            let announcing = settings.announce.then_some(&announcer);
+            let result = match (race.as_mut(), annealing.as_mut()) {
+                (Some(race), _) => race.step(steps),
+                (None, Some(annealing)) => Ok(annealing.step(steps).then_some(StepOutcome::Solved)),
+                (None, None) => {
+                    step_animated(solver, board, board_view, steps, announcing, tree.as_mut())
+                }
+            };
+            match result {
+                Ok(Some(StepOutcome::Solved)) => {
+                    // Winning a race or annealing doesn't solve the board in the tab.
+                    *solved = race.is_none() && annealing.is_none();
+                    *status = SolvingStatus::Solved;
+                    *celebration = Some(Celebration::new());
+                    play_sound(sounds.as_ref(), Effect::Victory);
+                }
+                Ok(Some(StepOutcome::Unsolvable)) => *status = SolvingStatus::Unsolvable,
+                Err(StepError::InvalidBoard) => *status = SolvingStatus::Invalid,
+                Ok(_) => {}
+            }
+
+            // Only one sound is played per frame, however many steps were taken.
+            let after = solver.stats();
+            if after.backtracks > before.backtracks {
+                play_sound(sounds.as_ref(), Effect::Backtrack);
+            } else if after.steps > before.steps {
+                play_sound(sounds.as_ref(), Effect::Place);
+            }
+        }
+        if settings.announce {
+            announcer.status(status);
+        }
+
+        if Action::Help.pressed(&input) {
+            help_overlay = match help_overlay {
+                Some(_) => None,
+                None => Some(HelpOverlay::new()),
+            };
+        }
+        if Action::Performance.pressed(&input) {
+            perf_overlay = match perf_overlay {
+                Some(_) => None,
+                None => Some(PerfOverlay::new()),
+            };
+        }
+        let screenshot = Action::Screenshot.pressed(&input) || command == Some(Command::Screenshot);
+
+        let render_start = Instant::now();
+        board_cache.update(&mut rl, &thread, board, widget_rects[1]);
         let mut d = rl.begin_drawing(&thread);
-        d.clear_background(Color::WHITE);
+        d.clear_background(settings.theme.background());
+
+        tab_bar.draw(&mut d, widget_rects[0]);
+        if show_settings {
+            settings_screen.draw(&mut d, view);
+        } else if let Some(race) = race {
+            race.draw_boards(&mut d, widget_rects[2], widget_rects[1]);
+        } else if let Some(annealing) = annealing {
+            annealing.draw_board(&mut d, widget_rects[1]);
+        } else {
+            board_view.draw_cached(&mut d, board, widget_rects[1], &board_cache);
+            gamepad.draw(&mut d, board_view, widget_rects[1]);
+            // The tree takes the place of the original puzzle when both are asked for.
+            if show_tree && let Some(tree) = tree {
+                tree.draw(&mut d, widget_rects[2]);
+            } else if split_view {
+                BoardView::new().draw(&mut d, &board.original(), widget_rects[2]);
+            }
+        }
+        if let Some(animation) = *celebration {
+            animation.draw(&mut d, widget_rects[1]);
+            if animation.is_finished() {
+                *celebration = None;
+            }
+        }
+        if let Some(overlay) = &help_overlay {
+            overlay.draw(&mut d, view);
+        }
+        status.draw(&mut d, widget_rects[3]);
+        match (editor, game, race, annealing) {
+            (Some(editor), _, _, _) => editor.draw(&mut d, widget_rects[4]),
+            (None, Some(game), _, _) => game.draw(&mut d, widget_rects[4]),
+            (None, None, Some(race), _) => race.draw(&mut d, widget_rects[4]),
+            (None, None, None, Some(annealing)) => annealing.draw(&mut d, widget_rects[4]),
+            (None, None, None, None) => solver.stats().draw(&mut d, widget_rects[4]),
+        }
+        start_button.set_label(
+            &match status {
+                SolvingStatus::Going => Message::Pause,
+                _ => Message::Start,
+            }
+            .to_string(),
+        );
+        step_button.set_label(&Message::Step.to_string());
+        hint_button.set_label(&Message::Hint.to_string());
+        reset_button.set_label(&Message::Reset.to_string());
+        start_button.draw(&mut d, widget_rects[5]);
+        step_button.draw(&mut d, widget_rects[6]);
+        hint_button.draw(&mut d, widget_rects[7]);
+        reset_button.draw(&mut d, widget_rects[8]);
+        stats_panel.set_stats(solver.stats());
+        stats_panel.draw(&mut d, widget_rects[10]);
+        // The open menu hangs down over everything else, so it has to be drawn last.
+        menu_bar.draw(&mut d, widget_rects[9]);
+
+        if screenshot {
+            save_screenshot(&d, &thread, view);
+        }
+
+        // This goes on last, so that it stays out of screenshots.
+        if let Some(overlay) = &mut perf_overlay {
+            let after = solver.stats();
+            overlay.record_frame(
+                after.steps.saturating_sub(before.steps),
+                after.validating.saturating_sub(before.validating),
+                render_start.elapsed(),
+                solver.attempt_stack_bytes(),
+            );
+            overlay.draw(&mut d, view);
+        }
+        ui::draw_tooltip(&mut d, &input);
+    }
 
-        board.draw(&mut d, widget_rects[0]);
-        status.draw(&mut d, widget_rects[1]);
+    for tab in tabs {
+        tab.close();
     }
+    settings_screen.settings().save();
 }
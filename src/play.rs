@@ -0,0 +1,271 @@
+//! Playing a puzzle by hand.
+//!
+//! In play mode the user fills in the board themselves instead of watching the solver. Every digit
+//! they enter is checked, wrong ones are counted as mistakes, and a clock runs until the board is
+//! finished. Giving up hands the board back to the solver.
+
+use std::time::{Duration, Instant};
+
+use raylib::prelude::*;
+
+use crate::board::{Board, BoardView, Entry};
+use crate::history::{Change, Move, MoveHistory};
+use crate::locale::Message;
+use crate::solver;
+use crate::ui::{self, Widget};
+
+/// How long the result of checking progress is shown for.
+const CHECK_LENGTH: Duration = Duration::from_millis(2000);
+
+/// A game of Sudoku being played by hand.
+#[derive(Debug, Clone)]
+pub struct Game {
+    /// The solution that entries are checked against. Puzzles without exactly one solution can
+    /// only be checked against the rules, so this is [`None`] for them.
+    solution: Option<Board>,
+
+    mistakes: u32,
+    started: Instant,
+
+    /// How long the game took, once it is over.
+    finished: Option<Duration>,
+    gave_up: bool,
+
+    /// How many digits were wrong the last time progress was checked, and when that was.
+    checked: Option<(usize, Instant)>,
+}
+
+impl Game {
+    /// Start playing the supplied board.
+    pub fn new(board: &Board) -> Game {
+        let solution = (solver::count_solutions(board, 2) == 1)
+            .then(|| solver::solve_copy(board))
+            .flatten();
+        Game {
+            solution,
+            mistakes: 0,
+            started: Instant::now(),
+            finished: None,
+            gave_up: false,
+            checked: None,
+        }
+    }
+
+    /// Get the number of wrong digits entered so far.
+    pub const fn mistakes(&self) -> u32 {
+        self.mistakes
+    }
+
+    /// Get how long the game has been going, or how long it took if it is over.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// Check whether the game is over, either because the board was filled in or because the
+    /// player gave up.
+    pub const fn is_over(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// Write a digit into a cell, or rub one out with [`None`], checking it as it goes in.
+    ///
+    /// Givens can't be changed, and nothing can be changed once the game is over. A digit which
+    /// doesn't match the solution (or breaks the rules, if there isn't a unique solution) still
+    /// goes in, but counts as a mistake. The change can be undone like any other edit. Returns
+    /// whether the digit was right; rubbing out always counts as right.
+    pub fn enter(
+        &mut self,
+        board: &mut Board,
+        view: &mut BoardView,
+        history: &mut MoveHistory,
+        index: usize,
+        entry: Option<Entry>,
+    ) -> bool {
+        if self.is_over() || board.is_given(index) {
+            return true;
+        }
+
+        let before = board.get_cell_index(index);
+        let change = Change::Entry {
+            before,
+            after: entry,
+        };
+        view.make_move(board, history, Move { index, change });
+
+        let correct = match (&self.solution, entry) {
+            (_, None) => true,
+            (Some(solution), Some(entry)) => solution.get_cell_index(index) == Some(entry),
+            (None, Some(_)) => board.is_valid(),
+        };
+        if !correct {
+            self.mistakes += 1;
+        }
+        if board.first_unfilled_index().is_none() && board.is_valid() {
+            self.finished = Some(self.started.elapsed());
+        }
+        correct
+    }
+
+    /// Check the player's progress so far, returning the cells which hold wrong digits.
+    ///
+    /// Only digits which have been entered are checked, so nothing is given away about the empty
+    /// cells. Without a unique solution, the only digits known to be wrong are the ones which
+    /// break the rules. Checking doesn't count as a mistake.
+    pub fn check(&mut self, board: &Board) -> Vec<usize> {
+        let wrong = match &self.solution {
+            Some(solution) => board.diff(solution),
+            None => {
+                let mut wrong: Vec<_> = board
+                    .conflicts()
+                    .into_iter()
+                    .flat_map(|(first, second)| [first, second])
+                    .filter(|&index| !board.is_given(index))
+                    .collect();
+                wrong.sort_unstable();
+                wrong.dedup();
+                wrong
+            }
+        };
+        self.checked = Some((wrong.len(), Instant::now()));
+        wrong
+    }
+
+    /// Stop the game and take out anything that stands in the way of the solver finishing the
+    /// board.
+    ///
+    /// If the solution is known, only the wrong digits are taken out. Otherwise, every digit
+    /// the player entered goes, since there's no telling which of them are wrong. The digits are
+    /// taken out as a single change, which can be undone like any other.
+    pub fn give_up(&mut self, board: &mut Board, view: &mut BoardView, history: &mut MoveHistory) {
+        if self.is_over() {
+            return;
+        }
+
+        let moves = (0..81)
+            .filter(|&index| !board.is_given(index))
+            .filter_map(|index| {
+                let before = board.get_cell_index(index)?;
+                let wrong = match &self.solution {
+                    Some(solution) => solution.get_cell_index(index) != Some(before),
+                    None => true,
+                };
+                let change = Change::Entry {
+                    before: Some(before),
+                    after: None,
+                };
+                wrong.then_some(Move { index, change })
+            })
+            .collect();
+        view.make_moves(board, history, moves);
+        self.finished = Some(self.started.elapsed());
+        self.gave_up = true;
+    }
+}
+
+impl Widget for Game {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let seconds = self.elapsed().as_secs();
+        let mistakes = self.mistakes;
+        let checked = self
+            .checked
+            .filter(|(_, started)| started.elapsed() < CHECK_LENGTH);
+        let text = match (self.finished, self.gave_up, checked) {
+            (None, _, Some((wrong, _))) => Message::Checked { wrong },
+            (None, _, None) => Message::Playing { seconds, mistakes },
+            (Some(_), false, _) => Message::SolvedIn { seconds, mistakes },
+            (Some(_), true, _) => Message::GaveUp { seconds },
+        }
+        .to_string();
+
+        d.draw_rectangle_rec(rect, Color::SKYBLUE);
+        ui::draw_text_centered(d, &text, rect, ui::font_size() * 0.6, Color::BLACK);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mistakes() {
+        let mut board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let solution = solver::solve_copy(&board).unwrap();
+        let mut view = BoardView::new();
+        let mut history = MoveHistory::new();
+        let mut game = Game::new(&board);
+
+        let index = board.first_unfilled_index().unwrap();
+        let right = solution.get_cell_index(index).unwrap();
+        let wrong = Entry::ALL
+            .into_iter()
+            .find(|&entry| entry != right)
+            .unwrap();
+        assert!(!game.enter(&mut board, &mut view, &mut history, index, Some(wrong)));
+        assert!(game.enter(&mut board, &mut view, &mut history, index, Some(right)));
+        assert_eq!(game.mistakes(), 1);
+
+        for index in 0..81 {
+            if board.get_cell_index(index).is_none() {
+                game.enter(
+                    &mut board,
+                    &mut view,
+                    &mut history,
+                    index,
+                    solution.get_cell_index(index),
+                );
+            }
+        }
+        assert!(game.is_over());
+        assert_eq!(game.mistakes(), 1);
+    }
+
+    #[test]
+    fn test_check() {
+        let mut board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let solution = solver::solve_copy(&board).unwrap();
+        let mut view = BoardView::new();
+        let mut history = MoveHistory::new();
+        let mut game = Game::new(&board);
+        assert!(game.check(&board).is_empty());
+
+        let index = board.first_unfilled_index().unwrap();
+        let right = solution.get_cell_index(index).unwrap();
+        let wrong = Entry::ALL
+            .into_iter()
+            .find(|&entry| entry != right)
+            .unwrap();
+        game.enter(&mut board, &mut view, &mut history, index, Some(wrong));
+        assert_eq!(game.check(&board), vec![index]);
+        assert_eq!(game.mistakes(), 1);
+    }
+
+    #[test]
+    fn test_give_up() {
+        let mut board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let solution = solver::solve_copy(&board).unwrap();
+        let mut view = BoardView::new();
+        let mut history = MoveHistory::new();
+        let mut game = Game::new(&board);
+
+        let mut empty = (0..81).filter(|&index| board.get_cell_index(index).is_none());
+        let (first, second) = (empty.next().unwrap(), empty.next().unwrap());
+        let right = solution.get_cell_index(first);
+        let wrong = Entry::ALL
+            .into_iter()
+            .find(|&entry| Some(entry) != solution.get_cell_index(second));
+        game.enter(&mut board, &mut view, &mut history, first, right);
+        game.enter(&mut board, &mut view, &mut history, second, wrong);
+
+        game.give_up(&mut board, &mut view, &mut history);
+        assert!(game.is_over());
+        assert_eq!(board.get_cell_index(first), right);
+        assert_eq!(board.get_cell_index(second), None);
+
+        // Undoing puts back the wrong digit, and then the one before it.
+        assert!(view.undo(&mut board, &mut history));
+        assert_eq!(board.get_cell_index(second), wrong);
+        assert!(view.undo(&mut board, &mut history));
+        assert_eq!(board.get_cell_index(second), None);
+        assert_eq!(board.get_cell_index(first), right);
+    }
+}
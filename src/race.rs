@@ -0,0 +1,158 @@
+//! Racing two ways of solving against each other.
+//!
+//! In race mode, the puzzle is solved twice side by side: once by guessing and backtracking, and
+//! once with logic. Both solvers take the same number of steps every frame, so whichever needs
+//! fewer steps finishes first, and the difference between the two approaches can be watched as it
+//! happens. The race works on copies of the puzzle, and leaves the board in the tab alone.
+
+use raylib::prelude::*;
+
+use crate::board::{Board, BoardView};
+use crate::locale::Message;
+use crate::solver::{Solver, StepError, StepMode, StepOutcome};
+use crate::ui::{self, Direction, Size, Widget};
+
+/// One of the solvers in a race, along with its copy of the puzzle.
+#[derive(Debug)]
+struct Racer {
+    solver: Solver,
+    board: Board,
+    view: BoardView,
+
+    /// How the solve ended, once it has.
+    outcome: Option<StepOutcome>,
+}
+
+impl Racer {
+    /// Start solving a copy of the supplied puzzle in the supplied way.
+    fn new(board: &Board, mode: StepMode) -> Racer {
+        let mut solver = Solver::new();
+        solver.set_mode(mode);
+        Racer {
+            solver,
+            board: board.clone(),
+            view: BoardView::new(),
+            outcome: None,
+        }
+    }
+
+    /// Step the solver up to `n` times, animating every digit that it writes or rubs out.
+    fn step(&mut self, n: usize) -> Result<(), StepError> {
+        if self.outcome.is_some() {
+            return Ok(());
+        }
+        for _ in 0..n {
+            let outcome = self.solver.step(&mut self.board)?;
+            if let Some((index, before)) = outcome.changed_cell() {
+                self.view.animate_change(&self.board, index, before);
+            }
+            if let Some(deduction) = self.solver.deduction() {
+                self.view.annotate(vec![deduction.clone()]);
+            }
+            if outcome.is_finished() {
+                self.outcome = Some(outcome);
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A race between guessing and logic on the same puzzle.
+#[derive(Debug)]
+pub struct Race {
+    /// The guessing solver, which is drawn on the left, and the logical one on the right.
+    racers: [Racer; 2],
+}
+
+impl Race {
+    /// Line up both solvers at the start of the supplied puzzle. Anything filled in after the
+    /// givens is left out, so that both start from scratch.
+    pub fn new(board: &Board) -> Race {
+        let puzzle = board.original();
+        Race {
+            racers: [
+                Racer::new(&puzzle, StepMode::Search),
+                Racer::new(&puzzle, StepMode::Logic),
+            ],
+        }
+    }
+
+    /// Step each solver that hasn't finished yet up to `n` times.
+    ///
+    /// Once both solvers have finished, the way they finished is returned. They are solving the
+    /// same puzzle, so they always finish the same way.
+    ///
+    /// # Errors
+    ///
+    /// If the puzzle breaks the rules, [`StepError::InvalidBoard`] is returned.
+    pub fn step(&mut self, n: usize) -> Result<Option<StepOutcome>, StepError> {
+        for racer in &mut self.racers {
+            racer.step(n)?;
+        }
+        let [first, second] = &self.racers;
+        Ok(first.outcome.filter(|_| second.outcome.is_some()))
+    }
+
+    /// Set the color that filled in digits are drawn in on both boards.
+    pub fn set_filled_color(&mut self, color: Color) {
+        for racer in &mut self.racers {
+            racer.view.set_filled_color(color);
+        }
+    }
+
+    /// Draw the guessing solver's board in `left`, and the logical solver's board in `right`.
+    pub fn draw_boards(&self, d: &mut RaylibDrawHandle, left: Rectangle, right: Rectangle) {
+        let [first, second] = &self.racers;
+        first.view.draw(d, &first.board, left);
+        second.view.draw(d, &second.board, right);
+    }
+
+    /// Check whether a racer finished before the other one did. Both win if they took the same
+    /// number of steps.
+    fn won(&self, racer: usize) -> bool {
+        let steps = |racer: &Racer| racer.solver.stats().steps;
+        let (this, other) = (&self.racers[racer], &self.racers[1 - racer]);
+        this.outcome.is_some() && (other.outcome.is_none() || steps(this) <= steps(other))
+    }
+}
+
+impl Widget for Race {
+    /// Draw how far along each solver is, under its board.
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let lanes = ui::split(rect, Direction::Horizontal, [Size::Flexible(1.0); 2]);
+        d.draw_rectangle_rec(rect, Color::LIGHTGRAY);
+        for (index, (racer, lane)) in self.racers.iter().zip(lanes).enumerate() {
+            let text = Message::RaceLane {
+                mode: racer.solver.mode(),
+                steps: racer.solver.stats().steps,
+                won: self.won(index),
+            }
+            .to_string();
+            ui::draw_text_centered(d, &text, lane, ui::font_size() * 0.6, Color::BLACK);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_race() {
+        let board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+        let solution = crate::solver::solve_copy(&board).unwrap();
+        let mut race = Race::new(&board);
+        let mut outcome = None;
+        while outcome.is_none() {
+            outcome = race.step(100).unwrap();
+        }
+        assert_eq!(outcome, Some(StepOutcome::Solved));
+        for racer in &race.racers {
+            assert_eq!(racer.board.to_line(), solution.to_line());
+        }
+        // Logic never has to take anything back, so it gets there first on a puzzle like this.
+        assert!(race.won(1));
+        assert!(!race.won(0));
+    }
+}
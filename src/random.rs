@@ -0,0 +1,116 @@
+//! Reproducible pseudo-random numbers.
+//!
+//! Everything random in this crate is driven by an explicit [`Seed`], so that the same puzzle and
+//! the same seed always lead to exactly the same result. The generator is a tiny SplitMix64, which
+//! is plenty for shuffling digits around and saves pulling in a whole crate for it.
+
+/// The starting point for a sequence of pseudo-random numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    /// Make up a seed from the current time.
+    ///
+    /// This is for when the caller doesn't care which seed is used. The seed should still be
+    /// reported somewhere so that the run can be reproduced later.
+    pub fn from_time() -> Seed {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        Seed(nanos as u64)
+    }
+}
+
+impl From<u64> for Seed {
+    fn from(value: u64) -> Self {
+        Seed(value)
+    }
+}
+
+impl std::fmt::Display for Seed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for Seed {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Seed).map_err(|_| ())
+    }
+}
+
+/// A pseudo-random number generator.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator which starts from the supplied seed.
+    pub const fn new(seed: Seed) -> Rng {
+        Rng { state: seed.0 }
+    }
+
+    /// Produce the next number in the sequence.
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Produce a number from `0` up to but not including `bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "cannot pick a number below zero");
+        // Taking the high half of the product keeps the bias negligible for small bounds, without
+        // the cost of a division.
+        ((self.next_u64() as u128 * bound as u128) >> 64) as usize
+    }
+
+    /// Produce a number between `0.0` (inclusive) and `1.0` (exclusive).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Put the items of a slice into a random order.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// Pick a random item from a slice, or [`None`] if it is empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.below(items.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(Seed(42));
+        let mut b = Rng::new(Seed(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        let mut items: Vec<usize> = (0..20).collect();
+        a.shuffle(&mut items);
+        let mut sorted = items.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+    }
+}
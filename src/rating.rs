@@ -0,0 +1,150 @@
+//! Difficulty ratings for puzzles.
+//!
+//! A puzzle is rated by solving it with the deduction engine from [`crate::logic`] and looking at
+//! the hardest technique that was needed along the way. Puzzles which cannot be finished by logic
+//! alone need guessing, which puts them in the hardest category.
+
+use crate::board::Board;
+use crate::logic::{Grid, Technique};
+use crate::solver;
+
+/// How hard a puzzle is for a person to solve.
+///
+/// The variants are ordered from easiest to hardest, so difficulties can be compared with the
+/// usual comparison operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, clap::ValueEnum)]
+pub enum Difficulty {
+    /// Only singles are needed.
+    Easy,
+
+    /// Locked candidates or pairs are needed.
+    Medium,
+
+    /// Triples, quads, or X-wings are needed.
+    Hard,
+
+    /// Logic alone is not enough, so the solver has to guess.
+    Expert,
+}
+
+impl Difficulty {
+    /// Get the difficulty of a single technique.
+    pub const fn of(technique: &Technique) -> Difficulty {
+        match technique {
+            Technique::NakedSingle | Technique::HiddenSingle(_) => Difficulty::Easy,
+            Technique::Pointing { .. } | Technique::Claiming { .. } => Difficulty::Medium,
+            Technique::NakedSubset { size: 2, .. } | Technique::HiddenSubset { size: 2, .. } => {
+                Difficulty::Medium
+            }
+            Technique::NakedSubset { .. }
+            | Technique::HiddenSubset { .. }
+            | Technique::XWing { .. } => Difficulty::Hard,
+        }
+    }
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Check whether a puzzle can be finished by logic, using only techniques up to the supplied
+/// difficulty.
+///
+/// A puzzle which passes this check has exactly one solution, since logic never rules out the
+/// real solution.
+pub fn solvable_within(board: &Board, max: Difficulty) -> bool {
+    if !board.is_valid() {
+        return false;
+    }
+
+    let mut grid = Grid::new(board);
+    while let Some(deduction) =
+        grid.find_deduction_where(|technique| Difficulty::of(technique) <= max)
+    {
+        grid.apply(&deduction);
+    }
+    grid.is_solved()
+}
+
+/// The difficulty of a puzzle, along with the techniques which were needed to solve it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rating {
+    /// How hard the puzzle is.
+    pub difficulty: Difficulty,
+
+    /// How many times each kind of technique was used, indexed by [`Technique::kind`].
+    pub uses: [usize; Technique::KINDS],
+}
+
+impl Rating {
+    /// Get the kinds of technique which were used at least once, from easiest to hardest.
+    pub fn kinds(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..Technique::KINDS).filter(|&kind| self.uses[kind] > 0)
+    }
+}
+
+/// Rate the difficulty of a puzzle.
+///
+/// If the puzzle has no solution at all, there is nothing to rate, so [`None`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use sudoku_solver::board::Board;
+/// use sudoku_solver::rating::{self, Difficulty};
+///
+/// let board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+/// assert_eq!(rating::rate(&board), Some(Difficulty::Easy));
+/// ```
+pub fn rate(board: &Board) -> Option<Difficulty> {
+    rate_in_detail(board).map(|rating| rating.difficulty)
+}
+
+/// Rate the difficulty of a puzzle like [`rate`], and also count the techniques that were used.
+///
+/// If logic gets stuck, the techniques counted are the ones used before the solver had to guess.
+///
+/// # Examples
+///
+/// ```
+/// use sudoku_solver::board::Board;
+/// use sudoku_solver::rating;
+///
+/// let board: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+/// let rating = rating::rate_in_detail(&board).unwrap();
+/// assert!(rating.kinds().all(|kind| kind <= 1));
+/// ```
+pub fn rate_in_detail(board: &Board) -> Option<Rating> {
+    if solver::is_dead_end(board) {
+        return None;
+    }
+
+    let mut grid = Grid::new(board);
+    let mut rating = Rating {
+        difficulty: Difficulty::Easy,
+        uses: [0; Technique::KINDS],
+    };
+    while let Some(deduction) = grid.find_deduction() {
+        rating.difficulty = rating.difficulty.max(Difficulty::of(&deduction.technique));
+        rating.uses[deduction.technique.kind()] += 1;
+        grid.apply(&deduction);
+    }
+
+    if grid.is_solved() {
+        return Some(rating);
+    }
+
+    // Logic got stuck, so the only question left is whether guessing can finish the job.
+    let mut stuck = board.clone();
+    grid.write_to(&mut stuck);
+    rating.difficulty = Difficulty::Expert;
+    solver::solve(&mut stuck).then_some(rating)
+}
@@ -0,0 +1,302 @@
+//! A picture of the search as a tree of guesses.
+//!
+//! Every digit that the search writes into a cell is a branch of the tree, one level deeper than
+//! the cell before it. Branches are cut off when the digit breaks the rules, or given up on once
+//! everything below them has failed, and the search moves on to the next digit in the same cell.
+//! The tree is built up from the [`StepOutcome`] of each step, and drawn with the branches in the
+//! order they were tried from left to right, which shows how the search has gone as a whole in a
+//! way the board can't.
+
+use std::collections::VecDeque;
+
+use raylib::prelude::*;
+
+use crate::locale::Message;
+use crate::solver::{HISTORY_LIMIT, StepOutcome};
+use crate::ui::{self, Direction, Size, Widget};
+
+/// The color of branches which are still being searched.
+const OPEN_COLOR: Color = Color::new(40, 90, 200, 255);
+
+/// The color of branches which broke the rules as soon as they were tried.
+const PRUNED_COLOR: Color = Color::new(220, 60, 60, 255);
+
+/// The color of branches which were given up on after everything below them failed.
+const EXHAUSTED_COLOR: Color = Color::new(150, 150, 150, 255);
+
+/// The color of the branches leading to the solution.
+const SOLUTION_COLOR: Color = Color::new(0, 160, 80, 255);
+
+/// What became of a branch of the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Fate {
+    /// The branch is still being searched.
+    Open,
+
+    /// The digit broke the rules, so nothing below it was searched.
+    Pruned,
+
+    /// Everything below the branch failed.
+    Exhausted,
+
+    /// The branch leads to the solution.
+    Solution,
+}
+
+/// A single guess in the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+    /// The guess that this one was made under, or [`None`] for the first guess.
+    parent: Option<usize>,
+
+    /// How many guesses deep this one is, starting from one.
+    depth: usize,
+    fate: Fate,
+}
+
+/// How to take back a single step of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Undo {
+    /// The step didn't change the tree.
+    Nothing,
+
+    /// The step made a new guess, and the deepest guess before it was this deep.
+    Branched { max_depth: usize },
+
+    /// The step failed the guess at the top of the path. If the next digit in the same cell was
+    /// guessed as well, the deepest guess before that is given.
+    Failed { top: usize, branched: Option<usize> },
+
+    /// The step marked the guesses on the path as leading to the solution.
+    Solved,
+}
+
+/// The tree of guesses made by a search so far.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SearchTree {
+    /// How to take back each of the last steps, as far back as the solver can step.
+    undo: VecDeque<Undo>,
+
+    /// Every guess, in the order they were made.
+    nodes: Vec<Node>,
+
+    /// The guesses that the search is currently under, from the first one down.
+    path: Vec<usize>,
+    max_depth: usize,
+}
+
+impl SearchTree {
+    /// Create an empty tree, for a search which hasn't started.
+    pub fn new() -> SearchTree {
+        SearchTree::default()
+    }
+
+    /// Get the number of guesses made so far, including the ones which were taken back.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check whether no guesses have been made yet.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Add a step of the search to the tree.
+    ///
+    /// Deductions aren't guesses, so they aren't part of the tree. A tree which is started in the
+    /// middle of a search only knows about the guesses made since then, so steps which would
+    /// take back a guess that it never saw are ignored.
+    pub fn record(&mut self, outcome: StepOutcome) {
+        let undo = self.apply(outcome);
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(undo);
+    }
+
+    /// Take back the last step, for when the solver steps back. Nothing happens if there is no
+    /// step to take back.
+    pub fn step_back(&mut self) {
+        match self.undo.pop_back() {
+            None | Some(Undo::Nothing) => {}
+            Some(Undo::Branched { max_depth }) => self.unbranch(max_depth),
+            Some(Undo::Failed { top, branched }) => {
+                if let Some(max_depth) = branched {
+                    self.unbranch(max_depth);
+                }
+                self.nodes[top].fate = Fate::Open;
+                self.path.push(top);
+            }
+            Some(Undo::Solved) => {
+                for &node in &self.path {
+                    self.nodes[node].fate = Fate::Open;
+                }
+            }
+        }
+    }
+
+    /// Change the tree to match a step, and say how to change it back.
+    fn apply(&mut self, outcome: StepOutcome) -> Undo {
+        match outcome {
+            StepOutcome::Placed { .. } => Undo::Branched {
+                max_depth: self.branch(self.path.last().copied()),
+            },
+            StepOutcome::Rejected { entry, .. } | StepOutcome::Backtracked { entry, .. } => {
+                let Some(top) = self.path.pop() else {
+                    return Undo::Nothing;
+                };
+                self.nodes[top].fate = match outcome {
+                    StepOutcome::Rejected { .. } => Fate::Pruned,
+                    _ => Fate::Exhausted,
+                };
+                // The next digit in the same cell is a new branch alongside the one that failed.
+                let branched = entry
+                    .successor()
+                    .map(|_| self.branch(self.nodes[top].parent));
+                Undo::Failed { top, branched }
+            }
+            StepOutcome::Solved => {
+                for &node in &self.path {
+                    self.nodes[node].fate = Fate::Solution;
+                }
+                Undo::Solved
+            }
+            StepOutcome::Deduced { .. } | StepOutcome::Unsolvable => Undo::Nothing,
+        }
+    }
+
+    /// Take back the last guess, which must be at the top of the path, and put back the depth of
+    /// the deepest guess from before it was made.
+    fn unbranch(&mut self, max_depth: usize) {
+        self.nodes.pop();
+        self.path.pop();
+        self.max_depth = max_depth;
+    }
+
+    /// Make a new guess under the supplied one, and carry on searching under it. The depth of the
+    /// deepest guess from before is returned, so that the guess can be taken back.
+    fn branch(&mut self, parent: Option<usize>) -> usize {
+        let depth = parent.map_or(1, |parent| self.nodes[parent].depth + 1);
+        self.nodes.push(Node {
+            parent,
+            depth,
+            fate: Fate::Open,
+        });
+        self.path.push(self.nodes.len() - 1);
+        let max_depth = self.max_depth.max(depth);
+        std::mem::replace(&mut self.max_depth, max_depth)
+    }
+}
+
+impl Widget for SearchTree {
+    /// Draw the tree, with the first guess at the top and the guesses in the order they were
+    /// made from left to right.
+    ///
+    /// There are usually far more guesses than there is room for, so only as many are drawn as
+    /// there are pixels across, along with the guesses the search is currently under.
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let scale = ui::scale();
+        let [title, plot] = ui::split(
+            rect,
+            Direction::Vertical,
+            [Size::Fixed(ui::font_size() * 0.8), Size::Flexible(1.0)],
+        );
+        d.draw_rectangle_rec(rect, Color::new(245, 245, 245, 255));
+        let text = Message::SearchTree {
+            guesses: self.len(),
+            depth: self.path.len(),
+        }
+        .to_string();
+        ui::draw_text_centered(d, &text, title, ui::font_size() * 0.5, Color::BLACK);
+        if self.is_empty() {
+            return;
+        }
+
+        let padding = 8.0 * scale;
+        let plot = Rectangle {
+            x: plot.x + padding,
+            y: plot.y + padding,
+            width: plot.width - padding * 2.0,
+            height: plot.height - padding * 2.0,
+        };
+        let point = |node: Option<usize>, column: usize| -> Vector2 {
+            let depth = node.map_or(0, |node| self.nodes[node].depth);
+            Vector2::new(
+                plot.x + (column as f32 + 0.5) / self.len() as f32 * plot.width,
+                plot.y + depth as f32 / self.max_depth as f32 * plot.height,
+            )
+        };
+        let thickness = scale;
+        let draw_node = |d: &mut RaylibDrawHandle, index: usize| {
+            let node = self.nodes[index];
+            let color = match node.fate {
+                Fate::Open => OPEN_COLOR,
+                Fate::Pruned => PRUNED_COLOR,
+                Fate::Exhausted => EXHAUSTED_COLOR,
+                Fate::Solution => SOLUTION_COLOR,
+            };
+            // The first guesses hang from a point above them, since there is no guess to join.
+            let from = match node.parent {
+                Some(parent) => point(Some(parent), parent),
+                None => point(None, index),
+            };
+            d.draw_line_ex(from, point(Some(index), index), thickness, color);
+        };
+
+        let stride = (self.len() / plot.width.max(1.0) as usize).max(1);
+        for index in (0..self.len()).step_by(stride) {
+            draw_node(d, index);
+        }
+        // The current branch goes on top, so that it can always be picked out.
+        for &index in &self.path {
+            draw_node(d, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Entry;
+
+    #[test]
+    fn test_search_tree() {
+        let mut tree = SearchTree::new();
+        tree.record(StepOutcome::Placed {
+            index: 0,
+            entry: Entry::One,
+        });
+        tree.record(StepOutcome::Placed {
+            index: 1,
+            entry: Entry::One,
+        });
+        tree.record(StepOutcome::Rejected {
+            index: 1,
+            entry: Entry::One,
+        });
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.path, vec![0, 2]);
+        assert_eq!(tree.nodes[1].fate, Fate::Pruned);
+        assert_eq!(tree.nodes[2].parent, Some(0));
+
+        // A 9 which fails has no digit after it, so the search goes back up a level.
+        tree.record(StepOutcome::Rejected {
+            index: 1,
+            entry: Entry::Nine,
+        });
+        assert_eq!(tree.path, vec![0]);
+        tree.record(StepOutcome::Solved);
+        assert_eq!(tree.nodes[0].fate, Fate::Solution);
+
+        tree.step_back();
+        tree.step_back();
+        assert_eq!(tree.path, vec![0, 2]);
+        assert_eq!(tree.nodes[0].fate, Fate::Open);
+
+        // Taking back every step leaves the tree as it started.
+        for _ in 0..4 {
+            tree.step_back();
+        }
+        assert_eq!(tree, SearchTree::new());
+    }
+}
@@ -0,0 +1,553 @@
+//! Settings which are kept from one run to the next.
+//!
+//! The settings are saved as a small TOML file in the platform's config directory. Only the flat
+//! `key = value` part of TOML is needed for them, so that is all that is read and written here.
+//! Keys which aren't recognized are skipped, so that a config file written by a newer version
+//! still loads.
+
+use std::path::PathBuf;
+
+use raylib::prelude::*;
+
+use crate::keys::{self, Action, Keymap, Shortcut};
+use crate::locale::{Locale, Message};
+use crate::solver::SolveState;
+use crate::ui::{self, Button, Direction, InputState, Size, Widget};
+
+/// The most solver steps that will be taken in a single frame. Anything much higher than this and
+/// the frame rate starts to suffer on slower machines.
+pub const MAX_STEPS_PER_FRAME: usize = 4096;
+
+/// The slowest and fastest speeds that the solver can be set to, in steps per second. The fastest
+/// speed stays under [`MAX_STEPS_PER_FRAME`] down to about 16 frames per second, so it is the same
+/// at any reasonable frame rate.
+pub const MIN_STEPS_PER_SECOND: usize = 1;
+/// See [`MIN_STEPS_PER_SECOND`].
+pub const MAX_STEPS_PER_SECOND: usize = 1 << 16;
+
+/// The font sizes that can be picked, from smallest to biggest.
+const FONT_SIZES: [f32; 3] = [24.0, ui::FONT_SIZE, 40.0];
+
+/// The colors that the window is drawn in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Theme {
+    /// Dark digits on a white background.
+    #[default]
+    Light,
+
+    /// The same board, surrounded by a dark background.
+    Dark,
+}
+
+impl Theme {
+    /// Get the color that the window is cleared to behind everything else.
+    pub const fn background(&self) -> Color {
+        match self {
+            Self::Light => Color::WHITE,
+            Self::Dark => Color::new(32, 32, 32, 255),
+        }
+    }
+
+    /// Get the color that digits filled in by the solver or the player are drawn in.
+    pub const fn filled_color(&self) -> Color {
+        match self {
+            Self::Light => Color::BLUE,
+            Self::Dark => Color::DARKBLUE,
+        }
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = ();
+
+    /// Parse a theme from its name, `light` or `dark`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Light => write!(f, "light"),
+            Self::Dark => write!(f, "dark"),
+        }
+    }
+}
+
+/// The way a board is written to a file. Both formats can be opened again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Format {
+    /// Nine rows of nine digits, grouped into boxes, as written by
+    /// [`Board`](crate::board::Board)'s `Display` implementation.
+    #[default]
+    Grid,
+
+    /// All 81 cells on one line, as written by [`Board::to_line`](crate::board::Board::to_line).
+    Line,
+}
+
+impl Format {
+    /// Write a solve in this format, so that it can be picked up again where it left off. See
+    /// [`SolveState`] for how the solver's progress is kept apart from the puzzle.
+    pub fn write_state(self, state: &SolveState) -> String {
+        match self {
+            Self::Grid => state.to_string(),
+            Self::Line => state.to_line(),
+        }
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = ();
+
+    /// Parse a format from its name, `grid` or `line`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "grid" => Ok(Self::Grid),
+            "line" => Ok(Self::Line),
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Grid => write!(f, "grid"),
+            Self::Line => write!(f, "line"),
+        }
+    }
+}
+
+/// Everything that can be changed on the settings screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// The colors that the window is drawn in.
+    pub theme: Theme,
+
+    /// The number of solver steps taken every second while solving.
+    pub steps_per_second: usize,
+
+    /// Whether sound effects are played.
+    pub sound: bool,
+
+    /// The size that text is drawn at.
+    pub font_size: f32,
+
+    /// The format that snapshots of the board are written in.
+    pub format: Format,
+
+    /// Whether the solve is described in words on standard output, as set out in
+    /// [`announce`](crate::announce).
+    pub announce: bool,
+
+    /// The language that text is shown in, or [`None`] to follow the environment until one is
+    /// picked. See [`Settings::locale`].
+    pub locale: Option<Locale>,
+
+    /// The keyboard shortcuts for everything that has one.
+    pub keymap: Keymap,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            theme: Theme::Light,
+            steps_per_second: 128,
+            sound: true,
+            font_size: ui::FONT_SIZE,
+            format: Format::Grid,
+            announce: false,
+            locale: None,
+            keymap: Keymap::DEFAULT,
+        }
+    }
+}
+
+impl Settings {
+    /// Get the language that text should be shown in: the one picked in the settings, or the one
+    /// that the environment asks for if none has been picked.
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_else(Locale::from_env)
+    }
+
+    /// Get the path of the config file, if there is anywhere to put it.
+    ///
+    /// This is `sudoku-solver/settings.toml` inside `%APPDATA%` on Windows,
+    /// `~/Library/Application Support` on macOS, and `$XDG_CONFIG_HOME` (or `~/.config`)
+    /// everywhere else.
+    pub fn path() -> Option<PathBuf> {
+        let env = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        let home = || env("HOME").map(PathBuf::from);
+        let config_dir = if cfg!(windows) {
+            env("APPDATA").map(PathBuf::from)
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library/Application Support"))
+        } else {
+            env("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| home().map(|home| home.join(".config")))
+        };
+        Some(config_dir?.join("sudoku-solver").join("settings.toml"))
+    }
+
+    /// Load the settings from the config file.
+    ///
+    /// If there is no config file yet, the defaults are used. A config file which can't be read
+    /// is reported and then ignored, and the defaults are used instead. The same goes for
+    /// keyboard shortcuts which would do two things at once: the conflict is reported, and the
+    /// usual shortcuts are used.
+    pub fn load() -> Settings {
+        let Some(path) = Settings::path() else {
+            return Settings::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                let mut settings: Settings = contents.parse().unwrap_or_else(|()| {
+                    eprintln!("{}", Message::CorruptSettings(&path));
+                    Settings::default()
+                });
+                if let Some((shortcut, first, second)) = settings.keymap.conflict() {
+                    let path = &path;
+                    eprintln!(
+                        "{}",
+                        Message::KeyConflict {
+                            path,
+                            shortcut,
+                            first,
+                            second
+                        }
+                    );
+                    settings.keymap = Keymap::DEFAULT;
+                }
+                settings
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Settings::default(),
+            Err(err) => {
+                let error = err.to_string();
+                eprintln!("{}", Message::FailedToRead { path: &path, error });
+                Settings::default()
+            }
+        }
+    }
+
+    /// Save the settings to the config file, creating its directory if needed.
+    pub fn save(&self) {
+        let Some(path) = Settings::path() else {
+            return;
+        };
+        let result = match path.parent() {
+            Some(dir) => std::fs::create_dir_all(dir),
+            None => Ok(()),
+        };
+        if let Err(err) = result.and_then(|()| std::fs::write(&path, self.to_string())) {
+            let error = Some(err.to_string());
+            eprintln!("{}", Message::FailedToWrite { path: &path, error });
+        }
+    }
+}
+
+impl std::str::FromStr for Settings {
+    type Err = ();
+
+    /// Read settings from the flat `key = value` subset of TOML, with strings in double quotes.
+    ///
+    /// Blank lines and comments are skipped, and so are keys which aren't recognized. Settings
+    /// which are missing keep their default values. If a line isn't a `key = value` pair, or a
+    /// value doesn't make sense for its key, `Err(())` is returned.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut settings = Settings::default();
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or(())?;
+            let value = value.trim();
+            let string = || {
+                value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .ok_or(())
+            };
+            match key.trim() {
+                "theme" => settings.theme = string()?.parse()?,
+                "steps_per_second" => {
+                    let steps: usize = value.parse().map_err(|_| ())?;
+                    settings.steps_per_second =
+                        steps.clamp(MIN_STEPS_PER_SECOND, MAX_STEPS_PER_SECOND);
+                }
+                "sound" => settings.sound = value.parse().map_err(|_| ())?,
+                "font_size" => {
+                    let size: f32 = value.parse().map_err(|_| ())?;
+                    if !size.is_finite() || size <= 0.0 {
+                        return Err(());
+                    }
+                    settings.font_size = size;
+                }
+                "format" => settings.format = string()?.parse()?,
+                "announce" => settings.announce = value.parse().map_err(|_| ())?,
+                "language" => settings.locale = Some(string()?.parse()?),
+                key => {
+                    // Shortcuts are written like `keys.step = "RIGHT, S"`, and an empty string
+                    // takes every shortcut away from an action.
+                    let Some(Ok(action)) = key.strip_prefix("keys.").map(str::parse::<Action>)
+                    else {
+                        continue;
+                    };
+                    let shortcuts = string()?
+                        .split(',')
+                        .filter(|shortcut| !shortcut.trim().is_empty())
+                        .map(str::parse)
+                        .collect::<Result<Vec<Shortcut>, ()>>()?;
+                    if !settings.keymap.bind(action, &shortcuts) {
+                        return Err(());
+                    }
+                }
+            }
+        }
+        Ok(settings)
+    }
+}
+
+impl std::fmt::Display for Settings {
+    /// Write the settings as TOML, one `key = value` pair per line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "theme = \"{}\"", self.theme)?;
+        writeln!(f, "steps_per_second = {}", self.steps_per_second)?;
+        writeln!(f, "sound = {}", self.sound)?;
+        // Debug formatting always keeps the decimal point, so that TOML reads the size as a float.
+        writeln!(f, "font_size = {:?}", self.font_size)?;
+        writeln!(f, "format = \"{}\"", self.format)?;
+        writeln!(f, "announce = {}", self.announce)?;
+        if let Some(locale) = self.locale {
+            writeln!(f, "language = \"{locale}\"")?;
+        }
+        for action in Action::ALL {
+            let shortcuts = self.keymap.to_config(action);
+            writeln!(f, "keys.{} = \"{shortcuts}\"", action.name())?;
+        }
+        Ok(())
+    }
+}
+
+/// A screen for changing the [`Settings`], with one button per setting. Clicking on a button
+/// moves its setting on to the next value.
+#[derive(Debug, Clone)]
+pub struct SettingsScreen {
+    settings: Settings,
+    buttons: [Button; 7],
+}
+
+impl SettingsScreen {
+    /// Create a screen for changing the supplied settings.
+    pub fn new(settings: Settings) -> SettingsScreen {
+        let mut screen = SettingsScreen {
+            settings,
+            buttons: std::array::from_fn(|_| Button::new("")),
+        };
+        screen.update_labels();
+        screen
+    }
+
+    /// Get the settings as they are now.
+    pub const fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Change the settings from outside the screen, such as with a keyboard shortcut.
+    pub fn settings_mut(&mut self) -> &mut Settings {
+        &mut self.settings
+    }
+
+    /// Get the name of each setting, in the same order as the buttons.
+    const fn names() -> [Message<'static>; 7] {
+        [
+            Message::Theme,
+            Message::Speed,
+            Message::Sound,
+            Message::FontSize,
+            Message::Snapshots,
+            Message::Announcements,
+            Message::Language,
+        ]
+    }
+
+    /// Get what each button does, and the shortcuts which do the same, in the same order as the
+    /// buttons.
+    fn tooltips() -> [String; 7] {
+        [
+            keys::tooltip(Message::TipTheme, &[]),
+            keys::tooltip(Message::TipSpeed, &[Action::Faster, Action::Slower]),
+            keys::tooltip(Message::TipSound, &[Action::Mute]),
+            keys::tooltip(Message::TipFontSize, &[]),
+            keys::tooltip(Message::TipSnapshots, &[Action::Snapshot]),
+            keys::tooltip(Message::TipAnnouncements, &[Action::Announce]),
+            keys::tooltip(Message::TipLanguage, &[]),
+        ]
+    }
+
+    /// Write the current value of each setting on its button, and what the button does in its
+    /// tooltip.
+    fn update_labels(&mut self) {
+        let settings = &self.settings;
+        let on_off = |on| if on { Message::On } else { Message::Off };
+        let labels = [
+            match settings.theme {
+                Theme::Light => Message::Light,
+                Theme::Dark => Message::Dark,
+            }
+            .to_string(),
+            Message::StepsPerSecond(settings.steps_per_second).to_string(),
+            on_off(settings.sound).to_string(),
+            settings.font_size.to_string(),
+            match settings.format {
+                Format::Grid => Message::Grid,
+                Format::Line => Message::Line,
+            }
+            .to_string(),
+            on_off(settings.announce).to_string(),
+            settings.locale().name().to_string(),
+        ];
+        for ((button, label), tooltip) in self.buttons.iter_mut().zip(labels).zip(Self::tooltips())
+        {
+            button.set_label(&label);
+            button.set_tooltip(&tooltip);
+        }
+    }
+
+    /// Split the screen into a row for each setting, with its name on the left and its button on
+    /// the right.
+    fn rows(rect: Rectangle) -> [[Rectangle; 2]; 7] {
+        let rows = ui::split(rect, Direction::Vertical, [Size::Flexible(1.0); 7]);
+        let padding = 8.0 * ui::scale();
+        rows.map(|row| {
+            let padded = Rectangle {
+                x: row.x + padding,
+                y: row.y + padding,
+                width: row.width - padding * 2.0,
+                height: row.height - padding * 2.0,
+            };
+            ui::split(padded, Direction::Horizontal, [Size::Flexible(1.0); 2])
+        })
+    }
+}
+
+impl Widget for SettingsScreen {
+    fn update(&mut self, input: &InputState, rect: Rectangle) {
+        for (button, [_, button_rect]) in self.buttons.iter_mut().zip(Self::rows(rect)) {
+            button.update(input, button_rect);
+        }
+
+        let settings = &mut self.settings;
+        let clicked = self.buttons.each_ref().map(Button::clicked);
+        if clicked[0] {
+            settings.theme = match settings.theme {
+                Theme::Light => Theme::Dark,
+                Theme::Dark => Theme::Light,
+            };
+        }
+        if clicked[1] {
+            // Going past the fastest speed wraps back around to the slowest.
+            settings.steps_per_second = match settings.steps_per_second * 2 {
+                steps if steps > MAX_STEPS_PER_SECOND => MIN_STEPS_PER_SECOND,
+                steps => steps,
+            };
+        }
+        if clicked[2] {
+            settings.sound = !settings.sound;
+        }
+        if clicked[3] {
+            settings.font_size = FONT_SIZES
+                .into_iter()
+                .find(|&size| size > settings.font_size)
+                .unwrap_or(FONT_SIZES[0]);
+        }
+        if clicked[4] {
+            settings.format = match settings.format {
+                Format::Grid => Format::Line,
+                Format::Line => Format::Grid,
+            };
+        }
+        if clicked[5] {
+            settings.announce = !settings.announce;
+        }
+        if clicked[6] {
+            let current = settings.locale();
+            let current = Locale::ALL.iter().position(|&locale| locale == current);
+            settings.locale = Some(Locale::ALL[(current.unwrap() + 1) % Locale::ALL.len()]);
+        }
+        self.update_labels();
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        d.draw_rectangle_rec(rect, Color::RAYWHITE);
+        for ((name, button), [name_rect, button_rect]) in Self::names()
+            .into_iter()
+            .zip(&self.buttons)
+            .zip(Self::rows(rect))
+        {
+            let name = name.to_string();
+            ui::draw_text_centered(d, &name, name_rect, ui::font_size() * 0.6, Color::BLACK);
+            button.draw(d, button_rect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let settings = Settings {
+            theme: Theme::Dark,
+            steps_per_second: 64,
+            sound: false,
+            font_size: 40.0,
+            format: Format::Line,
+            announce: true,
+            locale: Some(Locale::French),
+            keymap: Keymap::DEFAULT,
+        };
+        assert_eq!(settings.to_string().parse(), Ok(settings));
+
+        // A language that was never picked isn't saved, so the environment keeps choosing it.
+        let settings = Settings::default();
+        assert!(!settings.to_string().contains("language"));
+        assert_eq!(settings.to_string().parse(), Ok(settings));
+    }
+
+    #[test]
+    fn test_parse() {
+        let settings: Settings = "
+            # Written by hand.
+            theme = \"dark\"
+            colour = \"purple\"
+            steps_per_second = 10000000"
+            .parse()
+            .unwrap();
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.steps_per_second, MAX_STEPS_PER_SECOND);
+        assert_eq!(settings.format, Format::Grid);
+
+        let settings: Settings = "
+            keys.step = \"Right, Ctrl+N\"
+            keys.hint = \"\"
+            keys.fly = \"F\""
+            .parse()
+            .unwrap();
+        assert_eq!(settings.keymap.to_config(Action::Step), "RIGHT, Ctrl+N");
+        assert_eq!(settings.keymap.to_config(Action::Hint), "");
+        assert_eq!("keys.step = \"Hyper+S\"".parse::<Settings>(), Err(()));
+
+        assert_eq!("theme = dark".parse::<Settings>(), Err(()));
+        assert_eq!("font_size = -1".parse::<Settings>(), Err(()));
+    }
+}
@@ -0,0 +1,399 @@
+//! Boards whose size isn't 9x9.
+//!
+//! Their cells hold the same [`Entry`] as a 9x9 [`Board`](crate::board::Board), which goes all the
+//! way up to 16, but the rest of the board is simpler. It can be parsed, checked, counted, solved
+//! with the exact cover solver, and drawn, which is what the `solve`, `check`, and `count`
+//! commands and the window for other sizes need. The logic engine, the step-by-step solver, and
+//! everything built on them, such as hints, ratings, the editor, and play mode, only know about
+//! 9x9 boards.
+
+use raylib::prelude::*;
+
+use crate::board::Entry;
+use crate::exact_cover::ExactCover;
+use crate::ui::{self, Widget};
+
+/// The shape of the boxes on a board, which also decides the size of the board.
+///
+/// A board is as many cells wide as there are cells in a box, so a box which is 3 cells wide and
+/// 2 cells tall makes a 6x6 board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Shape {
+    /// The width of each box, in cells.
+    pub box_width: usize,
+
+    /// The height of each box, in cells.
+    pub box_height: usize,
+}
+
+impl Shape {
+    /// A 6x6 board with boxes 3 cells wide and 2 cells tall.
+    pub const SIX: Shape = Shape {
+        box_width: 3,
+        box_height: 2,
+    };
+
+    /// An ordinary 9x9 board.
+    pub const NINE: Shape = Shape {
+        box_width: 3,
+        box_height: 3,
+    };
+
+    /// A 12x12 board with boxes 4 cells wide and 3 cells tall.
+    pub const TWELVE: Shape = Shape {
+        box_width: 4,
+        box_height: 3,
+    };
+
+    /// A 16x16 board with 4x4 boxes, which uses every hexadecimal digit (written `1` to `9`, then
+    /// `A` to `G`).
+    pub const SIXTEEN: Shape = Shape {
+        box_width: 4,
+        box_height: 4,
+    };
+
+    /// Get the shape of the boxes for a board with the supplied width, if that size is supported.
+    pub const fn from_size(size: usize) -> Option<Shape> {
+        match size {
+            6 => Some(Shape::SIX),
+            9 => Some(Shape::NINE),
+            12 => Some(Shape::TWELVE),
+            16 => Some(Shape::SIXTEEN),
+            _ => None,
+        }
+    }
+
+    /// Get the width (and height) of the board, which is also the largest digit.
+    pub const fn size(&self) -> usize {
+        self.box_width * self.box_height
+    }
+
+    /// Get the number of cells on the board.
+    pub const fn cell_count(&self) -> usize {
+        self.size() * self.size()
+    }
+
+    /// Get the cells of every row, column, and box, in that order.
+    pub fn units(&self) -> Vec<Vec<usize>> {
+        let size = self.size();
+        let rows = (0..size).map(|row| (0..size).map(|column| row * size + column).collect());
+        let columns = (0..size).map(|column| (0..size).map(|row| row * size + column).collect());
+        let boxes = (0..size).map(|r#box| {
+            // There are as many boxes across the board as each box is tall.
+            let top = (r#box / self.box_height) * self.box_height;
+            let left = (r#box % self.box_height) * self.box_width;
+            (0..size)
+                .map(|i| (top + i / self.box_width) * size + left + i % self.box_width)
+                .collect()
+        });
+        rows.chain(columns).chain(boxes).collect()
+    }
+}
+
+/// A board of any supported [`Shape`], holding the digits from 1 up to the width of the board.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SizedBoard {
+    shape: Shape,
+    cells: Vec<Option<Entry>>,
+}
+
+impl SizedBoard {
+    /// Create a board of the supplied shape with every cell empty.
+    pub fn empty(shape: Shape) -> SizedBoard {
+        SizedBoard {
+            shape,
+            cells: vec![None; shape.cell_count()],
+        }
+    }
+
+    /// Get the shape of the board.
+    pub const fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// Retrieve the entry in the cell at the supplied index.
+    pub fn get(&self, index: usize) -> Option<Entry> {
+        self.cells[index]
+    }
+
+    /// Set the cell at the supplied index to an entry, or clear it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry is bigger than the width of the board.
+    pub fn set(&mut self, index: usize, entry: Option<Entry>) {
+        if let Some(entry) = entry {
+            assert!(
+                entry.fits(self.shape.size()),
+                "{entry} does not fit on a {0}x{0} board",
+                self.shape.size(),
+            );
+        }
+        self.cells[index] = entry;
+    }
+
+    /// Check that no row, column, or box holds the same digit twice.
+    pub fn is_valid(&self) -> bool {
+        self.shape.units().iter().all(|unit| {
+            let mut seen = 0u32;
+            unit.iter()
+                .filter_map(|&index| self.cells[index])
+                .all(|entry| {
+                    let bit = 1 << entry as u8;
+                    let fresh = seen & bit == 0;
+                    seen |= bit;
+                    fresh
+                })
+        })
+    }
+
+    /// Build the exact cover problem for the board. Row number `size * index + digit` stands for
+    /// the digit (counting from zero) in the cell at that index, like in
+    /// [`SudokuCover`](crate::exact_cover::SudokuCover).
+    fn cover(&self) -> ExactCover {
+        let size = self.shape.size();
+        let cell_count = self.shape.cell_count();
+        let units = self.shape.units();
+        let mut cover = ExactCover::new(cell_count + size * units.len(), 0);
+
+        for (index, given) in self.cells.iter().enumerate() {
+            for digit in 0..size {
+                if given.is_some_and(|given| given as usize != digit) {
+                    cover.add_row(&[]);
+                    continue;
+                }
+
+                let mut columns = vec![index];
+                for (unit, cells) in units.iter().enumerate() {
+                    if cells.contains(&index) {
+                        columns.push(cell_count + size * unit + digit);
+                    }
+                }
+                cover.add_row(&columns);
+            }
+        }
+        cover
+    }
+
+    /// Solve the board, returning the solution as a new board.
+    ///
+    /// If there is no solution, [`None`] is returned.
+    pub fn solve(&self) -> Option<SizedBoard> {
+        let size = self.shape.size();
+        let mut solution = self.clone();
+        for row in self.cover().solve()? {
+            solution.cells[row / size] = Some(Entry::up_to(size)[row % size]);
+        }
+        Some(solution)
+    }
+
+    /// Count the solutions of the board, stopping once `limit` have been found.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.cover().count_solutions(limit)
+    }
+
+    /// Write the board on a single line, reading along the rows from the top left, with `0` for
+    /// each empty cell. This is read back by [`str::parse`].
+    pub fn to_line(&self) -> String {
+        self.cells
+            .iter()
+            .map(|cell| cell.map_or('0', Entry::to_char))
+            .collect()
+    }
+
+    /// Write the board as a JSON object in the same layout as
+    /// [`Board::to_json`](crate::board::Board::to_json), except that each row has as many numbers
+    /// as the board is wide.
+    pub fn to_json(&self) -> String {
+        let rows: Vec<String> = self
+            .rows()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| cell.map_or_else(|| "null".to_string(), |entry| entry.to_string()))
+                    .collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+        format!("{{\"rows\":[{}]}}", rows.join(","))
+    }
+
+    /// Write the board as comma-separated values in the same layout as
+    /// [`Board::to_csv`](crate::board::Board::to_csv), with empty fields for the empty cells.
+    pub fn to_csv(&self) -> String {
+        self.rows()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| cell.map_or_else(String::new, |entry| entry.to_string()))
+                    .collect();
+                cells.join(",") + "\n"
+            })
+            .collect()
+    }
+
+    /// Get the cells of each row, from the top.
+    fn rows(&self) -> std::slice::Chunks<'_, Option<Entry>> {
+        self.cells.chunks(self.shape.size())
+    }
+}
+
+impl std::str::FromStr for SizedBoard {
+    type Err = ();
+
+    /// Convert a string into a board.
+    ///
+    /// This reads the same way as [`Board`](crate::board::Board), except that digits past 9 are
+    /// written as letters (`A` for 10, and so on), and the size of the board comes from the number
+    /// of cells. If that isn't the square of a supported size, or a digit is too big for the
+    /// board, `Err(())` is returned.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cells = Vec::new();
+        for line in s.lines() {
+            if line.trim_start().starts_with('+') {
+                continue;
+            }
+
+            for c in line.chars() {
+                let cell = match c {
+                    '0' | '-' | '_' | '.' => None,
+                    c if c.is_ascii_alphanumeric() => Some(Entry::from_char(c).ok_or(())?),
+                    _ => continue,
+                };
+                cells.push(cell);
+            }
+        }
+
+        let size = cells.len().isqrt();
+        let shape = Shape::from_size(size).ok_or(())?;
+        if size * size != cells.len() || cells.iter().flatten().any(|entry| !entry.fits(size)) {
+            return Err(());
+        }
+        Ok(SizedBoard { shape, cells })
+    }
+}
+
+impl std::fmt::Display for SizedBoard {
+    /// Write the board in the same layout as [`Board`](crate::board::Board), with the columns and
+    /// rows grouped by box.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let size = self.shape.size();
+        for row in 0..size {
+            if row != 0 && row % self.shape.box_height == 0 {
+                writeln!(f)?;
+            }
+
+            for column in 0..size {
+                if column != 0 && column % self.shape.box_width == 0 {
+                    write!(f, " ")?;
+                }
+
+                match self.cells[row * size + column] {
+                    Some(entry) => write!(f, "{}", entry.to_char())?,
+                    None => write!(f, "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Widget for SizedBoard {
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let size = self.shape.size();
+        let cell_size = Vector2::new(rect.width / size as f32, rect.height / size as f32);
+        // The usual font size is picked for 9x9 boards, and the digits would overflow the cells of
+        // anything much bigger.
+        let font_size = f32::min(ui::font_size(), cell_size.y * 0.6);
+
+        for (index, cell) in self.cells.iter().enumerate() {
+            let cell_rect = Rectangle {
+                x: rect.x + (index % size) as f32 * cell_size.x,
+                y: rect.y + (index / size) as f32 * cell_size.y,
+                width: cell_size.x,
+                height: cell_size.y,
+            };
+            d.draw_rectangle_lines_ex(cell_rect, 1.0, Color::GRAY);
+
+            if let Some(entry) = cell {
+                let text = entry.to_char().to_string();
+                ui::draw_text_centered(d, &text, cell_rect, font_size, Color::BLACK);
+            }
+        }
+
+        // The thick lines go between boxes, so they are spaced differently across and down when
+        // the boxes aren't square.
+        let thickness = ui::line_width() / 2.0;
+        for column in (0..=size).step_by(self.shape.box_width) {
+            let x = rect.x + column as f32 * cell_size.x - thickness / 2.0;
+            let line = Rectangle::new(x, rect.y, thickness, rect.height);
+            d.draw_rectangle_rec(line, Color::BLACK);
+        }
+        for row in (0..=size).step_by(self.shape.box_height) {
+            let y = rect.y + row as f32 * cell_size.y - thickness / 2.0;
+            let line = Rectangle::new(rect.x, y, rect.width, thickness);
+            d.draw_rectangle_rec(line, Color::BLACK);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_six() {
+        let board: SizedBoard = "
+            1-3 -5-
+            --6 1-3
+
+            2-- 5-4
+            -6- -3-
+
+            3-2 --5
+            -4- 3-2"
+            .parse()
+            .unwrap();
+        assert_eq!(board.shape(), Shape::SIX);
+
+        let solution = board.solve().unwrap();
+        assert!(solution.is_valid());
+        assert!(solution.cells.iter().all(Option::is_some));
+        assert_eq!(
+            solution.to_string().parse::<SizedBoard>().unwrap(),
+            solution
+        );
+    }
+
+    #[test]
+    fn test_twelve() {
+        let mut board = SizedBoard::empty(Shape::TWELVE);
+        board.set(0, Some(Entry::Twelve));
+        assert!(board.to_string().starts_with("C--- "));
+
+        let solution = board.solve().unwrap();
+        assert!(solution.is_valid());
+        // The second row starts inside the first box, so it can't have a 12 in the first four
+        // cells.
+        assert!((12..16).all(|index| solution.get(index) != Some(Entry::Twelve)));
+    }
+
+    #[test]
+    fn test_sixteen() {
+        let line = "G".to_string() + &"-".repeat(255);
+        let board: SizedBoard = line.parse().unwrap();
+        assert_eq!(board.shape(), Shape::SIXTEEN);
+        assert_eq!(board.get(0), Some(Entry::Sixteen));
+
+        let solution = board.solve().unwrap();
+        assert!(solution.is_valid());
+        assert_eq!(
+            solution.to_string().parse::<SizedBoard>().unwrap(),
+            solution
+        );
+        assert_eq!(solution.to_line().parse::<SizedBoard>().unwrap(), solution);
+        assert!(solution.to_json().starts_with("{\"rows\":[[16,"));
+        assert_eq!(solution.to_csv().lines().count(), 16);
+    }
+}
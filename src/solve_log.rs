@@ -0,0 +1,217 @@
+//! A record of every decision made by the [`Solver`](crate::solver::Solver).
+//!
+//! The log is meant for replaying and explaining a solve after the fact. Each entry says what the
+//! solver did to the board and, where it makes sense, why it did it.
+
+use crate::board::{Board, Entry, Unit};
+use crate::logic::Technique;
+use crate::solver::StepOutcome;
+
+/// Get a short name for a cell, like "r3c7" for the cell in row 3 and column 7.
+///
+/// Rows and columns are numbered from 1, since this is meant for people to read.
+pub fn cell_name(index: usize) -> String {
+    format!("r{}c{}", index / 9 + 1, index % 9 + 1)
+}
+
+/// Read a name written by [`cell_name`] back into the index of the cell.
+///
+/// If the name is not in that format, or the cell is off the board, [`None`] is returned.
+pub fn parse_cell_name(name: &str) -> Option<usize> {
+    let (row, column) = name.strip_prefix('r')?.split_once('c')?;
+    let (row, column): (usize, usize) = (row.parse().ok()?, column.parse().ok()?);
+    ((1..=9).contains(&row) && (1..=9).contains(&column)).then(|| (row - 1) * 9 + column - 1)
+}
+
+/// Find another cell which clashes with the entry at the supplied index.
+///
+/// The unit which the two cells share and the index of the other cell are returned. If the cell is
+/// empty or nothing clashes with it, [`None`] is returned.
+pub fn find_clash(board: &Board, index: usize) -> Option<(Unit, usize)> {
+    let entry = board.get_cell_index(index)?;
+    board.units_containing(index).into_iter().find_map(|unit| {
+        board
+            .variant()
+            .cells(unit)
+            .into_iter()
+            .find(|&other| other != index && board.get_cell_index(other) == Some(entry))
+            .map(|other| (unit, other))
+    })
+}
+
+/// A single decision made by the solver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogEntry {
+    /// An entry was written into an empty cell.
+    Placed {
+        /// The index of the cell.
+        index: usize,
+
+        /// The entry which was written.
+        entry: Entry,
+    },
+
+    /// An entry was ruled out because it broke the rules.
+    Eliminated {
+        /// The index of the cell.
+        index: usize,
+
+        /// The entry which was ruled out.
+        entry: Entry,
+
+        /// The unit where the rules were broken, and the other cell in that unit holding the same
+        /// entry. This is only [`None`] if the board was changed behind the solver's back.
+        clash: Option<(Unit, usize)>,
+    },
+
+    /// An entry was abandoned because no digit worked in a later cell.
+    Backtracked {
+        /// The index of the cell.
+        index: usize,
+
+        /// The entry which was abandoned.
+        entry: Entry,
+
+        /// The later cell which ran out of digits, if the log saw it happen.
+        dead_end: Option<usize>,
+    },
+
+    /// A logical technique was applied.
+    Deduced {
+        /// The technique which was applied.
+        technique: Technique,
+
+        /// The index of the cell and the entry which was written into it, if the technique placed
+        /// a digit rather than ruling out candidates.
+        placed: Option<(usize, Entry)>,
+    },
+}
+
+impl std::fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            LogEntry::Placed { index, entry } => {
+                write!(f, "placed {entry} at {}", cell_name(index))
+            }
+            LogEntry::Eliminated {
+                index,
+                entry,
+                clash,
+            } => {
+                write!(f, "eliminated {entry} at {}", cell_name(index))?;
+                if let Some((unit, other)) = clash {
+                    write!(f, ": clashes with {} in {unit}", cell_name(other))?;
+                }
+                Ok(())
+            }
+            LogEntry::Backtracked {
+                index,
+                entry,
+                dead_end,
+            } => {
+                write!(f, "backtracked from {entry} at {}", cell_name(index))?;
+                if let Some(dead_end) = dead_end {
+                    write!(f, ": no digit fits {}", cell_name(dead_end))?;
+                }
+                Ok(())
+            }
+            LogEntry::Deduced {
+                technique,
+                placed: Some((index, entry)),
+            } => write!(f, "placed {entry} at {} by {technique}", cell_name(index)),
+            LogEntry::Deduced {
+                technique,
+                placed: None,
+            } => write!(f, "eliminated candidates by {technique}"),
+        }
+    }
+}
+
+/// An ordered list of the decisions made during a solve.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SolveLog {
+    entries: Vec<LogEntry>,
+}
+
+impl SolveLog {
+    /// Create an empty log.
+    pub const fn new() -> SolveLog {
+        SolveLog {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Retrieve the entries of the log, oldest first.
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+
+    /// Get the number of entries in the log.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check whether the log is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record the outcome of a solver step.
+    ///
+    /// The clash is the reason for a [`StepOutcome::Rejected`] outcome, as found by
+    /// [`find_clash`] before the step was taken. Outcomes which do not change the board are not
+    /// recorded.
+    pub fn record(&mut self, outcome: StepOutcome, clash: Option<(Unit, usize)>) {
+        let entry = match outcome {
+            StepOutcome::Placed { index, entry } => LogEntry::Placed { index, entry },
+            StepOutcome::Rejected { index, entry } => LogEntry::Eliminated {
+                index,
+                entry,
+                clash,
+            },
+            StepOutcome::Backtracked { index, entry } => LogEntry::Backtracked {
+                index,
+                entry,
+                dead_end: self.last_cleared(),
+            },
+            StepOutcome::Deduced { technique, placed } => LogEntry::Deduced { technique, placed },
+            StepOutcome::Solved | StepOutcome::Unsolvable => return,
+        };
+        self.entries.push(entry);
+    }
+
+    /// Forget the most recent entry, for when the step that made it is undone.
+    pub(crate) fn pop(&mut self) -> Option<LogEntry> {
+        self.entries.pop()
+    }
+
+    /// Get the cell which was cleared by the last entry, if any.
+    ///
+    /// The solver only clears a cell when it gives up on a 9, since there is no digit left to try
+    /// after that.
+    fn last_cleared(&self) -> Option<usize> {
+        match self.entries.last()? {
+            LogEntry::Eliminated {
+                index,
+                entry: Entry::Nine,
+                ..
+            }
+            | LogEntry::Backtracked {
+                index,
+                entry: Entry::Nine,
+                ..
+            } => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SolveLog {
+    /// Write the log with one entry per line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
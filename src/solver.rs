@@ -1,8 +1,41 @@
-use crate::board::{Board, Entry};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::board::{Board, Candidates, Entry};
+use crate::exact_cover::SudokuCover;
+use crate::logic::{Action, Deduction, Grid, Technique};
+use crate::random::{Rng, Seed};
+use crate::solve_log::{self, SolveLog};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct AttemptLocation(usize);
 
+/// The most steps that [`Solver::step_back`] can undo. Older steps are forgotten so that long
+/// solves don't eat up all of the memory.
+pub const HISTORY_LIMIT: usize = 1 << 16;
+
+/// Everything needed to undo a single step of the [`Solver`].
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    /// What the step did. The cell which was changed, and what it held before, can be worked out
+    /// from this.
+    outcome: StepOutcome,
+
+    /// Whether the solver was backtracking before the step.
+    backtracking: bool,
+
+    /// The length of the attempt stack before the step. A single step changes at most the top of
+    /// the stack, so the top is all that needs to be remembered.
+    stack_len: usize,
+    stack_top: Option<AttemptLocation>,
+
+    /// The candidates from previous deductions, if there were any.
+    grid: Option<Box<Grid>>,
+}
+
 /// Solve a Sudoku board.
 ///
 /// This function will attempt to solve the supplied Sudoku board by mutating it. If the board was
@@ -10,31 +43,555 @@ struct AttemptLocation(usize);
 /// returned. If the board could not be solved, then the passed board remains unchanged and `false`
 /// is returned.
 pub fn solve(board: &mut Board) -> bool {
-    // What data is each stack frame holding? In other words, what data persists between changes to
-    // the board (between recursive calls)?
-    //
-    // - entry  (unique for every stack frame)
-    // - index
+    solve_with_limits(board, Limits::NONE) == SolveOutcome::Solved
+}
 
-    let Some(index) = board.first_unfilled_index() else {
-        return board.is_valid();
+/// Solve a Sudoku board, trying the digits of each cell in an order shuffled by the seed.
+///
+/// This behaves just like [`solve`]. The only difference is which solution is found when the board
+/// has more than one, and how long the search takes to get there. The same board and the same seed
+/// always lead to the same search, so seeded solves can be reproduced exactly.
+pub fn solve_seeded(board: &mut Board, seed: Seed) -> bool {
+    search(board, Limits::NONE, Some(seed)).0 == SolveOutcome::Solved
+}
+
+/// Count the solutions of a board, stopping once `limit` have been found.
+///
+/// This is mostly useful for checking that a puzzle is proper, in which case a limit of 2 is
+/// enough: a count of 1 means that the solution is unique.
+///
+/// # Examples
+///
+/// ```
+/// use sudoku_solver::board::Board;
+/// use sudoku_solver::solver;
+///
+/// let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+/// assert_eq!(solver::count_solutions(&puzzle, 2), 1);
+/// assert_eq!(solver::count_solutions(&Board::empty(), 2), 2);
+/// ```
+pub fn count_solutions(board: &Board, limit: usize) -> usize {
+    if is_dead_end(board) || limit == 0 {
+        return 0;
+    }
+    // Rules which the exact cover solver can't follow need the slower search.
+    match SudokuCover::for_board(board) {
+        Some(cover) => cover.count_solutions(board, limit),
+        None => count_by_search(&mut board.clone(), limit),
+    }
+}
+
+/// Count the solutions of a board by backtracking, stopping once `limit` have been found. The
+/// board is left the way it was found.
+fn count_by_search(board: &mut Board, limit: usize) -> usize {
+    let Some(index) = most_constrained_index(board) else {
+        return usize::from(board.is_valid());
     };
 
-    for number in 1..=9 {
-        // The unwrap call will never panic since we are only inputting numbers in the range 1..=9
-        let entry = Entry::try_from(number).unwrap();
+    let mut count = 0;
+    for entry in board.candidates(index).iter() {
         board.set_cell_index(index, Some(entry));
-        if !board.is_valid() {
-            continue;
+        if board.is_valid() {
+            count += count_by_search(board, limit - count);
+        }
+        if count >= limit {
+            break;
         }
+    }
+    board.set_cell_index(index, None);
+    count
+}
+
+/// Solve a Sudoku board without touching it.
+///
+/// This is like [`solve`], except that the solution is returned as a new board, so the original
+/// puzzle is still around afterwards. If the board could not be solved, [`None`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use sudoku_solver::board::Board;
+/// use sudoku_solver::solver;
+///
+/// let puzzle: Board = include_str!("../boards/medium-1.txt").parse().unwrap();
+/// let solution = solver::solve_copy(&puzzle).unwrap();
+/// assert!(puzzle.first_unfilled_index().is_some());
+/// assert!(solution.first_unfilled_index().is_none());
+/// ```
+pub fn solve_copy(board: &Board) -> Option<Board> {
+    let mut solution = board.clone();
+    solve(&mut solution).then_some(solution)
+}
+
+/// Solve a Sudoku board without touching it, as quickly as possible.
+///
+/// This gives the same answer as [`solve_copy`], but uses the exact cover solver, which is much
+/// faster on hard puzzles. Boards with a constraint that can't be written as an exact cover
+/// problem are solved by the usual search instead.
+pub fn solve_fast(board: &Board) -> Option<Board> {
+    match SudokuCover::for_board(board) {
+        Some(cover) => cover.solve(board),
+        None => solve_copy(board),
+    }
+}
+
+/// Bounds on the amount of work that [`solve_with_limits`] is allowed to do.
+///
+/// A limit of [`None`] means that there is no bound on that particular resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    /// The maximum number of search nodes to visit. A node is visited every time the solver
+    /// writes a candidate entry into a cell.
+    pub max_nodes: Option<u64>,
+
+    /// The maximum amount of wall-clock time to spend searching.
+    pub max_time: Option<Duration>,
+}
+
+impl Limits {
+    /// No limits at all. The search runs until the board is solved or proven unsolvable.
+    pub const NONE: Limits = Limits {
+        max_nodes: None,
+        max_time: None,
+    };
+}
+
+/// The result of a bounded solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SolveOutcome {
+    /// The board was solved.
+    Solved,
+
+    /// The search ran out of nodes or time before it could reach a conclusion.
+    LimitReached,
+
+    /// The whole search space was explored and no solution exists.
+    Unsolvable,
+}
+
+/// Solve a Sudoku board without exceeding the supplied limits.
+///
+/// This behaves just like [`solve`], except that the search gives up once it hits one of the
+/// limits. That makes it safe to run on adversarial inputs, where the plain backtracking search
+/// could take practically forever. If the outcome is anything other than
+/// [`SolveOutcome::Solved`], the board is left unchanged.
+pub fn solve_with_limits(board: &mut Board, limits: Limits) -> SolveOutcome {
+    search(board, limits, None).0
+}
+
+/// Statistics about a single backtracking search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SearchStats {
+    /// The number of search nodes visited. A node is visited every time the solver writes a
+    /// candidate entry into a cell.
+    pub nodes: u64,
+
+    /// The number of times the search picked an empty cell to fill in. Each of these is a point
+    /// where the search had to guess.
+    pub decisions: u64,
 
-        if solve(board) {
-            return true;
+    /// The number of entries which didn't immediately break the rules, and so were explored
+    /// further.
+    pub branches: u64,
+
+    /// The most guesses that were in play at once.
+    pub max_depth: usize,
+}
+
+impl SearchStats {
+    /// Get the average number of entries explored per guess, or zero if no guesses were made.
+    ///
+    /// A branching factor close to 1 means the search rarely had a real choice to make, while
+    /// higher values mean it had to try several options at many of its guesses.
+    pub fn branching_factor(&self) -> f64 {
+        if self.decisions == 0 {
+            return 0.0;
         }
+        self.branches as f64 / self.decisions as f64
     }
+}
 
-    board.set_cell_index(index, None);
-    false
+/// Solve a Sudoku board without exceeding the supplied limits, and report how much work it took.
+///
+/// This behaves just like [`solve_with_limits`]. Boards which are obviously unsolvable (see
+/// [`is_dead_end`]) are rejected without searching at all, so their statistics are all zero.
+pub fn solve_with_stats(board: &mut Board, limits: Limits) -> (SolveOutcome, SearchStats) {
+    search(board, limits, None)
+}
+
+/// Run a backtracking search, shuffling the digit order if there is a seed.
+pub(crate) fn search(
+    board: &mut Board,
+    limits: Limits,
+    seed: Option<Seed>,
+) -> (SolveOutcome, SearchStats) {
+    if is_dead_end(board) {
+        return (SolveOutcome::Unsolvable, SearchStats::default());
+    }
+
+    let mut search = Search::new(limits);
+    search.rng = seed.map(Rng::new);
+    let outcome = match search.run(board) {
+        Ok(true) => SolveOutcome::Solved,
+        Ok(false) => SolveOutcome::Unsolvable,
+        Err(Interrupted) => SolveOutcome::LimitReached,
+    };
+    log::debug!(
+        "backtracking search finished as {outcome:?} after {} nodes in {:.2?}",
+        search.stats.nodes,
+        search.start.elapsed()
+    );
+    (outcome, search.stats)
+}
+
+/// Check whether a board is obviously unsolvable.
+///
+/// This is a cheap check that catches boards which can never be completed without having to search
+/// for a solution. A board is a dead end if it breaks the rules already, if some empty cell has no
+/// candidates left, or if some digit cannot be placed anywhere in one of the rows, columns, or big
+/// cells. If this function returns `false`, that does not mean the board is solvable, only that
+/// finding out requires a real search.
+pub fn is_dead_end(board: &Board) -> bool {
+    if !board.is_valid() {
+        return true;
+    }
+
+    let candidates: [Candidates; 81] = std::array::from_fn(|index| board.candidates(index));
+    let empty_cell_stuck =
+        (0..81).any(|index| board.get_cell_index(index).is_none() && candidates[index].is_empty());
+    if empty_cell_stuck {
+        return true;
+    }
+
+    board.units().into_iter().any(|unit| {
+        let possible =
+            board
+                .variant()
+                .cells(unit)
+                .into_iter()
+                .fold(Candidates::NONE, |possible, index| {
+                    match board.get_cell_index(index) {
+                        Some(entry) => possible.union(Candidates::only(entry)),
+                        None => possible.union(candidates[index]),
+                    }
+                });
+        possible != Candidates::ALL
+    })
+}
+
+/// Solve a Sudoku board using every available CPU core.
+///
+/// The search is split up on the candidates of the most constrained empty cell (the one with the
+/// fewest candidates), and each branch is searched on its own thread. As soon as one branch finds
+/// a solution, the others are told to give up. Otherwise this function behaves exactly like
+/// [`solve`]: the board is mutated into a solved state and `true` is returned, or the board is
+/// left unchanged and `false` is returned.
+pub fn solve_parallel(board: &mut Board) -> bool {
+    if is_dead_end(board) {
+        return false;
+    }
+
+    let Some(index) = most_constrained_index(board) else {
+        return board.is_valid();
+    };
+
+    let cancelled = AtomicBool::new(false);
+    let branches: Vec<Entry> = board.candidates(index).iter().collect();
+    let solution = branches.into_par_iter().find_map_any(|entry| {
+        let mut branch = board.clone();
+        branch.set_cell_index(index, Some(entry));
+
+        let mut search = Search::new(Limits::NONE);
+        search.cancelled = Some(&cancelled);
+        match search.run(&mut branch) {
+            Ok(true) => {
+                cancelled.store(true, Ordering::Relaxed);
+                Some(branch)
+            }
+            Ok(false) | Err(Interrupted) => None,
+        }
+    });
+
+    match solution {
+        Some(solution) => {
+            *board = solution;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Find the empty cell with the fewest candidates.
+///
+/// Ties are broken in favor of the cell which comes first. If the board is full, [`None`] is
+/// returned.
+fn most_constrained_index(board: &Board) -> Option<usize> {
+    (0..81)
+        .filter(|&index| board.get_cell_index(index).is_none())
+        .min_by_key(|&index| board.candidates(index).len())
+}
+
+/// Marker for a search which was cut short, either by its [`Limits`] or by being cancelled.
+struct Interrupted;
+
+/// The state of a recursive backtracking search.
+struct Search<'a> {
+    limits: Limits,
+    start: Instant,
+    stats: SearchStats,
+
+    /// The number of guesses currently in play.
+    depth: usize,
+
+    /// A flag which other threads can set to make the search give up early.
+    cancelled: Option<&'a AtomicBool>,
+
+    /// The source of the digit order, if it should be shuffled rather than counting up from 1.
+    rng: Option<Rng>,
+}
+
+impl Search<'_> {
+    fn new(limits: Limits) -> Self {
+        Search {
+            limits,
+            start: Instant::now(),
+            stats: SearchStats::default(),
+            depth: 0,
+            cancelled: None,
+            rng: None,
+        }
+    }
+
+    /// Record a visit to a new node, failing if that would exceed the limits.
+    fn visit(&mut self) -> Result<(), Interrupted> {
+        self.stats.nodes += 1;
+        if self
+            .limits
+            .max_nodes
+            .is_some_and(|max| self.stats.nodes > max)
+        {
+            return Err(Interrupted);
+        }
+        if self
+            .limits
+            .max_time
+            .is_some_and(|max| self.start.elapsed() > max)
+        {
+            return Err(Interrupted);
+        }
+        if self
+            .cancelled
+            .is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+        {
+            return Err(Interrupted);
+        }
+        Ok(())
+    }
+
+    fn run(&mut self, board: &mut Board) -> Result<bool, Interrupted> {
+        // What data is each stack frame holding? In other words, what data persists between
+        // changes to the board (between recursive calls)?
+        //
+        // - entry  (unique for every stack frame)
+        // - index
+
+        let Some(index) = board.first_unfilled_index() else {
+            return Ok(board.is_valid());
+        };
+
+        self.stats.decisions += 1;
+        self.depth += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        let result = self.fill(board, index);
+        self.depth -= 1;
+        result
+    }
+
+    /// Try every entry in the empty cell at the supplied index, searching onwards from each one.
+    fn fill(&mut self, board: &mut Board, index: usize) -> Result<bool, Interrupted> {
+        let mut order = Entry::ALL;
+        if let Some(rng) = &mut self.rng {
+            rng.shuffle(&mut order);
+        }
+
+        for entry in order {
+            if let Err(err) = self.visit() {
+                board.set_cell_index(index, None);
+                return Err(err);
+            }
+
+            board.set_cell_index(index, Some(entry));
+            if !board.is_valid() {
+                continue;
+            }
+
+            self.stats.branches += 1;
+            log::trace!(
+                "trying {entry} at row {}, column {} at depth {}",
+                index / 9 + 1,
+                index % 9 + 1,
+                self.depth
+            );
+            match self.run(board) {
+                Ok(true) => return Ok(true),
+                Ok(false) => {}
+                Err(err) => {
+                    board.set_cell_index(index, None);
+                    return Err(err);
+                }
+            }
+        }
+
+        board.set_cell_index(index, None);
+        Ok(false)
+    }
+}
+
+/// What happened during a single call to [`Solver::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepOutcome {
+    /// An entry was written into a cell which was empty.
+    Placed {
+        /// The index of the cell.
+        index: usize,
+
+        /// The entry which was written.
+        entry: Entry,
+    },
+
+    /// The last entry broke the rules, so it was replaced with the next digit. If there was no
+    /// next digit, the cell was cleared instead and the solver started backtracking.
+    Rejected {
+        /// The index of the cell.
+        index: usize,
+
+        /// The entry which broke the rules.
+        entry: Entry,
+    },
+
+    /// Every digit failed in a later cell, so the entry in an earlier cell was replaced with the
+    /// next digit (or cleared, if it was already a 9).
+    Backtracked {
+        /// The index of the earlier cell.
+        index: usize,
+
+        /// The entry which was in the cell before backtracking.
+        entry: Entry,
+    },
+
+    /// A logical technique either placed a digit or ruled out some candidates. This only happens
+    /// in [`StepMode::Logic`].
+    Deduced {
+        /// The technique which was applied.
+        technique: Technique,
+
+        /// The index of the cell and the entry which was written into it, if the technique placed
+        /// a digit.
+        placed: Option<(usize, Entry)>,
+    },
+
+    /// The board is solved, so there is nothing left to do.
+    Solved,
+
+    /// Every possibility has been tried and none of them led to a solution.
+    Unsolvable,
+}
+
+impl StepOutcome {
+    /// Check whether the solver is finished, whether or not it was successful.
+    pub const fn is_finished(&self) -> bool {
+        matches!(self, StepOutcome::Solved | StepOutcome::Unsolvable)
+    }
+
+    /// Get the index of the cell that the step changed, along with what the cell held before the
+    /// step. Steps which didn't change any cell give [`None`].
+    pub const fn changed_cell(&self) -> Option<(usize, Option<Entry>)> {
+        match *self {
+            StepOutcome::Placed { index, .. }
+            | StepOutcome::Deduced {
+                placed: Some((index, _)),
+                ..
+            } => Some((index, None)),
+            StepOutcome::Rejected { index, entry } | StepOutcome::Backtracked { index, entry } => {
+                Some((index, Some(entry)))
+            }
+            StepOutcome::Deduced { placed: None, .. }
+            | StepOutcome::Solved
+            | StepOutcome::Unsolvable => None,
+        }
+    }
+}
+
+/// The reasons that [`Solver::step`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepError {
+    /// The board broke the rules before the solver touched it, or it was modified while the
+    /// solver was working on it.
+    InvalidBoard,
+}
+
+impl std::fmt::Display for StepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepError::InvalidBoard => write!(f, "the board is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+/// How the [`Solver`] decides what to do on each step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum StepMode {
+    /// Fill in the cells in order, trying every digit and backtracking when one fails.
+    #[default]
+    Search,
+
+    /// Apply one logical technique per step, the way a person would, and only fall back to
+    /// searching once logic runs out.
+    Logic,
+}
+
+/// Running totals for a [`Solver`], for showing how much work a solve has taken so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct StepStats {
+    /// The number of steps which changed something. Steps that only report that the solver is
+    /// finished aren't counted, and undoing a step takes it off again.
+    pub steps: u64,
+
+    /// The number of those steps which were [`StepOutcome::Backtracked`].
+    pub backtracks: u64,
+
+    /// The time spent inside [`Solver::step`]. Time spent paused between steps isn't included.
+    pub elapsed: Duration,
+
+    /// The part of `elapsed` spent checking whether the last entry broke the rules.
+    pub validating: Duration,
+
+    /// The number of cells that the search has written digits into, and may yet take back.
+    pub depth: usize,
+
+    /// The largest that `depth` has been so far.
+    pub max_depth: usize,
+
+    /// The number of [`StepOutcome::Deduced`] steps made with each kind of technique, numbered by
+    /// [`Technique::kind`].
+    pub techniques: [u64; Technique::KINDS],
+}
+
+impl StepStats {
+    /// Create the totals for a solve which hasn't taken any steps yet.
+    pub const fn new() -> StepStats {
+        StepStats {
+            steps: 0,
+            backtracks: 0,
+            elapsed: Duration::ZERO,
+            validating: Duration::ZERO,
+            depth: 0,
+            max_depth: 0,
+            techniques: [0; Technique::KINDS],
+        }
+    }
 }
 
 /// Holds solving state.
@@ -42,10 +599,27 @@ pub fn solve(board: &mut Board) -> bool {
 /// To enable asynchronous solving, this structure holds the solving state so that solving can be
 /// paused and resumed. This allows the UI to update between moves without using any truly async
 /// code.
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct Solver {
     attempt_stack: Vec<AttemptLocation>,
     backtracking: bool,
+
+    /// A record of every step, if the solver was asked to keep one.
+    log: Option<SolveLog>,
+
+    mode: StepMode,
+
+    /// The candidates left over from previous deductions in [`StepMode::Logic`]. This is dropped
+    /// once logic runs out and searching takes over.
+    grid: Option<Grid>,
+
+    /// The most recent steps, oldest first, for [`Solver::step_back`].
+    history: VecDeque<HistoryEntry>,
+
+    /// The reasoning behind the last step, if it was a [`StepOutcome::Deduced`] step.
+    deduction: Option<Deduction>,
+
+    stats: StepStats,
 }
 
 impl Solver {
@@ -54,60 +628,223 @@ impl Solver {
         Solver {
             attempt_stack: Vec::new(),
             backtracking: false,
+            log: None,
+            mode: StepMode::Search,
+            grid: None,
+            history: VecDeque::new(),
+            deduction: None,
+            stats: StepStats::new(),
         }
     }
 
+    /// Create a new solver which keeps a [`SolveLog`] of every step it takes.
+    ///
+    /// The log grows by one entry for every step, so this is best avoided for very long solves
+    /// unless the log is actually needed.
+    pub const fn with_log() -> Solver {
+        Solver {
+            attempt_stack: Vec::new(),
+            backtracking: false,
+            log: Some(SolveLog::new()),
+            mode: StepMode::Search,
+            grid: None,
+            history: VecDeque::new(),
+            deduction: None,
+            stats: StepStats::new(),
+        }
+    }
+
+    /// Retrieve the log of every step taken so far.
+    ///
+    /// This returns [`None`] unless the solver was created with [`Solver::with_log`].
+    pub fn log(&self) -> Option<&SolveLog> {
+        self.log.as_ref()
+    }
+
+    /// Get the reasoning behind the last step, if it was a [`StepOutcome::Deduced`] step, so that
+    /// it can be shown on the board.
+    pub fn deduction(&self) -> Option<&Deduction> {
+        self.deduction.as_ref()
+    }
+
+    /// Get the running totals for the solve so far.
+    ///
+    /// These aren't saved with a [`SolveState`], so a resumed solve starts counting from zero.
+    pub const fn stats(&self) -> StepStats {
+        self.stats
+    }
+
+    /// Get the number of bytes taken up by the stack of cells that the search has guessed in.
+    ///
+    /// This counts the room set aside for the stack, not just the part in use, since that is what
+    /// is actually taken from memory.
+    pub fn attempt_stack_bytes(&self) -> usize {
+        self.attempt_stack.capacity() * std::mem::size_of::<AttemptLocation>()
+    }
+
+    /// Get the way that the solver decides what to do on each step.
+    pub const fn mode(&self) -> StepMode {
+        self.mode
+    }
+
+    /// Change the way that the solver decides what to do on each step.
+    ///
+    /// This can be done in the middle of a solve. However, logic is only used until the first
+    /// guess is made, so switching to [`StepMode::Logic`] once searching has started has no effect
+    /// until the search is finished.
+    pub fn set_mode(&mut self, mode: StepMode) {
+        self.mode = mode;
+        self.grid = None;
+    }
+
     /// Step the solver once.
-    pub fn step(&mut self, board: &mut Board) -> bool {
-        if !board.is_valid() {
-            // The last move was not valid
-            let AttemptLocation(last_index) = self
-                .attempt_stack
-                .pop()
-                .expect("The board you passed was invalid to begin with");
-
-            let last_entry = board
-                .get_cell_index(last_index)
-                .expect("there should be a cell here");
-
-            if last_entry != Entry::Nine {
-                board.set_cell_index(last_index, Some(last_entry.successor().unwrap()));
-                self.attempt_stack.push(AttemptLocation(last_index));
-            } else {
-                board.set_cell_index(last_index, None);
-                self.backtracking = true;
+    ///
+    /// Each step either writes a new entry into the board, replaces the last entry with the next
+    /// digit, or erases the last entry in order to backtrack. The returned [`StepOutcome`] says
+    /// which of those happened, or whether the solver is finished. Once the solver is finished,
+    /// stepping it again just reports the same outcome without touching the board.
+    ///
+    /// # Errors
+    ///
+    /// If the board broke the rules before the solver made any moves, or was changed behind the
+    /// solver's back, [`StepError::InvalidBoard`] is returned and the board is left as it is.
+    pub fn step(&mut self, board: &mut Board) -> Result<StepOutcome, StepError> {
+        // The reason for a rejection has to be found before the offending entry is replaced.
+        let clash = match (&self.log, self.attempt_stack.last()) {
+            (Some(_), Some(&AttemptLocation(index))) if !board.is_valid() => {
+                solve_log::find_clash(board, index)
             }
+            _ => None,
+        };
+
+        let backtracking = self.backtracking;
+        let stack_len = self.attempt_stack.len();
+        let stack_top = self.attempt_stack.last().copied();
+        let grid = self.grid.clone().map(Box::new);
 
+        self.deduction = None;
+        let start = Instant::now();
+        let outcome = self.advance(board);
+        self.stats.elapsed += start.elapsed();
+        let outcome = outcome?;
+        if outcome.is_finished() {
+            // Finishing doesn't change anything, so there is nothing to undo.
+            return Ok(outcome);
+        }
+
+        if let Some(log) = &mut self.log {
+            log.record(outcome, clash);
+        }
+        self.stats.steps += 1;
+        match outcome {
+            StepOutcome::Backtracked { .. } => self.stats.backtracks += 1,
+            StepOutcome::Deduced { technique, .. } => self.stats.techniques[technique.kind()] += 1,
+            _ => {}
+        }
+        self.stats.depth = self.attempt_stack.len();
+        self.stats.max_depth = self.stats.max_depth.max(self.stats.depth);
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryEntry {
+            outcome,
+            backtracking,
+            stack_len,
+            stack_top,
+            grid,
+        });
+        Ok(outcome)
+    }
+
+    /// Undo the last step.
+    ///
+    /// Both the board and the solver are put back the way they were before the step, so stepping
+    /// forward again repeats it. Only the last [`HISTORY_LIMIT`] steps are remembered. If there is
+    /// nothing left to undo, `false` is returned and the board is left as it is.
+    pub fn step_back(&mut self, board: &mut Board) -> bool {
+        let Some(entry) = self.history.pop_back() else {
             return false;
+        };
+        self.deduction = None;
+
+        match entry.outcome {
+            StepOutcome::Placed { index, .. }
+            | StepOutcome::Deduced {
+                placed: Some((index, _)),
+                ..
+            } => board.set_cell_index(index, None),
+            StepOutcome::Rejected { index, entry } | StepOutcome::Backtracked { index, entry } => {
+                board.set_cell_index(index, Some(entry))
+            }
+            StepOutcome::Deduced { placed: None, .. }
+            | StepOutcome::Solved
+            | StepOutcome::Unsolvable => {}
         }
 
-        if self.backtracking {
-            let AttemptLocation(last_index) = self
-                .attempt_stack
-                .pop()
-                .expect("The board you passed was invalid to begin with");
+        self.stats.steps -= 1;
+        match entry.outcome {
+            StepOutcome::Backtracked { .. } => self.stats.backtracks -= 1,
+            StepOutcome::Deduced { technique, .. } => self.stats.techniques[technique.kind()] -= 1,
+            _ => {}
+        }
 
-            let last_entry = board
-                .get_cell_index(last_index)
-                .expect("there should be a cell here");
+        self.attempt_stack
+            .truncate(entry.stack_len.saturating_sub(1));
+        self.attempt_stack.extend(entry.stack_top);
+        self.stats.depth = self.attempt_stack.len();
+        self.backtracking = entry.backtracking;
+        self.grid = entry.grid.map(|grid| *grid);
+        if let Some(log) = &mut self.log {
+            log.pop();
+        }
+        true
+    }
 
-            if last_entry != Entry::Nine {
-                board.set_cell_index(last_index, Some(last_entry.successor().unwrap()));
-                self.attempt_stack.push(AttemptLocation(last_index));
-                self.backtracking = false;
+    /// Take a single step, without logging it.
+    fn advance(&mut self, board: &mut Board) -> Result<StepOutcome, StepError> {
+        if self.attempt_stack.is_empty() && !self.backtracking && is_dead_end(board) {
+            // Nothing has been tried yet, so any problems with the board were there from the
+            // start. There's no point in searching if we can already tell that it's hopeless.
+            return if board.is_valid() {
+                Ok(StepOutcome::Unsolvable)
             } else {
-                board.set_cell_index(last_index, None);
-                self.backtracking = true;
-            }
+                Err(StepError::InvalidBoard)
+            };
+        }
 
-            return false;
+        let start = Instant::now();
+        let valid = board.is_valid();
+        self.stats.validating += start.elapsed();
+        if !valid {
+            // The last move was not valid.
+            let Some((index, entry)) = self.retry_last(board)? else {
+                return Err(StepError::InvalidBoard);
+            };
+            return Ok(StepOutcome::Rejected { index, entry });
+        }
+
+        if self.mode == StepMode::Logic
+            && self.attempt_stack.is_empty()
+            && !self.backtracking
+            && let Some(outcome) = self.deduce(board)
+        {
+            return Ok(outcome);
+        }
+
+        if self.backtracking {
+            // Running out of moves to undo means that we have backtracked past the very first
+            // move, so every possibility has been tried.
+            let Some((index, entry)) = self.retry_last(board)? else {
+                return Ok(StepOutcome::Unsolvable);
+            };
+            return Ok(StepOutcome::Backtracked { index, entry });
         }
 
         // At this point the last move was valid, so we move on to make another move. Search for
         // the first unfilled cell in the board. If the board only has filled cells, then it must
         // be solved since no invalid entry can be made.
         let Some(index) = board.first_unfilled_index() else {
-            return true;
+            return Ok(StepOutcome::Solved);
         };
 
         // If there is an unfilled square, we need to try to fill it. But with what? The current
@@ -115,6 +852,547 @@ impl Solver {
         // that.
         board.set_cell_index(index, Some(Entry::One));
         self.attempt_stack.push(AttemptLocation(index));
-        false
+        Ok(StepOutcome::Placed {
+            index,
+            entry: Entry::One,
+        })
+    }
+
+    /// Apply the next logical deduction to the board.
+    ///
+    /// If logic has run out, [`None`] is returned and the board is left for searching to finish.
+    fn deduce(&mut self, board: &mut Board) -> Option<StepOutcome> {
+        let grid = self.grid.get_or_insert_with(|| Grid::new(board));
+        if grid.is_stuck() {
+            // Deductions never rule out the real solution, so there can't be one.
+            return Some(StepOutcome::Unsolvable);
+        }
+
+        let Some(deduction) = grid.find_deduction() else {
+            self.grid = None;
+            return None;
+        };
+        grid.apply(&deduction);
+
+        let placed = match deduction.action {
+            Action::Place { index, entry } => {
+                board.set_cell_index(index, Some(entry));
+                Some((index, entry))
+            }
+            Action::Eliminate(_) => None,
+        };
+        let technique = deduction.technique;
+        self.deduction = Some(deduction);
+        Some(StepOutcome::Deduced { technique, placed })
+    }
+
+    /// Replace the last attempted entry with the next digit.
+    ///
+    /// If the last attempt was already a 9, the cell is cleared and the solver starts backtracking.
+    /// The location and the old entry of the last attempt are returned, or [`None`] if there was
+    /// no last attempt.
+    fn retry_last(&mut self, board: &mut Board) -> Result<Option<(usize, Entry)>, StepError> {
+        let Some(&AttemptLocation(last_index)) = self.attempt_stack.last() else {
+            return Ok(None);
+        };
+        let Some(last_entry) = board.get_cell_index(last_index) else {
+            return Err(StepError::InvalidBoard);
+        };
+
+        self.attempt_stack.pop();
+        match last_entry.successor() {
+            Some(next_entry) => {
+                board.set_cell_index(last_index, Some(next_entry));
+                self.attempt_stack.push(AttemptLocation(last_index));
+                self.backtracking = false;
+            }
+            None => {
+                board.set_cell_index(last_index, None);
+                self.backtracking = true;
+            }
+        }
+        Ok(Some((last_index, last_entry)))
+    }
+
+    /// Step the solver up to `n` times.
+    ///
+    /// This is the same as calling [`Solver::step`] in a loop, except that the loop stops early as
+    /// soon as the solver is finished or an error occurs. The outcome of the last step is
+    /// returned. If `n` is zero, no steps are taken and [`None`] is returned.
+    pub fn step_n(
+        &mut self,
+        board: &mut Board,
+        n: usize,
+    ) -> Result<Option<StepOutcome>, StepError> {
+        let mut last = None;
+        for _ in 0..n {
+            let outcome = self.step(board)?;
+            last = Some(outcome);
+            if outcome.is_finished() {
+                break;
+            }
+        }
+        Ok(last)
+    }
+
+    /// Step the solver repeatedly until the time budget runs out.
+    ///
+    /// This is useful for the UI, since it lets the solver use up whatever time is left over in a
+    /// frame without the caller having to guess how many steps fit into it. The solver always
+    /// takes at least one step, even if the budget is zero. The loop stops early as soon as the
+    /// solver is finished or an error occurs, and the outcome of the last step is returned.
+    pub fn step_for(
+        &mut self,
+        board: &mut Board,
+        budget: Duration,
+    ) -> Result<StepOutcome, StepError> {
+        let start = Instant::now();
+        loop {
+            let outcome = self.step(board)?;
+            if outcome.is_finished() || start.elapsed() >= budget {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+/// Works out how many steps to take each frame so that the solver keeps a steady number of steps
+/// per second, however fast the frames are drawn.
+///
+/// Frames rarely line up with whole steps, so the part of a step left over from each frame is
+/// carried on to the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StepPacer {
+    owed: f64,
+}
+
+impl StepPacer {
+    /// Create a pacer which doesn't owe any steps yet.
+    pub const fn new() -> StepPacer {
+        StepPacer { owed: 0.0 }
+    }
+
+    /// Get the number of steps to take in a frame which comes `elapsed` after the last one, at a
+    /// rate of `per_second` steps per second.
+    ///
+    /// No more than `limit` steps are taken in one frame. Anything over that is dropped rather
+    /// than saved for later, so that a slow frame doesn't lead to an even slower one.
+    pub fn steps(&mut self, elapsed: Duration, per_second: usize, limit: usize) -> usize {
+        self.owed += elapsed.as_secs_f64() * per_second as f64;
+        let steps = self.owed.floor();
+        if steps >= limit as f64 {
+            self.owed = 0.0;
+            return limit;
+        }
+        self.owed -= steps;
+        steps as usize
+    }
+
+    /// Forget about any part of a step left over, for when the solver stops.
+    pub fn reset(&mut self) {
+        self.owed = 0.0;
+    }
+}
+
+/// A snapshot of an in-progress animated solve.
+///
+/// The [`Solver`] on its own is meaningless without the board it is working on, so the two are
+/// saved together. The text format starts with a line holding the backtracking flag, a line
+/// holding the attempt stack, and a line listing the cells which are givens, followed by the board
+/// in the usual puzzle file format. For example:
+///
+/// ```text
+/// backtracking false
+/// attempts 0 1 5
+/// givens 4 5 7
+/// 7-- -48 -5-
+/// ...
+/// ```
+#[derive(Debug)]
+pub struct SolveState {
+    /// The solver, including its attempt stack and backtracking flag.
+    pub solver: Solver,
+
+    /// The board that the solver is working on.
+    pub board: Board,
+}
+
+impl SolveState {
+    /// Write the state like the [`Display`](std::fmt::Display) implementation does, except that
+    /// the board is written on a single line by [`Board::to_line`]. It is parsed back the same way.
+    pub fn to_line(&self) -> String {
+        format!("{}{}\n", self.header(), self.board.to_line())
+    }
+
+    /// Get the lines which come before the board, saying where the solver is up to.
+    fn header(&self) -> String {
+        let attempts = self
+            .solver
+            .attempt_stack
+            .iter()
+            .map(|AttemptLocation(index)| format!(" {index}"));
+        let givens = (0..81)
+            .filter(|&index| self.board.is_given(index))
+            .map(|index| format!(" {index}"));
+        format!(
+            "backtracking {}\nattempts{}\ngivens{}\n",
+            self.solver.backtracking,
+            attempts.collect::<String>(),
+            givens.collect::<String>()
+        )
+    }
+}
+
+impl std::fmt::Display for SolveState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.header(), self.board)
+    }
+}
+
+impl std::str::FromStr for SolveState {
+    type Err = ();
+
+    /// Parse a solve state written by the [`Display`](std::fmt::Display) implementation.
+    ///
+    /// Unlike boards, the header lines must be in exactly the right format. Any attempt location
+    /// or given that does not point at a filled cell of the board is rejected, since the solver
+    /// would not be able to resume from it.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.splitn(4, '\n');
+
+        let backtracking = match lines.next().map(str::trim) {
+            Some("backtracking true") => true,
+            Some("backtracking false") => false,
+            _ => return Err(()),
+        };
+
+        let attempts = lines.next().ok_or(())?.trim();
+        let attempts = attempts.strip_prefix("attempts").ok_or(())?;
+        let attempt_stack = attempts
+            .split_whitespace()
+            .map(|index| index.parse().map(AttemptLocation).map_err(|_| ()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let givens = lines.next().ok_or(())?.trim();
+        let givens = givens.strip_prefix("givens").ok_or(())?;
+        let givens = givens
+            .split_whitespace()
+            .map(|index| index.parse().map_err(|_| ()))
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        let mut board: Board = lines.next().unwrap_or("").parse()?;
+        let all_filled = attempt_stack
+            .iter()
+            .map(|&AttemptLocation(index)| index)
+            .chain(givens.iter().copied())
+            .all(|index| index < 81 && board.get_cell_index(index).is_some());
+        if !all_filled {
+            return Err(());
+        }
+        // Every digit in the board was read as a given, so the ones that the solver filled in are
+        // written back in to clear their flags.
+        for index in (0..81).filter(|index| !givens.contains(index)) {
+            board.set_cell_index(index, board.get_cell_index(index));
+        }
+
+        Ok(SolveState {
+            solver: Solver {
+                attempt_stack,
+                backtracking,
+                ..Solver::new()
+            },
+            board,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEDIUM: &str = include_str!("../boards/medium-1.txt");
+
+    #[test]
+    fn test_solve_fast() {
+        let board: Board = MEDIUM.parse().unwrap();
+        let expected = solve_copy(&board).unwrap().to_line();
+        assert_eq!(solve_fast(&board).unwrap().to_line(), expected);
+
+        // The cage has to hold an 8 and a 9, which only the cage's own rows make sure of.
+        let text = "
+            --- --- ---
+            --- --- ---
+            --- --- ---
+
+            --- --- ---
+            --- --- ---
+            --- --- ---
+
+            --- --- ---
+            --- --- ---
+            --- --- ---
+
+            cage: 17 r1c1 r1c2";
+        let board: Board = text.parse().unwrap();
+        let solution = solve_fast(&board).unwrap();
+        assert!(solution.is_valid());
+        let sum: usize = [0, 1]
+            .map(|index| solution.get_cell_index(index).unwrap() as usize + 1)
+            .iter()
+            .sum();
+        assert_eq!(sum, 17);
+    }
+
+    #[test]
+    fn test_solve_with_limits() {
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let limits = Limits {
+            max_nodes: Some(1),
+            max_time: None,
+        };
+        assert_eq!(
+            solve_with_limits(&mut board, limits),
+            SolveOutcome::LimitReached
+        );
+        assert_eq!(board.first_unfilled_index(), Some(0));
+
+        assert_eq!(
+            solve_with_limits(&mut board, Limits::NONE),
+            SolveOutcome::Solved
+        );
+        assert_eq!(board.first_unfilled_index(), None);
+        assert!(board.is_valid());
+    }
+
+    #[test]
+    fn test_solve_state_round_trip() {
+        let mut state = SolveState {
+            solver: Solver::new(),
+            board: MEDIUM.parse().unwrap(),
+        };
+        state.solver.step_n(&mut state.board, 50).unwrap();
+
+        let restored: SolveState = state.to_string().parse().unwrap();
+        assert_eq!(restored.to_string(), state.to_string());
+        let given = |index| state.board.is_given(index);
+        assert!((0..81).all(|index| restored.board.is_given(index) == given(index)));
+
+        let restored: SolveState = state.to_line().parse().unwrap();
+        assert_eq!(restored.to_string(), state.to_string());
+        assert!(
+            "backtracking maybe\nattempts\n"
+                .parse::<SolveState>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_is_dead_end() {
+        let board: Board = MEDIUM.parse().unwrap();
+        assert!(!is_dead_end(&board));
+
+        // The top left cell can see every digit somewhere in its row or column.
+        let mut board = Board::empty();
+        let blockers = [
+            (1, 1),
+            (2, 2),
+            (3, 3),
+            (4, 4),
+            (5, 5),
+            (9, 6),
+            (18, 7),
+            (27, 8),
+            (36, 9),
+        ];
+        for (index, number) in blockers {
+            board.set_cell_index(index, Some(Entry::try_from(number).unwrap()));
+        }
+        assert!(is_dead_end(&board));
+        assert_eq!(
+            solve_with_limits(&mut board, Limits::NONE),
+            SolveOutcome::Unsolvable
+        );
+    }
+
+    #[test]
+    fn test_solve_parallel() {
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let mut expected: Board = MEDIUM.parse().unwrap();
+        assert!(solve_parallel(&mut board));
+        assert!(solve(&mut expected));
+        assert_eq!(board.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_step_outcomes() {
+        let mut board = Board::empty();
+        board.set_cell_index(0, Some(Entry::One));
+        board.set_cell_index(1, Some(Entry::One));
+        assert_eq!(Solver::new().step(&mut board), Err(StepError::InvalidBoard));
+
+        let mut board = Board::empty();
+        let blockers = [
+            (1, 1),
+            (2, 2),
+            (3, 3),
+            (4, 4),
+            (5, 5),
+            (9, 6),
+            (18, 7),
+            (27, 8),
+            (36, 9),
+        ];
+        for (index, number) in blockers {
+            board.set_cell_index(index, Some(Entry::try_from(number).unwrap()));
+        }
+        assert_eq!(Solver::new().step(&mut board), Ok(StepOutcome::Unsolvable));
+
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let mut solver = Solver::new();
+        assert_eq!(
+            solver.step(&mut board),
+            Ok(StepOutcome::Placed {
+                index: 0,
+                entry: Entry::One
+            })
+        );
+        let outcome = solver.step(&mut board);
+        assert_eq!(
+            outcome,
+            Ok(StepOutcome::Rejected {
+                index: 0,
+                entry: Entry::One
+            })
+        );
+        assert_eq!(outcome.unwrap().changed_cell(), Some((0, Some(Entry::One))));
+        assert_eq!(
+            solver.step_n(&mut board, usize::MAX),
+            Ok(Some(StepOutcome::Solved))
+        );
+    }
+
+    #[test]
+    fn test_step_for() {
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let mut solver = Solver::with_log();
+        let outcome = solver.step_for(&mut board, Duration::ZERO).unwrap();
+        assert_eq!(
+            outcome,
+            StepOutcome::Placed {
+                index: 0,
+                entry: Entry::One
+            }
+        );
+        assert_eq!(solver.log().unwrap().len(), 1);
+
+        let outcome = solver.step_for(&mut board, Duration::from_secs(3600));
+        assert_eq!(outcome, Ok(StepOutcome::Solved));
+        assert!(board.is_valid() && (0..81).all(|index| board.get_cell_index(index).is_some()));
+    }
+
+    #[test]
+    fn test_solve_log() {
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let mut solver = Solver::with_log();
+        solver.step_n(&mut board, 2).unwrap();
+
+        let log = solver.log().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(
+            log.to_string(),
+            "placed 1 at r1c1\neliminated 1 at r1c1: clashes with r6c1 in column 1\n"
+        );
+    }
+
+    #[test]
+    fn test_logic_mode() {
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let mut solver = Solver::new();
+        solver.set_mode(StepMode::Logic);
+
+        let outcome = solver.step(&mut board).unwrap();
+        assert!(matches!(outcome, StepOutcome::Deduced { .. }));
+        assert!(solver.deduction().is_some());
+        while !solver.step(&mut board).unwrap().is_finished() {}
+        assert!(board.first_unfilled_index().is_none());
+        assert!(board.is_valid());
+        let stats = solver.stats();
+        assert!(stats.techniques.iter().sum::<u64>() > 0);
+        assert!(stats.depth <= stats.max_depth);
+    }
+
+    #[test]
+    fn test_step_back() {
+        let original: Board = MEDIUM.parse().unwrap();
+        let mut board = original.clone();
+        let mut solver = Solver::new();
+        let outcomes: Vec<_> = (0..200).map(|_| solver.step(&mut board).unwrap()).collect();
+        let after = board.to_string();
+        let backtracks = outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome, StepOutcome::Backtracked { .. }))
+            .count();
+        assert_eq!(solver.stats().steps, 200);
+        assert_eq!(solver.stats().backtracks, backtracks as u64);
+        assert!(solver.stats().validating <= solver.stats().elapsed);
+        assert!(solver.stats().max_depth > 0);
+
+        for _ in 0..200 {
+            assert!(solver.step_back(&mut board));
+        }
+        assert!(!solver.step_back(&mut board));
+        assert_eq!(solver.stats().steps, 0);
+        assert_eq!(solver.stats().backtracks, 0);
+        assert_eq!(solver.stats().depth, 0);
+        assert_eq!(board.to_string(), original.to_string());
+
+        for &outcome in &outcomes {
+            assert_eq!(solver.step(&mut board).unwrap(), outcome);
+        }
+        assert_eq!(board.to_string(), after);
+    }
+
+    #[test]
+    fn test_solve_seeded() {
+        let mut first = Board::empty();
+        let mut second = Board::empty();
+        assert!(solve_seeded(&mut first, Seed(7)));
+        assert!(solve_seeded(&mut second, Seed(7)));
+        assert!(first.is_valid());
+        assert_eq!(first.to_string(), second.to_string());
+
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let expected = solve_copy(&board).unwrap();
+        assert!(solve_seeded(&mut board, Seed(7)));
+        assert_eq!(board.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        let mut board: Board = MEDIUM.parse().unwrap();
+        let empty = (0..81)
+            .filter(|&index| board.get_cell_index(index).is_none())
+            .count();
+        let (outcome, stats) = solve_with_stats(&mut board, Limits::NONE);
+        assert_eq!(outcome, SolveOutcome::Solved);
+        assert_eq!(stats.max_depth, empty);
+        assert!(stats.decisions >= empty as u64);
+        assert!(stats.nodes >= stats.branches);
+        assert!(stats.branching_factor() >= 1.0);
+    }
+
+    #[test]
+    fn test_step_pacer() {
+        // The same speed takes the same number of steps in a second, whatever the frame rate.
+        for fps in [30, 60, 144] {
+            let mut pacer = StepPacer::new();
+            let frame = Duration::from_secs_f64(1.0 / fps as f64);
+            let steps: usize = (0..fps).map(|_| pacer.steps(frame, 100, 4096)).sum();
+            assert!((99..=100).contains(&steps), "{steps} steps at {fps} FPS");
+        }
+
+        let mut pacer = StepPacer::new();
+        assert_eq!(pacer.steps(Duration::from_secs(10), 1000, 4096), 4096);
+        assert_eq!(pacer.steps(Duration::ZERO, 1000, 4096), 0);
     }
 }
@@ -0,0 +1,148 @@
+//! Sound effects for the solver.
+//!
+//! The sounds are simple enough that they are made up on the spot instead of being loaded from
+//! files, so there is nothing extra to ship alongside the program. Each one is built as a WAV file
+//! in memory, which raylib then loads like any other.
+
+use raylib::audio::{RaylibAudio, Sound};
+use raylib::core::error::Error;
+
+/// The number of samples played every second.
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Something the solver did that has a sound to go with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Effect {
+    /// A short, high tick for a digit being placed.
+    Place,
+
+    /// A low thud for the solver taking back a guess.
+    Backtrack,
+
+    /// A rising chime for the board being solved.
+    Victory,
+}
+
+impl Effect {
+    /// Build the samples of the sound.
+    fn samples(self) -> Vec<i16> {
+        match self {
+            Self::Place => tone(1200.0, 0.03, 0.6),
+            Self::Backtrack => tone(90.0, 0.15, 0.8),
+            // C, E, G, and C again, an octave up.
+            Self::Victory => [523.25, 659.25, 783.99, 1046.5]
+                .into_iter()
+                .flat_map(|frequency| tone(frequency, 0.15, 0.5))
+                .collect(),
+        }
+    }
+}
+
+/// Build the samples of a sine wave which dies away over its length.
+///
+/// The wave starts at `volume`, which goes from zero to one, and fades out to silence by the end.
+fn tone(frequency: f32, seconds: f32, volume: f32) -> Vec<i16> {
+    let count = (SAMPLE_RATE as f32 * seconds) as usize;
+    (0..count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let fade = 1.0 - i as f32 / count as f32;
+            let sample = (2.0 * std::f32::consts::PI * frequency * t).sin() * volume * fade;
+            (sample * f32::from(i16::MAX)) as i16
+        })
+        .collect()
+}
+
+/// Wrap up the samples as a mono, 16 bit WAV file.
+fn to_wav(samples: &[i16]) -> Vec<u8> {
+    let data_size = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + samples.len() * 2);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_size).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    // Uncompressed samples, one channel.
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_size.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+/// The sound effects, ready to be played.
+pub struct Sounds<'aud> {
+    place: Sound<'aud>,
+    backtrack: Sound<'aud>,
+    victory: Sound<'aud>,
+    muted: bool,
+}
+
+impl<'aud> Sounds<'aud> {
+    /// Build every sound effect and load it into the audio device.
+    pub fn load(audio: &'aud RaylibAudio) -> Result<Sounds<'aud>, Error> {
+        let load = |effect: Effect| {
+            let wave = audio.new_wave_from_memory(".wav", &to_wav(&effect.samples()))?;
+            audio.new_sound_from_wave(&wave)
+        };
+        Ok(Sounds {
+            place: load(Effect::Place)?,
+            backtrack: load(Effect::Backtrack)?,
+            victory: load(Effect::Victory)?,
+            muted: false,
+        })
+    }
+
+    /// Check whether the sounds are muted.
+    pub const fn muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Mute or unmute the sounds.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Play the sound for an effect, unless the sounds are muted.
+    ///
+    /// Placing digits happens so often that a tick which is still playing is left to finish
+    /// instead of starting over, or all that would be heard is a buzz.
+    pub fn play(&self, effect: Effect) {
+        if self.muted {
+            return;
+        }
+
+        let sound = match effect {
+            Effect::Place => &self.place,
+            Effect::Backtrack => &self.backtrack,
+            Effect::Victory => &self.victory,
+        };
+        if effect != Effect::Place || !sound.is_playing() {
+            sound.play();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_wav() {
+        let samples = Effect::Place.samples();
+        let wav = to_wav(&samples);
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(wav[40..44], ((samples.len() * 2) as u32).to_le_bytes());
+    }
+}
@@ -0,0 +1,211 @@
+//! A frontend which shows a puzzle being solved in the terminal, for SSH sessions and machines
+//! without a display.
+//!
+//! The board is drawn with ratatui, but the solving itself is the same as in the window: a
+//! [`Solver`] is stepped as many times a frame as a [`StepPacer`] says, and the status line goes
+//! through the same [`SolvingStatus`] states.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+
+use crate::board::Board;
+use crate::graphics::SolvingStatus;
+use crate::locale::Message;
+use crate::settings::{self, MAX_STEPS_PER_SECOND, MIN_STEPS_PER_SECOND};
+use crate::solver::{Solver, StepError, StepOutcome, StepPacer};
+
+/// How long to wait for a key press before going on to the next frame.
+const FRAME_TIME: Duration = Duration::from_millis(16);
+
+/// The line drawn above, between, and below the rows of boxes.
+const BOX_BORDER: &str = "+-------+-------+-------+";
+
+/// A puzzle being solved in the terminal.
+struct App {
+    board: Board,
+    solver: Solver,
+    status: SolvingStatus,
+    steps_per_second: usize,
+    pacer: StepPacer,
+
+    /// The cell which the last step changed, so that it can be picked out on the board.
+    last_changed: Option<usize>,
+}
+
+impl App {
+    fn new(board: Board, steps_per_second: usize) -> App {
+        let status = if board.is_valid() {
+            SolvingStatus::Stopped
+        } else {
+            SolvingStatus::Invalid
+        };
+        App {
+            board,
+            solver: Solver::new(),
+            status,
+            steps_per_second,
+            pacer: StepPacer::new(),
+            last_changed: None,
+        }
+    }
+
+    /// Draw frames and handle key presses until the user quits.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let mut last_frame = Instant::now();
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(FRAME_TIME)?
+                && let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char(' ') => self.status = self.status.toggled(),
+                    KeyCode::Char('s') | KeyCode::Right
+                        if !matches!(self.status, SolvingStatus::Going) =>
+                    {
+                        self.step(1);
+                    }
+                    KeyCode::Up => {
+                        self.steps_per_second =
+                            std::cmp::min(self.steps_per_second * 2, MAX_STEPS_PER_SECOND);
+                    }
+                    KeyCode::Down => {
+                        self.steps_per_second =
+                            std::cmp::max(self.steps_per_second / 2, MIN_STEPS_PER_SECOND);
+                    }
+                    _ => {}
+                }
+            }
+
+            let elapsed = last_frame.elapsed();
+            last_frame = Instant::now();
+            if matches!(self.status, SolvingStatus::Going) {
+                let steps = self.pacer.steps(
+                    elapsed,
+                    self.steps_per_second,
+                    settings::MAX_STEPS_PER_FRAME,
+                );
+                self.step(steps);
+            } else {
+                // Time spent stopped doesn't count towards the next steps.
+                self.pacer.reset();
+            }
+        }
+    }
+
+    /// Step the solver up to `n` times, and update the status to match.
+    fn step(&mut self, n: usize) {
+        if self.status.is_finished() {
+            return;
+        }
+
+        match self.solver.step_n(&mut self.board, n) {
+            Ok(Some(StepOutcome::Solved)) => self.status = SolvingStatus::Solved,
+            Ok(Some(StepOutcome::Unsolvable)) => self.status = SolvingStatus::Unsolvable,
+            Ok(Some(outcome)) => {
+                self.last_changed = outcome.changed_cell().map(|(index, _)| index);
+                if let SolvingStatus::Stopped | SolvingStatus::Hint(_) = self.status {
+                    self.status = SolvingStatus::Paused;
+                }
+            }
+            Ok(None) => {}
+            Err(StepError::InvalidBoard) => self.status = SolvingStatus::Invalid,
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [board, status, stats, speed, help] = Layout::vertical([
+            Constraint::Length(13),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        frame.render_widget(Paragraph::new(self.board_lines()), board);
+
+        let color = match self.status {
+            SolvingStatus::Going => Color::Green,
+            SolvingStatus::Stopped => Color::Red,
+            SolvingStatus::Paused | SolvingStatus::Solved | SolvingStatus::Hint(_) => Color::Yellow,
+            SolvingStatus::Unsolvable => Color::Magenta,
+            SolvingStatus::Invalid => Color::Gray,
+        };
+        let message = self.status.message().to_string();
+        frame.render_widget(Span::styled(message, Style::new().fg(color)), status);
+
+        let solver_stats = self.solver.stats();
+        let message = Message::StepStats {
+            steps: solver_stats.steps,
+            backtracks: solver_stats.backtracks,
+            seconds: solver_stats.elapsed.as_secs_f32(),
+        };
+        frame.render_widget(Span::raw(message.to_string()), stats);
+
+        let message = Message::StepsPerSecond(self.steps_per_second);
+        frame.render_widget(Span::raw(message.to_string()), speed);
+
+        let message = Message::TerminalHelp.to_string();
+        frame.render_widget(
+            Span::styled(message, Style::new().fg(Color::DarkGray)),
+            help,
+        );
+    }
+
+    /// Lay out the board the way the puzzle files do, with the givens in bold and the cell which
+    /// the solver just changed highlighted.
+    fn board_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::raw(BOX_BORDER)];
+        for row in 0..9 {
+            let mut spans = vec![Span::raw("|")];
+            for column in 0..9 {
+                let index = row * 9 + column;
+                let style = if Some(index) == self.last_changed {
+                    Style::new().fg(Color::Black).bg(Color::Yellow)
+                } else if self.board.is_given(index) {
+                    Style::new().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::new().fg(Color::Cyan)
+                };
+                let text = match self.board.get_cell_index(index) {
+                    Some(entry) => entry.to_string(),
+                    None => ".".to_string(),
+                };
+
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(text, style));
+                if column % 3 == 2 {
+                    spans.push(Span::raw(" |"));
+                }
+            }
+            lines.push(Line::from(spans));
+            if row % 3 == 2 {
+                lines.push(Line::raw(BOX_BORDER));
+            }
+        }
+        lines
+    }
+}
+
+/// Take over the terminal and show the board being solved, until the user quits.
+///
+/// The solver starts paused. Space starts and pauses it, S or the right arrow takes a single step
+/// while it is paused, the up and down arrows change the speed, and Q or escape quits. The
+/// terminal is put back the way it was before returning, even if drawing fails.
+pub fn run(board: Board, steps_per_second: usize) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = App::new(board, steps_per_second).run(&mut terminal);
+    ratatui::restore();
+    result
+}
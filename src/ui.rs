@@ -1,7 +1,15 @@
 //! This module contains tools related to UI widgets.
 
-use raylib::drawing::RaylibDrawHandle;
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+use raylib::color::Color;
+use raylib::consts::{GamepadAxis, GamepadButton, KeyboardKey, MouseButton};
+use raylib::core::error::Error;
+use raylib::drawing::{RaylibDraw, RaylibDrawHandle};
 use raylib::math::{Rectangle, Vector2};
+use raylib::text::{RaylibFont, WeakFont};
+use raylib::{RaylibHandle, RaylibThread};
 
 use crate::geometry;
 
@@ -9,17 +17,739 @@ pub const LINE_WIDTH: f32 = 10.0;
 pub const FONT_SIZE: f32 = 32.0;
 pub const FONT_SPACING: f32 = 1.0;
 
+/// The width of the lines between the cells in each box, next to the [`LINE_WIDTH`] of the lines
+/// between boxes.
+pub const THIN_LINE_WIDTH: f32 = 2.0;
+
+/// The font that ships with the program, DejaVu Sans. Its license is in
+/// `assets/LICENSE-DejaVu.txt`.
+const BUNDLED_FONT: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+
+/// How long the mouse has to rest on a widget before its tooltip appears.
+const TOOLTIP_DELAY: Duration = Duration::from_millis(600);
+
+thread_local! {
+    /// The font loaded by [`load_font`], and the size that text is drawn at. Raylib can only be
+    /// used from the main thread anyway, so there is no point sharing this between threads.
+    ///
+    /// The font is held weakly so that it is never unloaded. Unloading it after the window has
+    /// closed would crash, and it is needed right up until then.
+    static FONT: RefCell<Option<(WeakFont, f32)>> = const { RefCell::new(None) };
+
+    /// How many times bigger than normal everything is drawn, set by [`set_scale`].
+    static SCALE: Cell<f32> = const { Cell::new(1.0) };
+
+    /// The tooltips offered this frame and what the mouse has been doing, for [`draw_tooltip`].
+    static TOOLTIP: RefCell<TooltipState> = const {
+        RefCell::new(TooltipState {
+            offered: None,
+            shown: None,
+            resting: None,
+        })
+    };
+}
+
+/// What [`draw_tooltip`] needs to remember from one frame to the next.
+struct TooltipState {
+    /// The tooltip of the widget under the mouse this frame.
+    offered: Option<String>,
+
+    /// The tooltip that was offered last frame.
+    shown: Option<String>,
+
+    /// Where the mouse came to rest, and when.
+    resting: Option<(Vector2, Instant)>,
+}
+
+/// Offer a tooltip for the widget under the mouse.
+///
+/// Widgets call this from [`Widget::update`] while the mouse is over them. The tooltip is only
+/// drawn once the mouse has stayed put for a moment, by [`draw_tooltip`] at the end of the frame.
+pub fn offer_tooltip(text: &str) {
+    TOOLTIP.with_borrow_mut(|tooltip| tooltip.offered = Some(text.to_string()));
+}
+
+/// Draw the tooltip offered this frame, if the mouse has rested on its widget for long enough.
+///
+/// Tooltips go on top of everything else, so this should be called last thing in every frame,
+/// whether or not anything offered a tooltip. It goes just below and to the right of the mouse,
+/// but is kept inside the window.
+pub fn draw_tooltip(d: &mut RaylibDrawHandle, input: &InputState) {
+    let (text, since) = TOOLTIP.with_borrow_mut(|tooltip| {
+        let offered = tooltip.offered.take();
+        let moved = match tooltip.resting {
+            Some((position, _)) => position != input.mouse_position,
+            None => true,
+        };
+        // Clicking or moving on to another widget starts the wait over again.
+        if moved || input.mouse_pressed || offered != tooltip.shown {
+            tooltip.resting = Some((input.mouse_position, Instant::now()));
+        }
+        tooltip.shown.clone_from(&offered);
+        (offered, tooltip.resting.map(|(_, since)| since))
+    });
+    let (Some(text), Some(since)) = (text, since) else {
+        return;
+    };
+    if since.elapsed() < TOOLTIP_DELAY {
+        return;
+    }
+
+    let size = font_size() * 0.5;
+    let dimensions = measure_text(d, &text, size);
+    let padding = size * 0.4;
+    let width = dimensions.x + padding * 2.0;
+    let height = dimensions.y + padding * 2.0;
+    let rect = Rectangle {
+        x: f32::min(
+            input.mouse_position.x + 16.0 * scale(),
+            d.get_screen_width() as f32 - width,
+        )
+        .max(0.0),
+        y: f32::min(
+            input.mouse_position.y + 16.0 * scale(),
+            d.get_screen_height() as f32 - height,
+        )
+        .max(0.0),
+        width,
+        height,
+    };
+    d.draw_rectangle_rec(rect, Color::RAYWHITE);
+    d.draw_rectangle_lines_ex(rect, 1.0, Color::DARKGRAY);
+    draw_text_centered(d, &text, rect, size, Color::BLACK);
+}
+
+/// Get the scale factor of the monitor that the window is on, like 2 for most 4K screens.
+///
+/// On macOS, raylib works in points rather than pixels, so the window is already the right size
+/// on a Retina display and this is always 1. Everywhere else, sizes are in pixels and have to be
+/// scaled up by this much to look the same as on an ordinary monitor.
+pub fn monitor_scale(rl: &RaylibHandle) -> f32 {
+    if cfg!(target_os = "macos") {
+        return 1.0;
+    }
+    let scale = rl.get_window_scale_dpi().x;
+    if scale.is_finite() && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
+}
+
+/// Draw everything `scale` times bigger than normal from now on, to suit the monitor.
+///
+/// This scales the gridlines and the text, and the layout should use [`scale`] for anything else
+/// with a fixed size. The font has to be loaded again afterwards with [`load_font`], or the text
+/// stays the old size.
+pub fn set_scale(scale: f32) {
+    SCALE.set(scale);
+}
+
+/// Get how many times bigger than normal everything is drawn.
+pub fn scale() -> f32 {
+    SCALE.get()
+}
+
+/// Get the width of the lines between the boxes of the board, which is [`LINE_WIDTH`] scaled to
+/// suit the monitor.
+pub fn line_width() -> f32 {
+    LINE_WIDTH * scale()
+}
+
+/// Get the width of the lines between the cells in each box of the board, which is
+/// [`THIN_LINE_WIDTH`] scaled to suit the monitor.
+pub fn thin_line_width() -> f32 {
+    THIN_LINE_WIDTH * scale()
+}
+
+/// Load the bundled font, and draw text at the supplied size from now on. The size is scaled to
+/// suit the monitor, as set by [`set_scale`].
+///
+/// Until this is called, text is drawn in raylib's built in bitmap font at [`FONT_SIZE`]. That
+/// font gets blurry when it is scaled up, which this one doesn't.
+///
+/// # Errors
+///
+/// If raylib can't load the font, the error is returned and text keeps using the old font.
+pub fn load_font(rl: &mut RaylibHandle, thread: &RaylibThread, size: f32) -> Result<(), Error> {
+    // The glyphs are rendered at twice the size they are usually drawn at, so that they stay sharp
+    // on big boards.
+    let size = size * scale();
+    let glyph_size = (size * 2.0) as i32;
+    // Raylib only renders plain ASCII unless it is told otherwise, which isn't enough for the
+    // accented letters in translations.
+    let glyphs: String = (' '..='~')
+        .chain('\u{a0}'..='ÿ')
+        .chain(['Œ', 'œ'])
+        .collect();
+    let font = rl.load_font_from_memory(thread, ".ttf", BUNDLED_FONT, glyph_size, Some(&glyphs))?;
+    FONT.set(Some((font.make_weak(), size)));
+    Ok(())
+}
+
+/// Get the size that text is drawn at, scaled to suit the monitor.
+pub fn font_size() -> f32 {
+    FONT.with_borrow(|font| font.as_ref().map_or(FONT_SIZE * scale(), |&(_, size)| size))
+}
+
+/// Measure how much room some text takes up at the supplied size.
+pub fn measure_text(rl: &RaylibHandle, text: &str, size: f32) -> Vector2 {
+    FONT.with_borrow(|font| match font {
+        Some((font, _)) => font.measure_text(text, size, FONT_SPACING),
+        None => rl.get_font_default().measure_text(text, size, FONT_SPACING),
+    })
+}
+
+/// Draw some text at the supplied size, centered in a rectangle. The text can be drawn straight to
+/// the screen, or into a texture.
+pub fn draw_text_centered<D>(d: &mut D, text: &str, rect: Rectangle, size: f32, color: Color)
+where
+    D: RaylibDraw + std::ops::Deref<Target = RaylibHandle>,
+{
+    let dimensions = measure_text(d, text, size);
+    let position = Vector2 {
+        x: rect.x + (rect.width - dimensions.x) / 2.0,
+        y: rect.y + (rect.height - dimensions.y) / 2.0,
+    };
+    FONT.with_borrow(|font| match font {
+        Some((font, _)) => d.draw_text_ex(font, text, position, size, FONT_SPACING, color),
+        None => {
+            let font = d.get_font_default();
+            d.draw_text_ex(font, text, position, size, FONT_SPACING, color);
+        }
+    });
+}
+
 /// Represents a UI widget.
 ///
 /// The discrete parts of this application can be split up into logical widgets, and this trait
 /// allows for manipulation of abstract widgets.
 pub trait Widget {
+    /// Respond to the mouse and keyboard.
+    ///
+    /// This is called once per frame, before drawing, with the rectangle that the widget will be
+    /// drawn into. Widgets which don't care about input can leave this alone.
+    fn update(&mut self, _input: &InputState, _rect: Rectangle) {}
+
     /// Draw a widget.
     ///
     /// This is the core method for displaying widgets on the screen. Since widgets should not be
     /// aware of their position and size, the draw method takes a rectangle into which the widget
     /// should fit. This way, the main function can manage the placement and size of widgets.
     fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle);
+
+    /// Get the size that the widget would like to be drawn at, which the layout uses to share out
+    /// the window. A width or height of zero means the widget has no preference that way, and
+    /// takes whatever room is left. By default, this is the same as [`Widget::min_size`].
+    fn preferred_size(&self) -> Vector2 {
+        self.min_size()
+    }
+
+    /// Get the smallest size that the widget can be drawn at without its contents spilling out.
+    /// Widgets which can be squashed down to nothing can leave this alone.
+    fn min_size(&self) -> Vector2 {
+        Vector2::zero()
+    }
+}
+
+/// A snapshot of the mouse, keyboard and gamepad, taken once per frame and handed to every widget.
+///
+/// Raylib can be asked about input directly, but going through this means that widgets don't need
+/// the whole [`RaylibHandle`], and that they can be fed made up input in tests.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InputState {
+    /// Where the mouse is, in screen space.
+    pub mouse_position: Vector2,
+
+    /// Whether the left mouse button went down this frame.
+    pub mouse_pressed: bool,
+
+    /// Whether the left mouse button came back up this frame.
+    pub mouse_released: bool,
+
+    /// Whether the left mouse button is being held down.
+    pub mouse_down: bool,
+
+    /// Whether either Shift key is being held down.
+    pub shift: bool,
+
+    /// Whether either Control key is being held down.
+    pub ctrl: bool,
+
+    /// The keys which were pressed this frame, in the order they were pressed.
+    pub keys_pressed: Vec<KeyboardKey>,
+
+    /// The buttons on the first gamepad which were pressed this frame.
+    pub buttons_pressed: Vec<GamepadButton>,
+
+    /// The buttons on the first gamepad which were let go of this frame.
+    pub buttons_released: Vec<GamepadButton>,
+
+    /// How far the left stick of the first gamepad is pushed, from -1 to 1 in each direction, with
+    /// up being negative like the rest of screen space.
+    pub left_stick: Vector2,
+}
+
+impl InputState {
+    /// Take a snapshot of the input for this frame.
+    ///
+    /// This empties raylib's queue of pressed keys, so it should only be called once per frame.
+    pub fn capture(rl: &mut RaylibHandle) -> InputState {
+        let keys_pressed = std::iter::from_fn(|| rl.get_key_pressed()).collect();
+        // Only the first gamepad is used, since there is only one board to play on.
+        let gamepad = rl.is_gamepad_available(0);
+        let buttons = |happened: fn(&RaylibHandle, i32, GamepadButton) -> bool| {
+            GAMEPAD_BUTTONS
+                .into_iter()
+                .filter(|&button| gamepad && happened(rl, 0, button))
+                .collect()
+        };
+        let buttons_pressed = buttons(RaylibHandle::is_gamepad_button_pressed);
+        let buttons_released = buttons(RaylibHandle::is_gamepad_button_released);
+        let left_stick = if gamepad {
+            Vector2::new(
+                rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+                rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+            )
+        } else {
+            Vector2::zero()
+        };
+        InputState {
+            mouse_position: rl.get_mouse_position(),
+            mouse_pressed: rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT),
+            mouse_released: rl.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT),
+            mouse_down: rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT),
+            shift: rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT),
+            ctrl: rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL),
+            keys_pressed,
+            buttons_pressed,
+            buttons_released,
+            left_stick,
+        }
+    }
+
+    /// Check whether a key was pressed this frame.
+    pub fn key_pressed(&self, key: KeyboardKey) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Check whether a gamepad button was pressed this frame.
+    pub fn button_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    /// Check whether a gamepad button was let go of this frame.
+    pub fn button_released(&self, button: GamepadButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+}
+
+/// Every button on a gamepad that raylib knows the name of.
+const GAMEPAD_BUTTONS: [GamepadButton; 17] = [
+    GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP,
+    GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT,
+    GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN,
+    GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+    GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+    GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2,
+    GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT,
+    GamepadButton::GAMEPAD_BUTTON_MIDDLE,
+    GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT,
+    GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB,
+    GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB,
+];
+
+/// A button with a text label, which can be clicked with the left mouse button.
+///
+/// Like a real button, a click only counts if the mouse is pressed and then released while it is
+/// over the button, so the user can change their mind by dragging away before letting go. The
+/// button has to be updated once per frame with [`Widget::update`] to notice clicks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Button {
+    label: String,
+
+    /// What the button does, shown when the mouse rests on it.
+    tooltip: Option<String>,
+    hovered: bool,
+    pressed: bool,
+    clicked: bool,
+}
+
+impl Button {
+    /// Create a button with the supplied label.
+    pub fn new(label: &str) -> Button {
+        Button {
+            label: label.to_string(),
+            tooltip: None,
+            hovered: false,
+            pressed: false,
+            clicked: false,
+        }
+    }
+
+    /// Change the text on the button.
+    pub fn set_label(&mut self, label: &str) {
+        if self.label != label {
+            self.label = label.to_string();
+        }
+    }
+
+    /// Change the tooltip shown when the mouse rests on the button. This should say what the
+    /// button does, and its keyboard shortcut if it has one.
+    pub fn set_tooltip(&mut self, tooltip: &str) {
+        if self.tooltip.as_deref() != Some(tooltip) {
+            self.tooltip = Some(tooltip.to_string());
+        }
+    }
+
+    /// Check whether the button was clicked during the last [`Widget::update`].
+    pub const fn clicked(&self) -> bool {
+        self.clicked
+    }
+}
+
+impl Widget for Button {
+    fn update(&mut self, input: &InputState, rect: Rectangle) {
+        self.hovered = geometry::rect_contains_point(rect, input.mouse_position);
+        self.clicked = false;
+        if self.hovered
+            && let Some(tooltip) = &self.tooltip
+        {
+            offer_tooltip(tooltip);
+        }
+        if self.hovered && input.mouse_pressed {
+            self.pressed = true;
+        }
+        if input.mouse_released {
+            self.clicked = self.pressed && self.hovered;
+            self.pressed = false;
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let fill = match (self.pressed, self.hovered) {
+            (true, true) => Color::GRAY,
+            (false, true) => Color::LIGHTGRAY,
+            _ => Color::RAYWHITE,
+        };
+        d.draw_rectangle_rec(rect, fill);
+        d.draw_rectangle_lines_ex(rect, 2.0, Color::DARKGRAY);
+
+        draw_text_centered(d, &self.label, rect, font_size() * 0.6, Color::BLACK);
+    }
+
+    fn preferred_size(&self) -> Vector2 {
+        Vector2::new(0.0, font_size() * 1.25)
+    }
+
+    fn min_size(&self) -> Vector2 {
+        Vector2::new(0.0, font_size() * 0.6)
+    }
+}
+
+/// A row of tabs, one of which is selected. Clicking on a tab asks for it to be selected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TabBar {
+    titles: Vec<String>,
+    selected: usize,
+    clicked: Option<usize>,
+}
+
+impl TabBar {
+    /// Create a tab bar with no tabs.
+    pub const fn new() -> TabBar {
+        TabBar {
+            titles: Vec::new(),
+            selected: 0,
+            clicked: None,
+        }
+    }
+
+    /// Replace the tabs with ones carrying the supplied titles, and pick the selected one.
+    pub fn set_tabs(&mut self, titles: Vec<String>, selected: usize) {
+        self.titles = titles;
+        self.selected = selected;
+    }
+
+    /// Get the index of the tab that was clicked during the last [`Widget::update`], if any.
+    pub const fn clicked(&self) -> Option<usize> {
+        self.clicked
+    }
+
+    /// Get the rectangle of each tab, with the tabs sharing the width of the bar equally.
+    fn tab_rects(&self, rect: Rectangle) -> Vec<Rectangle> {
+        let width = rect.width / self.titles.len().max(1) as f32;
+        (0..self.titles.len())
+            .map(|i| Rectangle::new(rect.x + i as f32 * width, rect.y, width, rect.height))
+            .collect()
+    }
+}
+
+impl Widget for TabBar {
+    fn update(&mut self, input: &InputState, rect: Rectangle) {
+        self.clicked = None;
+        if input.mouse_pressed {
+            self.clicked = self
+                .tab_rects(rect)
+                .into_iter()
+                .position(|tab| geometry::rect_contains_point(tab, input.mouse_position));
+        }
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        d.draw_rectangle_rec(rect, Color::LIGHTGRAY);
+        for (i, (tab, title)) in self
+            .tab_rects(rect)
+            .into_iter()
+            .zip(&self.titles)
+            .enumerate()
+        {
+            if i == self.selected {
+                d.draw_rectangle_rec(tab, Color::RAYWHITE);
+            }
+            d.draw_rectangle_lines_ex(tab, 1.0, Color::DARKGRAY);
+            draw_text_centered(d, title, tab, font_size() * 0.5, Color::BLACK);
+        }
+    }
+
+    fn preferred_size(&self) -> Vector2 {
+        Vector2::new(0.0, font_size())
+    }
+
+    fn min_size(&self) -> Vector2 {
+        Vector2::new(0.0, font_size() * 0.5)
+    }
+}
+
+/// One of the menus in a [`MenuBar`], with the items that drop down from it. Each item has a
+/// label and a value, which is handed back when the item is picked.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Menu<T> {
+    /// The text on the menu bar.
+    pub title: String,
+
+    /// The label and value of each item, from top to bottom.
+    pub items: Vec<(String, T)>,
+}
+
+/// A bar of menus along the top of the window.
+///
+/// Clicking on the title of a menu drops it down, and clicking on one of its items picks it. Any
+/// click closes the menu again. The dropped down menu hangs over whatever is below the bar, so the
+/// bar should be drawn after everything else.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MenuBar<T> {
+    menus: Vec<Menu<T>>,
+    open: Option<usize>,
+    hovered: Option<usize>,
+    clicked: Option<T>,
+}
+
+impl<T: Copy> MenuBar<T> {
+    /// Create a menu bar holding the supplied menus, from left to right.
+    pub const fn new(menus: Vec<Menu<T>>) -> MenuBar<T> {
+        MenuBar {
+            menus,
+            open: None,
+            hovered: None,
+            clicked: None,
+        }
+    }
+
+    /// Check whether one of the menus is dropped down.
+    ///
+    /// While a menu is open, clicks belong to it, so widgets underneath it should ignore them.
+    pub const fn is_open(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Get the value of the item that was picked during the last [`Widget::update`], if any.
+    pub const fn clicked(&self) -> Option<T> {
+        self.clicked
+    }
+
+    /// Get the rectangle of each menu's title on the bar.
+    fn title_rects(&self, rect: Rectangle) -> Vec<Rectangle> {
+        let width = font_size() * 3.0;
+        (0..self.menus.len())
+            .map(|i| Rectangle::new(rect.x + i as f32 * width, rect.y, width, rect.height))
+            .collect()
+    }
+
+    /// Get the rectangle of each item in the open menu, stacked up underneath its title.
+    fn item_rects(&self, rect: Rectangle) -> Vec<Rectangle> {
+        let Some(open) = self.open else {
+            return Vec::new();
+        };
+        let title = self.title_rects(rect)[open];
+        let width = font_size() * 9.0;
+        (0..self.menus[open].items.len())
+            .map(|i| {
+                let y = title.y + (i + 1) as f32 * title.height;
+                Rectangle::new(title.x, y, width, title.height)
+            })
+            .collect()
+    }
+}
+
+impl<T: Copy> Widget for MenuBar<T> {
+    fn update(&mut self, input: &InputState, rect: Rectangle) {
+        let contains_mouse =
+            |rect: Rectangle| geometry::rect_contains_point(rect, input.mouse_position);
+        self.clicked = None;
+        self.hovered = self.item_rects(rect).into_iter().position(contains_mouse);
+        if !input.mouse_pressed {
+            return;
+        }
+
+        if let Some(open) = self.open
+            && let Some(item) = self.hovered
+        {
+            self.clicked = Some(self.menus[open].items[item].1);
+        }
+        let title = self.title_rects(rect).into_iter().position(contains_mouse);
+        self.open = match title {
+            Some(title) if self.open != Some(title) => Some(title),
+            _ => None,
+        };
+        self.hovered = None;
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, rect: Rectangle) {
+        let font_size = font_size() * 0.5;
+        d.draw_rectangle_rec(rect, Color::LIGHTGRAY);
+        for (i, (menu, title)) in self.menus.iter().zip(self.title_rects(rect)).enumerate() {
+            if self.open == Some(i) {
+                d.draw_rectangle_rec(title, Color::RAYWHITE);
+            }
+            draw_text_centered(d, &menu.title, title, font_size, Color::BLACK);
+        }
+
+        let Some(open) = self.open else {
+            return;
+        };
+        for (i, ((label, _), item)) in self.menus[open]
+            .items
+            .iter()
+            .zip(self.item_rects(rect))
+            .enumerate()
+        {
+            let fill = if self.hovered == Some(i) {
+                Color::LIGHTGRAY
+            } else {
+                Color::RAYWHITE
+            };
+            d.draw_rectangle_rec(item, fill);
+            d.draw_rectangle_lines_ex(item, 1.0, Color::DARKGRAY);
+            draw_text_centered(d, label, item, font_size, Color::BLACK);
+        }
+    }
+
+    fn preferred_size(&self) -> Vector2 {
+        Vector2::new(0.0, font_size() * 0.875)
+    }
+
+    fn min_size(&self) -> Vector2 {
+        Vector2::new(0.0, font_size() * 0.5)
+    }
+}
+
+/// Which way a rectangle is cut up by [`split`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The parts sit side by side, from left to right.
+    Horizontal,
+
+    /// The parts are stacked on top of each other, from top to bottom.
+    Vertical,
+}
+
+/// How much room one part of a [`split`] takes up along the direction of the split.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Size {
+    /// Exactly this many pixels.
+    Fixed(f32),
+
+    /// A share of whatever the fixed parts leave over. Each flexible part gets room in proportion
+    /// to its weight, so two parts with a weight of 1 get half each.
+    Flexible(f32),
+}
+
+impl Size {
+    /// Get a fixed size that fits a widget along the supplied direction: its preferred size, or
+    /// its minimum size if that is bigger.
+    pub fn of(widget: &dyn Widget, direction: Direction) -> Size {
+        let (preferred, min) = (widget.preferred_size(), widget.min_size());
+        Size::Fixed(match direction {
+            Direction::Horizontal => f32::max(preferred.x, min.x),
+            Direction::Vertical => f32::max(preferred.y, min.y),
+        })
+    }
+}
+
+/// Cut a rectangle into parts along one direction, and get the rectangle for each part.
+///
+/// The fixed parts are given their room first, and the flexible parts share out the rest. If
+/// the fixed parts don't fit, the flexible parts are squashed down to nothing and the fixed parts
+/// spill out of the end of the rectangle.
+///
+/// # Examples
+///
+/// ```
+/// use raylib::math::Rectangle;
+/// use sudoku_solver::ui::{self, Direction, Size};
+///
+/// let window = Rectangle::new(0.0, 0.0, 300.0, 200.0);
+/// let sizes = [Size::Flexible(1.0), Size::Fixed(50.0)];
+/// let [top, bottom] = ui::split(window, Direction::Vertical, sizes);
+/// assert_eq!(top, Rectangle::new(0.0, 0.0, 300.0, 150.0));
+/// assert_eq!(bottom, Rectangle::new(0.0, 150.0, 300.0, 50.0));
+/// ```
+pub fn split<const N: usize>(
+    rect: Rectangle,
+    direction: Direction,
+    sizes: [Size; N],
+) -> [Rectangle; N] {
+    let length = match direction {
+        Direction::Horizontal => rect.width,
+        Direction::Vertical => rect.height,
+    };
+    let (fixed, weight) = sizes
+        .iter()
+        .fold((0.0, 0.0), |(fixed, weight), size| match size {
+            Size::Fixed(pixels) => (fixed + pixels, weight),
+            Size::Flexible(share) => (fixed, weight + share),
+        });
+    let spare = f32::max(length - fixed, 0.0);
+
+    let mut start = 0.0;
+    sizes.map(|size| {
+        let part = match size {
+            Size::Fixed(pixels) => pixels,
+            Size::Flexible(share) if weight > 0.0 => spare * share / weight,
+            Size::Flexible(_) => 0.0,
+        };
+        let part_rect = match direction {
+            Direction::Horizontal => Rectangle::new(rect.x + start, rect.y, part, rect.height),
+            Direction::Vertical => Rectangle::new(rect.x, rect.y + start, rect.width, part),
+        };
+        start += part;
+        part_rect
+    })
+}
+
+/// Get the biggest square which fits in a rectangle, tucked into its top left corner.
+pub fn square(rect: Rectangle) -> Rectangle {
+    let side = f32::min(rect.width, rect.height);
+    Rectangle::new(rect.x, rect.y, side, side)
 }
 
 /// Determine where the given point would be without the grid lines.
@@ -37,21 +767,21 @@ pub trait Widget {
 /// ```
 /// use raylib::math::Vector2;
 ///
-/// fn go(point: Vector2) -> Vector2 {
+/// fn go(point: Vector2) -> Option<Vector2> {
 ///     let board_size = Vector2::new(100.0, 100.0);
 ///     sudoku_solver::ui::without_gridlines(board_size, point)
 /// }
 ///
 /// assert_eq!(go(Vector2::new(20.0, 20.0)), Some(Vector2::new(10.0, 10.0)));
-/// assert_eq!(go(Vector2::new(43.0, 13.0)), Some(Vector2::new(3.0, 3.0)));
+/// assert_eq!(go(Vector2::new(43.0, 13.0)), Some(Vector2::new(23.0, 3.0)));
 /// assert_eq!(go(Vector2::new(42.0, 69.0)), None);
 /// ```
 pub fn without_gridlines(board_size: Vector2, point: Vector2) -> Option<Vector2> {
     fn single_axis(coordinate: f32, cell_size: f32) -> Option<f32> {
-        let mut retval = coordinate - LINE_WIDTH;
+        let mut retval = coordinate - line_width();
         let mut nth_cell = 0;
         while retval >= cell_size {
-            retval -= cell_size + LINE_WIDTH;
+            retval -= cell_size + line_width();
             nth_cell += 1;
         }
 
@@ -65,8 +795,8 @@ pub fn without_gridlines(board_size: Vector2, point: Vector2) -> Option<Vector2>
     let board_rect = Rectangle::new(0.0, 0.0, board_size.x, board_size.y);
     if geometry::rect_contains_point(board_rect, point) {
         let cell_size = Vector2 {
-            x: (board_size.x - LINE_WIDTH * 4.0) / 3.0,
-            y: (board_size.y - LINE_WIDTH * 4.0) / 3.0,
+            x: (board_size.x - line_width() * 4.0) / 3.0,
+            y: (board_size.y - line_width() * 4.0) / 3.0,
         };
 
         let x = single_axis(point.x, cell_size.x)?;
@@ -77,3 +807,49 @@ pub fn without_gridlines(board_size: Vector2, point: Vector2) -> Option<Vector2>
         return None;
     }
 }
+
+/// Compute the size of each cell of a board of the supplied size.
+pub fn cell_size(board_size: Vector2) -> Vector2 {
+    Vector2 {
+        x: (board_size.x - line_width() * 4.0) / 9.0,
+        y: (board_size.y - line_width() * 4.0) / 9.0,
+    }
+}
+
+/// Get how far the lines of the grid push a cell along, where `cell_index` is the cell's row or
+/// column from 0 to 8.
+///
+/// Every cell sits after the outer border and one line for each box that comes before it, so the
+/// cells in the first box are pushed along by one line width, the second box by two and the third
+/// by three. Adding this to the space taken up by the cells before it gives the cell's position.
+fn line_width_offset(cell_index: usize) -> f32 {
+    (cell_index / 3 + 1) as f32 * line_width()
+}
+
+/// Get the rectangle that a cell takes up on the screen, where `board_rect` is the rectangle that
+/// the board is drawn into and `index` counts along the rows from the top left.
+///
+/// The cells in each box are packed together, and the boxes are spaced apart by the width of the
+/// lines between them. This is the opposite of
+/// [`board::point_to_index`](crate::board::point_to_index), so anything that needs to know where a
+/// cell is drawn should ask this rather than working it out again.
+///
+/// # Examples
+///
+/// ```
+/// use raylib::math::Rectangle;
+///
+/// let board_rect = Rectangle::new(100.0, 0.0, 490.0, 490.0);
+/// let cell = sudoku_solver::ui::cell_rect(board_rect, 3);
+/// assert_eq!((cell.x, cell.y, cell.width), (270.0, 10.0, 50.0));
+/// ```
+pub fn cell_rect(board_rect: Rectangle, index: usize) -> Rectangle {
+    let cell_size = cell_size(Vector2::new(board_rect.width, board_rect.height));
+    let (row, column) = (index / 9, index % 9);
+    Rectangle {
+        x: board_rect.x + column as f32 * cell_size.x + line_width_offset(column),
+        y: board_rect.y + row as f32 * cell_size.y + line_width_offset(row),
+        width: cell_size.x,
+        height: cell_size.y,
+    }
+}
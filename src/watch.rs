@@ -0,0 +1,111 @@
+//! Noticing when a puzzle file changes, so that it can be solved again or reloaded.
+//!
+//! Files are watched by polling their modification time and size rather than by asking the
+//! operating system to report changes. That is slower to notice a change, but it works the same
+//! everywhere and needs nothing more than the standard library. A change is only reported once the
+//! file has looked the same for two checks in a row, so that a file which is still being written
+//! isn't read half-finished.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long to wait between checks on the file.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// What is known about a file at one moment. A file which can't be read has no stamp.
+type Stamp = Option<(SystemTime, u64)>;
+
+/// Keeps an eye on a single file.
+#[derive(Debug, Clone)]
+pub struct FileWatcher {
+    path: PathBuf,
+    stamp: Stamp,
+
+    /// A new stamp which has been seen once, and is waiting to be seen again.
+    pending: Option<Stamp>,
+
+    /// When the file was last checked, or [`None`] if it hasn't been since the watch began.
+    checked: Option<Instant>,
+}
+
+impl FileWatcher {
+    /// Start watching the file at the supplied path. The file as it is now counts as unchanged.
+    pub fn new(path: impl Into<PathBuf>) -> FileWatcher {
+        let path = path.into();
+        FileWatcher {
+            stamp: stamp(&path),
+            path,
+            pending: None,
+            checked: None,
+        }
+    }
+
+    /// Check whether the file has changed since the last time this returned `true`, or since the
+    /// watch began.
+    ///
+    /// This is cheap enough to call every frame, since the file is only looked at once every
+    /// [`POLL_INTERVAL`]. In between, the answer is always `false`.
+    pub fn changed(&mut self) -> bool {
+        if self
+            .checked
+            .is_some_and(|checked| checked.elapsed() < POLL_INTERVAL)
+        {
+            return false;
+        }
+        self.checked = Some(Instant::now());
+
+        let stamp = stamp(&self.path);
+        if stamp == self.stamp {
+            self.pending = None;
+            return false;
+        }
+        if self.pending != Some(stamp) {
+            self.pending = Some(stamp);
+            return false;
+        }
+        self.stamp = stamp;
+        self.pending = None;
+        true
+    }
+
+    /// Wait until the file changes.
+    pub fn wait(&mut self) {
+        while !self.changed() {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Look at the file at the supplied path.
+fn stamp(path: &Path) -> Stamp {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let path = std::env::temp_dir().join(format!("watch-test-{}.txt", std::process::id()));
+        std::fs::write(&path, "1").unwrap();
+
+        let mut watcher = FileWatcher::new(&path);
+        assert!(!watcher.changed());
+
+        // The size changes too, in case the clock is too coarse to see the new modification time.
+        std::fs::write(&path, "12").unwrap();
+        assert!(!watcher.changed());
+        std::thread::sleep(POLL_INTERVAL);
+        assert!(!watcher.changed());
+        std::thread::sleep(POLL_INTERVAL);
+        assert!(watcher.changed());
+        std::thread::sleep(POLL_INTERVAL);
+        assert!(!watcher.changed());
+
+        std::fs::remove_file(&path).unwrap();
+        watcher.wait();
+        assert!(!watcher.changed());
+    }
+}